@@ -0,0 +1,103 @@
+use std::{ops::{Add, Sub}, fmt::Display};
+
+use crate::glmath::*;
+
+/// An axis aligned bounding box described by its min and max corners.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct Aabb3<T: PartialOrd + Copy> {
+    pub min: Vec3<T>,
+    pub max: Vec3<T>
+}
+
+impl<T: PartialOrd + Copy + Display> Display for Aabb3<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{} - {}]", self.min, self.max)
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + Sub<Output = T> + Mul<Output = T>>
+    Aabb3<T>
+{
+    pub fn new(min: Vec3<T>, max: Vec3<T>) -> Aabb3<T> {
+        Aabb3::<T> { min, max }
+    }
+
+    /// Constructs the smallest box containing every point.
+    /// Returns None if no points are given.
+    pub fn from_points(points: &[Vec3<T>]) -> Option<Aabb3<T>> {
+        let first = *points.first()?;
+        let mut result = Aabb3::<T>::new(first, first);
+
+        for point in &points[1..] {
+            result.expand(*point);
+        }
+
+        Some(result)
+    }
+
+    /// Grows the box so that it contains the point.
+    pub fn expand(&mut self, point: Vec3<T>) {
+        self.min = Vec3::<T>::new(
+            T::min(self.min.x, point.x),
+            T::min(self.min.y, point.y),
+            T::min(self.min.z, point.z));
+
+        self.max = Vec3::<T>::new(
+            T::max(self.max.x, point.x),
+            T::max(self.max.y, point.y),
+            T::max(self.max.z, point.z));
+    }
+
+    /// Returns the smallest box containing both boxes.
+    pub fn merged(&self, other: &Aabb3<T>) -> Aabb3<T> {
+        let mut result = *self;
+        result.expand(other.min);
+        result.expand(other.max);
+        result
+    }
+
+    pub fn center(&self) -> Vec3<T> {
+        (self.min + self.max) * T::HALF
+    }
+
+    /// Half of the size of the box on each axis.
+    pub fn half_extents(&self) -> Vec3<T> {
+        (self.max - self.min) * T::HALF
+    }
+
+    pub fn size(&self) -> Vec3<T> {
+        self.max - self.min
+    }
+
+    pub fn surface_area(&self) -> T {
+        let size = self.size();
+        (size.x * size.y + size.y * size.z + size.z * size.x) * T::TWO
+    }
+
+    pub fn contains_point(&self, point: Vec3<T>) -> bool {
+        point.x >= self.min.x && point.x <= self.max.x &&
+            point.y >= self.min.y && point.y <= self.max.y &&
+            point.z >= self.min.z && point.z <= self.max.z
+    }
+
+    pub fn overlaps(&self, other: &Aabb3<T>) -> bool {
+        self.min.x <= other.max.x && self.max.x >= other.min.x &&
+            self.min.y <= other.max.y && self.max.y >= other.min.y &&
+            self.min.z <= other.max.z && self.max.z >= other.min.z
+    }
+
+    /// Returns the point inside the box closest to the given point.
+    pub fn closest_point(&self, point: Vec3<T>) -> Vec3<T> {
+        Vec3::<T>::new(
+            T::min(T::max(point.x, self.min.x), self.max.x),
+            T::min(T::max(point.y, self.min.y), self.max.y),
+            T::min(T::max(point.z, self.min.z), self.max.z))
+    }
+
+    /// Squared distance from the point to the box, zero for points inside.
+    pub fn distance_sq(&self, point: Vec3<T>) -> T {
+        let offset = self.closest_point(point) - point;
+        offset * offset
+    }
+}
@@ -0,0 +1,482 @@
+use std::ops::{Add, AddAssign, Sub, Neg};
+
+use crate::glmath::*;
+use crate::glmath::transform::Transform;
+
+/// A value that can be interpolated between keyframes.
+pub trait Animatable<T>: Copy {
+    fn lerp(a: &Self, b: &Self, t: T) -> Self;
+
+    /// Cubic Hermite interpolation from p0 to p1 over an interval of the
+    /// given duration. The tangents are rates of change per unit time.
+    fn hermite(p0: &Self, m0: &Self, p1: &Self, m1: &Self, t: T, duration: T) -> Self;
+
+    /// The average rate of change from one value to another over a duration.
+    fn slope(from: &Self, to: &Self, duration: T) -> Self;
+}
+
+/// How values between two keyframes are found.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum Interpolation {
+    /// Holds each keyframe's value until the next keyframe.
+    Step,
+    #[default]
+    Linear,
+    /// Cubic Hermite curves through the keyframes. Keyframes without
+    /// explicit tangents use Catmull-Rom tangents from their neighbors.
+    Cubic
+}
+
+/// How times outside of a track's keyframes are mapped onto it.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum WrapMode {
+    /// Holds the first or last value.
+    #[default]
+    Clamp,
+    /// Repeats the track from the start.
+    Loop,
+    /// Plays the track forwards then backwards.
+    PingPong
+}
+
+/// Remaps the progress through an interval between two keyframes.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum Easing {
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    ElasticIn,
+    ElasticOut,
+    ElasticInOut,
+    BackIn,
+    BackOut,
+    BackInOut,
+    BounceIn,
+    BounceOut,
+    BounceInOut
+}
+
+/// A value at a point in time.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct Keyframe<T: PartialOrd + Copy, V> {
+    pub time: T,
+    pub value: V,
+    /// The easing applied to the interval leaving this keyframe.
+    pub easing: Easing,
+    /// Incoming and outgoing tangents for cubic interpolation, per unit time.
+    pub tangents: Option<(V, V)>
+}
+
+/// A sequence of keyframes sampled by time.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Track<T: PartialOrd + Copy, V> {
+    /// Keyframes sorted by time.
+    pub keyframes: Vec<Keyframe<T, V>>,
+    pub interpolation: Interpolation,
+    pub wrap: WrapMode
+}
+
+impl Easing {
+    /// Eases a progress in [0, 1]. Elastic and back easings overshoot the range.
+    pub fn apply<T>(&self, t: T) -> T
+        where T: PartialOrd + Copy + Vectorable<T> +
+            Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+            Div<Output = T> + Neg<Output = T>
+    {
+        let c = |value: f64| T::from_f64(value);
+        let pow2 = |x: T| (x * c(std::f64::consts::LN_2)).exp();
+
+        // Constants from Robert Penner's easing equations.
+        let back = c(1.70158);
+        let back_in_out = c(1.70158 * 1.525);
+        let elastic = T::TWO * T::PI / c(3.0);
+        let elastic_in_out = T::TWO * T::PI / c(4.5);
+
+        match self {
+            Easing::Linear => t,
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => T::ONE - (T::ONE - t) * (T::ONE - t),
+            Easing::QuadInOut => {
+                if t < T::HALF {
+                    T::TWO * t * t
+                }
+                else {
+                    let u = T::TWO - T::TWO * t;
+                    T::ONE - u * u * T::HALF
+                }
+            }
+            Easing::CubicIn => t * t * t,
+            Easing::CubicOut => {
+                let u = T::ONE - t;
+                T::ONE - u * u * u
+            }
+            Easing::CubicInOut => {
+                if t < T::HALF {
+                    c(4.0) * t * t * t
+                }
+                else {
+                    let u = T::TWO - T::TWO * t;
+                    T::ONE - u * u * u * T::HALF
+                }
+            }
+            Easing::ElasticIn => {
+                if t <= T::ZERO || t >= T::ONE {
+                    return t;
+                }
+
+                -pow2(c(10.0) * t - c(10.0)) * ((c(10.0) * t - c(10.75)) * elastic).sin()
+            }
+            Easing::ElasticOut => {
+                if t <= T::ZERO || t >= T::ONE {
+                    return t;
+                }
+
+                pow2(-c(10.0) * t) * ((c(10.0) * t - c(0.75)) * elastic).sin() + T::ONE
+            }
+            Easing::ElasticInOut => {
+                if t <= T::ZERO || t >= T::ONE {
+                    return t;
+                }
+
+                let wave = ((c(20.0) * t - c(11.125)) * elastic_in_out).sin();
+
+                if t < T::HALF {
+                    -pow2(c(20.0) * t - c(10.0)) * wave * T::HALF
+                }
+                else {
+                    pow2(c(10.0) - c(20.0) * t) * wave * T::HALF + T::ONE
+                }
+            }
+            Easing::BackIn => (back + T::ONE) * t * t * t - back * t * t,
+            Easing::BackOut => {
+                let u = t - T::ONE;
+                T::ONE + (back + T::ONE) * u * u * u + back * u * u
+            }
+            Easing::BackInOut => {
+                if t < T::HALF {
+                    let u = T::TWO * t;
+                    u * u * ((back_in_out + T::ONE) * u - back_in_out) * T::HALF
+                }
+                else {
+                    let u = T::TWO * t - T::TWO;
+                    (u * u * ((back_in_out + T::ONE) * u + back_in_out) + T::TWO) * T::HALF
+                }
+            }
+            Easing::BounceIn => T::ONE - bounce_out(T::ONE - t),
+            Easing::BounceOut => bounce_out(t),
+            Easing::BounceInOut => {
+                if t < T::HALF {
+                    (T::ONE - bounce_out(T::ONE - T::TWO * t)) * T::HALF
+                }
+                else {
+                    (T::ONE + bounce_out(T::TWO * t - T::ONE)) * T::HALF
+                }
+            }
+        }
+    }
+}
+
+impl<T: PartialOrd + Copy, V> Keyframe<T, V> {
+    pub fn new(time: T, value: V) -> Keyframe<T, V> {
+        Keyframe::<T, V> { time, value, easing: Easing::Linear, tangents: None }
+    }
+
+    pub fn with_tangents(time: T, value: V, in_tangent: V, out_tangent: V) -> Keyframe<T, V> {
+        Keyframe::<T, V> { time, value, easing: Easing::Linear, tangents: Some((in_tangent, out_tangent)) }
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + Neg<Output = T>,
+    V: Animatable<T>>
+    Track<T, V>
+{
+    pub fn new(interpolation: Interpolation, wrap: WrapMode) -> Track<T, V> {
+        Track::<T, V> { keyframes: Vec::new(), interpolation, wrap }
+    }
+
+    /// Adds a keyframe in time order, replacing any keyframe at the same time.
+    pub fn insert(&mut self, keyframe: Keyframe<T, V>) {
+        let index = self.keyframes.partition_point(|key| key.time < keyframe.time);
+
+        if self.keyframes.get(index).is_some_and(|key| key.time == keyframe.time) {
+            self.keyframes[index] = keyframe;
+        }
+        else {
+            self.keyframes.insert(index, keyframe);
+        }
+    }
+
+    pub fn start_time(&self) -> T {
+        self.keyframes.first().map_or(T::ZERO, |key| key.time)
+    }
+
+    pub fn end_time(&self) -> T {
+        self.keyframes.last().map_or(T::ZERO, |key| key.time)
+    }
+
+    pub fn duration(&self) -> T {
+        self.end_time() - self.start_time()
+    }
+
+    /// Maps a time onto the track's keyframes by its wrap mode.
+    pub fn wrap_time(&self, time: T) -> T {
+        let start = self.start_time();
+        let duration = self.duration();
+
+        if duration <= T::ZERO {
+            return start;
+        }
+
+        let offset = time - start;
+
+        match self.wrap {
+            WrapMode::Clamp => T::min(T::max(time, start), self.end_time()),
+            WrapMode::Loop => start + offset - duration * floor(offset / duration),
+            WrapMode::PingPong => {
+                let period = duration * T::TWO;
+                let local = offset - period * floor(offset / period);
+
+                if local > duration { start + period - local } else { start + local }
+            }
+        }
+    }
+
+    /// The value at a time, or None if the track has no keyframes.
+    pub fn sample(&self, time: T) -> Option<V> {
+        let first = self.keyframes.first()?;
+
+        if self.keyframes.len() == 1 {
+            return Some(first.value);
+        }
+
+        let time = self.wrap_time(time);
+        let next = self.keyframes.partition_point(|key| key.time <= time).clamp(1, self.keyframes.len() - 1);
+        let index = next - 1;
+
+        let a = &self.keyframes[index];
+        let b = &self.keyframes[next];
+        let duration = b.time - a.time;
+        let t = T::min(T::max((time - a.time) / duration, T::ZERO), T::ONE);
+        let t = a.easing.apply(t);
+
+        Some(match self.interpolation {
+            Interpolation::Step => if time >= b.time { b.value } else { a.value },
+            Interpolation::Linear => V::lerp(&a.value, &b.value, t),
+            Interpolation::Cubic => {
+                let m0 = a.tangents.map_or_else(|| self.auto_tangent(index), |tangents| tangents.1);
+                let m1 = b.tangents.map_or_else(|| self.auto_tangent(next), |tangents| tangents.0);
+
+                V::hermite(&a.value, &m0, &b.value, &m1, t, duration)
+            }
+        })
+    }
+
+    /// The Catmull-Rom tangent of a keyframe, or the slope towards its only
+    /// neighbor at either end of the track.
+    fn auto_tangent(&self, index: usize) -> V {
+        let previous = &self.keyframes[index.saturating_sub(1)];
+        let next = &self.keyframes[usize::min(index + 1, self.keyframes.len() - 1)];
+
+        V::slope(&previous.value, &next.value, next.time - previous.time)
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    Track<T, Transform<T>>
+{
+    /// Creates a transform track from matrix keyframes, decomposed into
+    /// translation, rotation and scale so they interpolate without shearing.
+    pub fn from_matrices(keys: &[(T, Mat44<T>)], interpolation: Interpolation, wrap: WrapMode) -> Track<T, Transform<T>> {
+        let mut track = Track::<T, Transform<T>>::new(interpolation, wrap);
+
+        for (time, matrix) in keys {
+            track.insert(Keyframe::<T, Transform<T>>::new(*time, Transform::<T>::from_matrix(matrix)));
+        }
+
+        track
+    }
+
+    /// The transform at a time as a matrix.
+    pub fn sample_matrix(&self, time: T) -> Option<Mat44<T>> {
+        self.sample(time).map(|transform| transform.to_matrix())
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>>
+    Animatable<T> for T
+{
+    fn lerp(a: &T, b: &T, t: T) -> T {
+        linear(*a, *b, t)
+    }
+
+    fn hermite(p0: &T, m0: &T, p1: &T, m1: &T, t: T, duration: T) -> T {
+        cubic(*p0, *m0, *p1, *m1, t, duration)
+    }
+
+    fn slope(from: &T, to: &T, duration: T) -> T {
+        (*to - *from) * (T::ONE / duration)
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>>
+    Animatable<T> for Vec2<T>
+{
+    fn lerp(a: &Vec2<T>, b: &Vec2<T>, t: T) -> Vec2<T> {
+        linear(*a, *b, t)
+    }
+
+    fn hermite(p0: &Vec2<T>, m0: &Vec2<T>, p1: &Vec2<T>, m1: &Vec2<T>, t: T, duration: T) -> Vec2<T> {
+        cubic(*p0, *m0, *p1, *m1, t, duration)
+    }
+
+    fn slope(from: &Vec2<T>, to: &Vec2<T>, duration: T) -> Vec2<T> {
+        (*to - *from) * (T::ONE / duration)
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>>
+    Animatable<T> for Vec3<T>
+{
+    fn lerp(a: &Vec3<T>, b: &Vec3<T>, t: T) -> Vec3<T> {
+        linear(*a, *b, t)
+    }
+
+    fn hermite(p0: &Vec3<T>, m0: &Vec3<T>, p1: &Vec3<T>, m1: &Vec3<T>, t: T, duration: T) -> Vec3<T> {
+        cubic(*p0, *m0, *p1, *m1, t, duration)
+    }
+
+    fn slope(from: &Vec3<T>, to: &Vec3<T>, duration: T) -> Vec3<T> {
+        (*to - *from) * (T::ONE / duration)
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>>
+    Animatable<T> for Vec4<T>
+{
+    fn lerp(a: &Vec4<T>, b: &Vec4<T>, t: T) -> Vec4<T> {
+        linear(*a, *b, t)
+    }
+
+    fn hermite(p0: &Vec4<T>, m0: &Vec4<T>, p1: &Vec4<T>, m1: &Vec4<T>, t: T, duration: T) -> Vec4<T> {
+        cubic(*p0, *m0, *p1, *m1, t, duration)
+    }
+
+    fn slope(from: &Vec4<T>, to: &Vec4<T>, duration: T) -> Vec4<T> {
+        (*to - *from) * (T::ONE / duration)
+    }
+}
+
+// Rotations take the shorter arc between keyframes.
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    Animatable<T> for Quat<T>
+{
+    fn lerp(a: &Quat<T>, b: &Quat<T>, t: T) -> Quat<T> {
+        let b = if a.dot(b) < T::ZERO { -*b } else { *b };
+        Quat::<T>::slerp_arc(*a, b, t)
+    }
+
+    /// Interpolates the components then normalizes, as glTF does.
+    fn hermite(p0: &Quat<T>, m0: &Quat<T>, p1: &Quat<T>, m1: &Quat<T>, t: T, duration: T) -> Quat<T> {
+        let p1 = if p0.dot(p1) < T::ZERO { -*p1 } else { *p1 };
+        cubic(*p0, *m0, p1, *m1, t, duration).get_normalized()
+    }
+
+    fn slope(from: &Quat<T>, to: &Quat<T>, duration: T) -> Quat<T> {
+        let to = if from.dot(to) < T::ZERO { -*to } else { *to };
+        (to - *from) * (T::ONE / duration)
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    Animatable<T> for Transform<T>
+{
+    fn lerp(a: &Transform<T>, b: &Transform<T>, t: T) -> Transform<T> {
+        Transform::<T>::new(
+            Vec3::<T>::lerp(&a.translation, &b.translation, t),
+            Quat::<T>::lerp(&a.rotation, &b.rotation, t),
+            Vec3::<T>::lerp(&a.scale, &b.scale, t))
+    }
+
+    fn hermite(p0: &Transform<T>, m0: &Transform<T>, p1: &Transform<T>, m1: &Transform<T>, t: T, duration: T) -> Transform<T> {
+        Transform::<T>::new(
+            Vec3::<T>::hermite(&p0.translation, &m0.translation, &p1.translation, &m1.translation, t, duration),
+            Quat::<T>::hermite(&p0.rotation, &m0.rotation, &p1.rotation, &m1.rotation, t, duration),
+            Vec3::<T>::hermite(&p0.scale, &m0.scale, &p1.scale, &m1.scale, t, duration))
+    }
+
+    fn slope(from: &Transform<T>, to: &Transform<T>, duration: T) -> Transform<T> {
+        Transform::<T>::new(
+            Vec3::<T>::slope(&from.translation, &to.translation, duration),
+            Quat::<T>::slope(&from.rotation, &to.rotation, duration),
+            Vec3::<T>::slope(&from.scale, &to.scale, duration))
+    }
+}
+
+fn linear<T, V>(a: V, b: V, t: T) -> V
+    where V: Add<Output = V> + Sub<Output = V> + Mul<T, Output = V> + Copy
+{
+    a + (b - a) * t
+}
+
+/// Hermite basis functions, with tangents scaled from per unit time to
+/// per unit of interpolation parameter.
+fn cubic<T, V>(p0: V, m0: V, p1: V, m1: V, t: T, duration: T) -> V
+    where T: Copy + Vectorable<T> + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+    V: Add<Output = V> + Mul<T, Output = V> + Copy
+{
+    let t2 = t * t;
+    let t3 = t2 * t;
+    let three = T::ONE + T::TWO;
+
+    p0 * (T::TWO * t3 - three * t2 + T::ONE) +
+        m0 * ((t3 - T::TWO * t2 + t) * duration) +
+        p1 * (three * t2 - T::TWO * t3) +
+        m1 * ((t3 - t2) * duration)
+}
+
+fn bounce_out<T>(t: T) -> T
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>
+{
+    let c = |value: f64| T::from_f64(value);
+    let n1 = c(7.5625);
+    let d1 = c(2.75);
+
+    if t < T::ONE / d1 {
+        n1 * t * t
+    }
+    else if t < T::TWO / d1 {
+        let u = t - c(1.5) / d1;
+        n1 * u * u + c(0.75)
+    }
+    else if t < c(2.5) / d1 {
+        let u = t - c(2.25) / d1;
+        n1 * u * u + c(0.9375)
+    }
+    else {
+        let u = t - c(2.625) / d1;
+        n1 * u * u + c(0.984375)
+    }
+}
+
+/// The largest whole number not greater than x.
+fn floor<T: Vectorable<T>>(x: T) -> T {
+    T::from_f64(x.to_f64().floor())
+}
@@ -0,0 +1,439 @@
+use std::ops::{Add, Sub, Neg};
+
+use crate::glmath::*;
+use crate::glmath::frustum::Containment;
+
+/// A node of the flattened hierarchy. Nodes are stored depth first so the
+/// left child of an interior node always directly follows it.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct BvhNode<T: PartialOrd + Copy> {
+    pub bounds: Aabb3<T>,
+    /// Index of the right child for interior nodes, or of the first
+    /// primitive index for leaves.
+    pub offset: usize,
+    /// Number of primitives in a leaf, zero for interior nodes.
+    pub count: usize
+}
+
+impl<T: PartialOrd + Copy> BvhNode<T> {
+    pub fn is_leaf(&self) -> bool {
+        self.count > 0
+    }
+}
+
+/// A bounding volume hierarchy over primitives given by their bounds.
+/// Queries report primitives by their index in the list the tree was
+/// built from.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Bvh<T: PartialOrd + Copy> {
+    pub nodes: Vec<BvhNode<T>>,
+    /// Primitive indices referenced by the leaves.
+    pub indices: Vec<usize>
+}
+
+/// Leaves with at most this many primitives are not split unless the
+/// surface area heuristic says splitting is cheaper.
+const MAX_LEAF_SIZE: usize = 4;
+
+/// How the split of a node is chosen.
+#[derive(Copy, Clone)]
+enum Split {
+    /// Evaluate the surface area heuristic at every primitive.
+    Sweep,
+    /// Evaluate the surface area heuristic at the borders of equally sized bins.
+    Binned(usize)
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    Bvh<T>
+{
+    /// Builds a tree by evaluating the surface area heuristic for every
+    /// possible split. Gives the best trees but is the slowest to build.
+    pub fn build_sah(bounds: &[Aabb3<T>]) -> Bvh<T> {
+        Bvh::<T>::build(bounds, Split::Sweep)
+    }
+
+    /// Builds a tree by evaluating the surface area heuristic at the borders
+    /// of a fixed number of bins per axis.
+    pub fn build_binned(bounds: &[Aabb3<T>], bins: usize) -> Bvh<T> {
+        Bvh::<T>::build(bounds, Split::Binned(usize::max(bins, 2)))
+    }
+
+    fn build(bounds: &[Aabb3<T>], split: Split) -> Bvh<T> {
+        let mut bvh = Bvh::<T> {
+            nodes: Vec::with_capacity(bounds.len() * 2),
+            indices: (0..bounds.len()).collect()
+        };
+
+        if !bounds.is_empty() {
+            let centroids: Vec<Vec3<T>> = bounds.iter().map(|b| b.center()).collect();
+            bvh.build_node(bounds, &centroids, 0, bounds.len(), split);
+        }
+
+        bvh
+    }
+
+    fn build_node(&mut self, bounds: &[Aabb3<T>], centroids: &[Vec3<T>],
+        start: usize, end: usize, split: Split) -> usize
+    {
+        let node_index = self.nodes.len();
+        let node_bounds = self.bounds_of(bounds, start, end);
+
+        self.nodes.push(BvhNode::<T> {
+            bounds: node_bounds,
+            offset: start,
+            count: end - start
+        });
+
+        let count = end - start;
+        if count == 1 {
+            return node_index;
+        }
+
+        let best = match split {
+            Split::Sweep => self.find_sweep_split(bounds, centroids, start, end),
+            Split::Binned(bins) => self.find_binned_split(bounds, centroids, start, end, bins)
+        };
+
+        // Splitting costs one traversal step plus the expected cost of the children.
+        let leaf_cost = T::from_f64(count as f64);
+        let mid = match best {
+            Some((mid, cost)) if count > MAX_LEAF_SIZE || T::ONE + cost / node_bounds.surface_area() < leaf_cost => mid,
+            None if count > MAX_LEAF_SIZE => self.split_median(centroids, start, end, &node_bounds),
+            _ => return node_index
+        };
+
+        self.build_node(bounds, centroids, start, mid, split);
+        let right = self.build_node(bounds, centroids, mid, end, split);
+
+        self.nodes[node_index].offset = right;
+        self.nodes[node_index].count = 0;
+
+        node_index
+    }
+
+    fn bounds_of(&self, bounds: &[Aabb3<T>], start: usize, end: usize) -> Aabb3<T> {
+        let mut result = bounds[self.indices[start]];
+
+        for index in &self.indices[start + 1..end] {
+            result = result.merged(&bounds[*index]);
+        }
+
+        result
+    }
+
+    fn sort_by_axis(&mut self, centroids: &[Vec3<T>], start: usize, end: usize, axis: usize) {
+        self.indices[start..end].sort_by(|a, b| {
+            centroids[*a][axis].partial_cmp(&centroids[*b][axis]).unwrap_or(std::cmp::Ordering::Equal)
+        });
+    }
+
+    /// Sorts along each axis and sweeps over the sorted primitives. Leaves
+    /// the indices partitioned along the best axis and returns the split
+    /// position and its unnormalized cost.
+    fn find_sweep_split(&mut self, bounds: &[Aabb3<T>], centroids: &[Vec3<T>],
+        start: usize, end: usize) -> Option<(usize, T)>
+    {
+        let count = end - start;
+        let mut best: Option<(usize, usize, T)> = None;
+        let mut right_areas = vec![T::ZERO; count];
+
+        for axis in 0..3 {
+            self.sort_by_axis(centroids, start, end, axis);
+
+            let mut right = bounds[self.indices[end - 1]];
+            for i in (1..count).rev() {
+                right = right.merged(&bounds[self.indices[start + i]]);
+                right_areas[i] = right.surface_area();
+            }
+
+            let mut left = bounds[self.indices[start]];
+            for i in 1..count {
+                let cost = left.surface_area() * T::from_f64(i as f64) +
+                    right_areas[i] * T::from_f64((count - i) as f64);
+
+                if best.is_none_or(|(_, _, best_cost)| cost < best_cost) {
+                    best = Some((axis, start + i, cost));
+                }
+
+                left = left.merged(&bounds[self.indices[start + i]]);
+            }
+        }
+
+        let (axis, mid, cost) = best?;
+        self.sort_by_axis(centroids, start, end, axis);
+
+        Some((mid, cost))
+    }
+
+    /// Sorts the primitives into bins along each axis by their centroids
+    /// and evaluates the splits between bins. Leaves the indices partitioned
+    /// along the best axis and returns the split position and its cost.
+    fn find_binned_split(&mut self, bounds: &[Aabb3<T>], centroids: &[Vec3<T>],
+        start: usize, end: usize, bins: usize) -> Option<(usize, T)>
+    {
+        let mut centroid_bounds = Aabb3::<T>::new(centroids[self.indices[start]], centroids[self.indices[start]]);
+        for index in &self.indices[start + 1..end] {
+            centroid_bounds.expand(centroids[*index]);
+        }
+
+        let mut best: Option<(usize, usize, T)> = None;
+
+        for axis in [0, 1, 2] {
+            let min = centroid_bounds.min[axis];
+            let extent = centroid_bounds.max[axis] - min;
+
+            if extent <= T::ZERO {
+                continue;
+            }
+
+            let mut bin_bounds: Vec<Option<Aabb3<T>>> = vec![None; bins];
+            let mut bin_counts = vec![0; bins];
+
+            for index in &self.indices[start..end] {
+                let bin = bin_of(centroids[*index][axis], min, extent, bins);
+                bin_counts[bin] += 1;
+                bin_bounds[bin] = Some(match bin_bounds[bin] {
+                    Some(b) => b.merged(&bounds[*index]),
+                    None => bounds[*index]
+                });
+            }
+
+            let mut right_areas = vec![T::ZERO; bins];
+            let mut right: Option<Aabb3<T>> = None;
+            for bin in (1..bins).rev() {
+                right = merge_optional(right, bin_bounds[bin]);
+                right_areas[bin] = right.map_or(T::ZERO, |b| b.surface_area());
+            }
+
+            let mut left: Option<Aabb3<T>> = None;
+            let mut left_count = 0;
+            for bin in 1..bins {
+                left = merge_optional(left, bin_bounds[bin - 1]);
+                left_count += bin_counts[bin - 1];
+                let right_count = (end - start) - left_count;
+
+                if left_count == 0 || right_count == 0 {
+                    continue;
+                }
+
+                let cost = left.map_or(T::ZERO, |b| b.surface_area()) * T::from_f64(left_count as f64) +
+                    right_areas[bin] * T::from_f64(right_count as f64);
+
+                if best.is_none_or(|(_, _, best_cost)| cost < best_cost) {
+                    best = Some((axis, bin, cost));
+                }
+            }
+        }
+
+        let (axis, split_bin, cost) = best?;
+        let min = centroid_bounds.min[axis];
+        let extent = centroid_bounds.max[axis] - min;
+
+        let (left, right): (Vec<usize>, Vec<usize>) = self.indices[start..end]
+            .iter()
+            .partition(|index| bin_of(centroids[**index][axis], min, extent, bins) < split_bin);
+
+        let mid = start + left.len();
+        self.indices[start..mid].copy_from_slice(&left);
+        self.indices[mid..end].copy_from_slice(&right);
+
+        Some((mid, cost))
+    }
+
+    /// Splits at the median of the longest axis of the centroids. Used when
+    /// no split separates the primitives, such as when centroids coincide.
+    fn split_median(&mut self, centroids: &[Vec3<T>], start: usize, end: usize, bounds: &Aabb3<T>) -> usize {
+        let size = bounds.size();
+        let axis = if size.x > size.y && size.x > size.z { 0 } else if size.y > size.z { 1 } else { 2 };
+
+        self.sort_by_axis(centroids, start, end, axis);
+        start + (end - start) / 2
+    }
+
+    /// Recomputes every node's bounds after the primitives moved.
+    /// The tree's structure is kept, so its quality degrades with large motion.
+    pub fn refit(&mut self, bounds: &[Aabb3<T>]) {
+        // Children are stored after their parents.
+        for node_index in (0..self.nodes.len()).rev() {
+            let node = self.nodes[node_index];
+
+            self.nodes[node_index].bounds = if node.is_leaf() {
+                self.bounds_of(bounds, node.offset, node.offset + node.count)
+            }
+            else {
+                self.nodes[node_index + 1].bounds.merged(&self.nodes[node.offset].bounds)
+            };
+        }
+    }
+
+    /// Finds the closest primitive hit by the ray. The callback intersects
+    /// the ray with a primitive and returns the hit distance. Returns the
+    /// primitive index and distance of the closest hit.
+    pub fn closest_hit<F>(&self, ray: &Ray3<T>, mut intersect: F) -> Option<(usize, T)>
+        where F: FnMut(usize) -> Option<T>
+    {
+        let mut closest: Option<(usize, T)> = None;
+        let mut stack: Vec<(usize, T)> = Vec::with_capacity(64);
+
+        if let Some((enter, _)) = self.nodes.first().and_then(|n| ray.intersect_aabb_range(&n.bounds)) {
+            stack.push((0, enter));
+        }
+
+        while let Some((node_index, enter)) = stack.pop() {
+            if closest.is_some_and(|(_, dist)| enter > dist) {
+                continue;
+            }
+
+            let node = &self.nodes[node_index];
+
+            if node.is_leaf() {
+                for index in &self.indices[node.offset..node.offset + node.count] {
+                    if let Some(dist) = intersect(*index) {
+                        if dist >= T::ZERO && closest.is_none_or(|(_, best)| dist < best) {
+                            closest = Some((*index, dist));
+                        }
+                    }
+                }
+
+                continue;
+            }
+
+            let left = ray.intersect_aabb_range(&self.nodes[node_index + 1].bounds).map(|r| (node_index + 1, r.0));
+            let right = ray.intersect_aabb_range(&self.nodes[node.offset].bounds).map(|r| (node.offset, r.0));
+
+            // Push the nearer child last so it is visited first.
+            match (left, right) {
+                (Some(l), Some(r)) if l.1 < r.1 => { stack.push(r); stack.push(l); }
+                (Some(l), Some(r)) => { stack.push(l); stack.push(r); }
+                (Some(l), None) => stack.push(l),
+                (None, Some(r)) => stack.push(r),
+                (None, None) => {}
+            }
+        }
+
+        closest
+    }
+
+    /// Finds any primitive hit by the ray closer than max_distance, stopping
+    /// at the first one. Useful for shadow and visibility rays.
+    pub fn any_hit<F>(&self, ray: &Ray3<T>, max_distance: T, mut intersect: F) -> Option<usize>
+        where F: FnMut(usize) -> Option<T>
+    {
+        let mut stack: Vec<usize> = Vec::with_capacity(64);
+
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+
+            match ray.intersect_aabb_range(&node.bounds) {
+                Some((enter, _)) if enter <= max_distance => {}
+                _ => continue
+            }
+
+            if node.is_leaf() {
+                for index in &self.indices[node.offset..node.offset + node.count] {
+                    if intersect(*index).is_some_and(|dist| dist >= T::ZERO && dist <= max_distance) {
+                        return Some(*index);
+                    }
+                }
+            }
+            else {
+                stack.push(node.offset);
+                stack.push(node_index + 1);
+            }
+        }
+
+        None
+    }
+
+    /// Calls back with every primitive whose leaf overlaps the box. The
+    /// primitives themselves are not tested against the box.
+    pub fn query_aabb<F>(&self, aabb: &Aabb3<T>, mut callback: F)
+        where F: FnMut(usize)
+    {
+        let mut stack: Vec<usize> = Vec::with_capacity(64);
+
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+
+            if !node.bounds.overlaps(aabb) {
+                continue;
+            }
+
+            if node.is_leaf() {
+                for index in &self.indices[node.offset..node.offset + node.count] {
+                    callback(*index);
+                }
+            }
+            else {
+                stack.push(node.offset);
+                stack.push(node_index + 1);
+            }
+        }
+    }
+
+    /// Calls back with every primitive whose leaf is inside or intersects
+    /// the frustum. Subtrees entirely inside are reported without testing.
+    pub fn query_frustum<F>(&self, frustum: &Frustum<T>, mut callback: F)
+        where F: FnMut(usize)
+    {
+        let mut stack: Vec<(usize, bool)> = Vec::with_capacity(64);
+
+        if !self.nodes.is_empty() {
+            stack.push((0, false));
+        }
+
+        while let Some((node_index, inside)) = stack.pop() {
+            let node = &self.nodes[node_index];
+
+            let inside = inside || match frustum.test_aabb(&node.bounds) {
+                Containment::Outside => continue,
+                Containment::Inside => true,
+                Containment::Intersect => false
+            };
+
+            if node.is_leaf() {
+                for index in &self.indices[node.offset..node.offset + node.count] {
+                    callback(*index);
+                }
+            }
+            else {
+                stack.push((node.offset, inside));
+                stack.push((node_index + 1, inside));
+            }
+        }
+    }
+}
+
+/// The bin a centroid coordinate falls into.
+fn bin_of<T: PartialOrd + Copy + Vectorable<T> +
+    Sub<Output = T> + Mul<Output = T> + Div<Output = T>>
+    (value: T, min: T, extent: T, bins: usize) -> usize
+{
+    let scaled = (value - min) / extent * T::from_f64(bins as f64);
+
+    // The cast saturates negative values to zero, and the last bin is
+    // inclusive of the maximum.
+    usize::min(scaled.to_f64() as usize, bins - 1)
+}
+
+fn merge_optional<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + Sub<Output = T> + Mul<Output = T>>
+    (a: Option<Aabb3<T>>, b: Option<Aabb3<T>>) -> Option<Aabb3<T>>
+{
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.merged(&b)),
+        (a, None) => a,
+        (None, b) => b
+    }
+}
@@ -0,0 +1,239 @@
+use std::ops::{Add, Sub, Neg};
+
+use crate::glmath::*;
+
+/// The closest pair of points between two shapes.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct ClosestPoints<T: PartialOrd + Copy> {
+    /// The point on the first shape.
+    pub on_a: Vec3<T>,
+    /// The point on the second shape.
+    pub on_b: Vec3<T>,
+    /// Squared distance between the two points.
+    pub distance_sq: T
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T>>
+    ClosestPoints<T>
+{
+    pub fn new(on_a: Vec3<T>, on_b: Vec3<T>) -> ClosestPoints<T> {
+        ClosestPoints::<T> {
+            on_a,
+            on_b,
+            distance_sq: (on_b - on_a).length_sq()
+        }
+    }
+
+    pub fn distance(&self) -> T {
+        self.distance_sq.sqrt()
+    }
+}
+
+/// Closest points between the point p and the segment ab.
+pub fn point_segment<T>(p: Vec3<T>, a: Vec3<T>, b: Vec3<T>) -> ClosestPoints<T>
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T>
+{
+    let ab = b - a;
+    let len_sq = ab * ab;
+
+    if len_sq == T::ZERO {
+        return ClosestPoints::<T>::new(p, a);
+    }
+
+    let t = T::min(T::max(((p - a) * ab) / len_sq, T::ZERO), T::ONE);
+    ClosestPoints::<T>::new(p, a + ab * t)
+}
+
+/// Closest points between the segments p1 q1 and p2 q2.
+pub fn segment_segment<T>(p1: Vec3<T>, q1: Vec3<T>, p2: Vec3<T>, q2: Vec3<T>) -> ClosestPoints<T>
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T> + Neg<Output = T>
+{
+    let clamp = |x: T| T::min(T::max(x, T::ZERO), T::ONE);
+
+    let d1 = q1 - p1;
+    let d2 = q2 - p2;
+    let r = p1 - p2;
+    let a = d1 * d1;
+    let e = d2 * d2;
+    let f = d2 * r;
+
+    let s;
+    let t;
+
+    if a <= T::EPSILON && e <= T::EPSILON {
+        return ClosestPoints::<T>::new(p1, p2);
+    }
+
+    if a <= T::EPSILON {
+        s = T::ZERO;
+        t = clamp(f / e);
+    }
+    else {
+        let c = d1 * r;
+
+        if e <= T::EPSILON {
+            t = T::ZERO;
+            s = clamp(-c / a);
+        }
+        else {
+            // Closest points of the infinite lines, then clamp each
+            // parameter and recompute the other against it.
+            let b = d1 * d2;
+            let denom = a * e - b * b;
+
+            let s_line = if denom != T::ZERO { clamp((b * f - c * e) / denom) } else { T::ZERO };
+            let t_line = (b * s_line + f) / e;
+
+            if t_line < T::ZERO {
+                t = T::ZERO;
+                s = clamp(-c / a);
+            }
+            else if t_line > T::ONE {
+                t = T::ONE;
+                s = clamp((b - c) / a);
+            }
+            else {
+                t = t_line;
+                s = s_line;
+            }
+        }
+    }
+
+    ClosestPoints::<T>::new(p1 + d1 * s, p2 + d2 * t)
+}
+
+/// Closest points between the point p and the triangle abc, found by
+/// classifying p against the triangle's Voronoi regions as in Ericson's
+/// Real-Time Collision Detection.
+pub fn point_triangle<T>(p: Vec3<T>, a: Vec3<T>, b: Vec3<T>, c: Vec3<T>) -> ClosestPoints<T>
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T>
+{
+    let ab = b - a;
+    let ac = c - a;
+
+    // Vertex region a.
+    let ap = p - a;
+    let d1 = ab * ap;
+    let d2 = ac * ap;
+    if d1 <= T::ZERO && d2 <= T::ZERO {
+        return ClosestPoints::<T>::new(p, a);
+    }
+
+    // Vertex region b.
+    let bp = p - b;
+    let d3 = ab * bp;
+    let d4 = ac * bp;
+    if d3 >= T::ZERO && d4 <= d3 {
+        return ClosestPoints::<T>::new(p, b);
+    }
+
+    // Edge region ab.
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= T::ZERO && d1 >= T::ZERO && d3 <= T::ZERO {
+        let v = d1 / (d1 - d3);
+        return ClosestPoints::<T>::new(p, a + ab * v);
+    }
+
+    // Vertex region c.
+    let cp = p - c;
+    let d5 = ab * cp;
+    let d6 = ac * cp;
+    if d6 >= T::ZERO && d5 <= d6 {
+        return ClosestPoints::<T>::new(p, c);
+    }
+
+    // Edge region ac.
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= T::ZERO && d2 >= T::ZERO && d6 <= T::ZERO {
+        let w = d2 / (d2 - d6);
+        return ClosestPoints::<T>::new(p, a + ac * w);
+    }
+
+    // Edge region bc.
+    let va = d3 * d6 - d5 * d4;
+    if va <= T::ZERO && (d4 - d3) >= T::ZERO && (d5 - d6) >= T::ZERO {
+        let w = (d4 - d3) / ((d4 - d3) + (d5 - d6));
+        return ClosestPoints::<T>::new(p, b + (c - b) * w);
+    }
+
+    // Face region.
+    let denom = T::ONE / (va + vb + vc);
+    let v = vb * denom;
+    let w = vc * denom;
+
+    ClosestPoints::<T>::new(p, a + ab * v + ac * w)
+}
+
+/// Closest points between the point p and an oriented box.
+/// Points inside the box are their own closest point.
+pub fn point_obb<T>(p: Vec3<T>, obb: &Obb<T>) -> ClosestPoints<T>
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T> + Neg<Output = T>
+{
+    let offset = p - obb.center;
+    let mut result = obb.center;
+
+    for i in 0..3 {
+        let axis = obb.orientation.column(i);
+        let extent = obb.half_extents[i];
+        let dist = T::min(T::max(offset * axis, -extent), extent);
+
+        result = result + axis * dist;
+    }
+
+    ClosestPoints::<T>::new(p, result)
+}
+
+/// Closest points between two triangles. Intersecting triangles return
+/// a point on their intersection with zero distance.
+pub fn triangle_triangle<T>(tri_a: &[Vec3<T>; 3], tri_b: &[Vec3<T>; 3]) -> ClosestPoints<T>
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T> + Neg<Output = T>
+{
+    // Any edge passing through the other triangle means they intersect.
+    for (from, to) in [(tri_a, tri_b), (tri_b, tri_a)] {
+        for i in 0..3 {
+            let start = from[i];
+            let end = from[(i + 1) % 3];
+            let edge = Ray3::<T>::new(start, end - start);
+
+            if let Some(hit) = edge.intersect_triangle(to[0], to[1], to[2]) {
+                if hit.distance <= T::ONE {
+                    let point = edge.at(hit.distance);
+                    return ClosestPoints::<T>::new(point, point);
+                }
+            }
+        }
+    }
+
+    // Otherwise the closest points involve a vertex and a face, or two edges.
+    let mut best = point_triangle(tri_a[0], tri_b[0], tri_b[1], tri_b[2]);
+    let mut consider = |candidate: ClosestPoints<T>| {
+        if candidate.distance_sq < best.distance_sq {
+            best = candidate;
+        }
+    };
+
+    for i in 0..3 {
+        consider(point_triangle(tri_a[i], tri_b[0], tri_b[1], tri_b[2]));
+
+        let from_b = point_triangle(tri_b[i], tri_a[0], tri_a[1], tri_a[2]);
+        consider(ClosestPoints::<T>::new(from_b.on_b, from_b.on_a));
+
+        for j in 0..3 {
+            consider(segment_segment(tri_a[i], tri_a[(i + 1) % 3], tri_b[j], tri_b[(j + 1) % 3]));
+        }
+    }
+
+    best
+}
@@ -0,0 +1,793 @@
+use std::ops::{Add, AddAssign, Sub, Neg};
+
+use crate::glmath::*;
+
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct Sphere<T: PartialOrd + Copy> {
+    pub center: Vec3<T>,
+    pub radius: T
+}
+
+/// The segment from a to b swept by radius.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct Capsule<T: PartialOrd + Copy> {
+    pub a: Vec3<T>,
+    pub b: Vec3<T>,
+    pub radius: T
+}
+
+/// An oriented box. The columns of the orientation are the box's local axes.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct Obb<T: PartialOrd + Copy> {
+    pub center: Vec3<T>,
+    pub half_extents: Vec3<T>,
+    pub orientation: Mat33<T>
+}
+
+/// A convex shape given by the points it wraps, of which there must be at
+/// least one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConvexHull<T: PartialOrd + Copy> {
+    points: Vec<Vec3<T>>
+}
+
+/// Describes how two shapes overlap. Moving the second shape along
+/// normal by depth separates the shapes.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct Contact<T: PartialOrd + Copy> {
+    /// Unit length direction pointing from the first shape to the second.
+    pub normal: Vec3<T>,
+    /// Penetration depth along the normal.
+    pub depth: T,
+    /// The point midway between the deepest points of both shapes.
+    pub point: Vec3<T>
+}
+
+/// A convex shape described by its support mapping, usable with GJK and EPA.
+pub trait Support<T: PartialOrd + Copy> {
+    /// Returns the point of the shape furthest along dir.
+    fn support(&self, dir: Vec3<T>) -> Vec3<T>;
+
+    /// Any point inside the shape.
+    fn center(&self) -> Vec3<T>;
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> +
+    Mul<Output = T> + Div<Output = T> + DivAssign<T> +
+    Neg<Output = T>>
+    Sphere<T>
+{
+    pub fn new(center: Vec3<T>, radius: T) -> Sphere<T> {
+        Sphere::<T> { center, radius }
+    }
+
+    pub fn collide_sphere(&self, other: &Sphere<T>) -> Option<Contact<T>> {
+        let offset = other.center - self.center;
+        let radii = self.radius + other.radius;
+        let dist_sq = offset.length_sq();
+
+        if dist_sq > radii * radii {
+            return None;
+        }
+
+        let dist = dist_sq.sqrt();
+        let normal = if dist > T::ZERO { offset / dist } else { Vec3::<T>::Y };
+        let deepest_a = self.center + normal * self.radius;
+        let deepest_b = other.center - normal * other.radius;
+
+        Some(Contact::<T> {
+            normal,
+            depth: radii - dist,
+            point: (deepest_a + deepest_b) * T::HALF
+        })
+    }
+
+    pub fn collide_aabb(&self, aabb: &Aabb3<T>) -> Option<Contact<T>> {
+        let closest = aabb.closest_point(self.center);
+        let offset = closest - self.center;
+        let dist_sq = offset.length_sq();
+
+        if dist_sq > self.radius * self.radius {
+            return None;
+        }
+
+        if dist_sq > T::ZERO {
+            let dist = dist_sq.sqrt();
+            let normal = offset / dist;
+
+            return Some(Contact::<T> {
+                normal,
+                depth: self.radius - dist,
+                point: (self.center + normal * self.radius + closest) * T::HALF
+            });
+        }
+
+        // The center is inside the box, push out through the nearest face.
+        let mut best_axis = 0;
+        let mut best_dist = T::ZERO;
+        let mut best_sign = T::ZERO;
+
+        for axis in 0..3 {
+            let to_min = self.center[axis] - aabb.min[axis];
+            let to_max = aabb.max[axis] - self.center[axis];
+            let (dist, sign) = if to_min < to_max { (to_min, T::ONE) } else { (to_max, -T::ONE) };
+
+            if axis == 0 || dist < best_dist {
+                best_axis = axis;
+                best_dist = dist;
+                best_sign = sign;
+            }
+        }
+
+        let mut normal = Vec3::<T>::ZERO;
+        normal[best_axis] = best_sign;
+
+        Some(Contact::<T> {
+            normal,
+            depth: self.radius + best_dist,
+            point: self.center
+        })
+    }
+
+    /// Collides against the box as an aabb in the box's local frame.
+    pub fn collide_obb(&self, obb: &Obb<T>) -> Option<Contact<T>> {
+        let offset = self.center - obb.center;
+        let local = Sphere::<T>::new(Vec3::<T>::new(offset * obb.axis(0), offset * obb.axis(1), offset * obb.axis(2)), self.radius);
+        let contact = local.collide_aabb(&Aabb3::<T>::new(-obb.half_extents, obb.half_extents))?;
+
+        let to_world = |v: Vec3<T>| obb.axis(0) * v.x + obb.axis(1) * v.y + obb.axis(2) * v.z;
+
+        Some(Contact::<T> {
+            normal: to_world(contact.normal),
+            depth: contact.depth,
+            point: to_world(contact.point) + obb.center
+        })
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> +
+    Mul<Output = T> + Div<Output = T> + DivAssign<T> +
+    Neg<Output = T>>
+    Capsule<T>
+{
+    pub fn new(a: Vec3<T>, b: Vec3<T>, radius: T) -> Capsule<T> {
+        Capsule::<T> { a, b, radius }
+    }
+
+    pub fn collide_capsule(&self, other: &Capsule<T>) -> Option<Contact<T>> {
+        let closest = closest::segment_segment(self.a, self.b, other.a, other.b);
+        let (closest_a, closest_b) = (closest.on_a, closest.on_b);
+        let offset = closest_b - closest_a;
+        let radii = self.radius + other.radius;
+        let dist_sq = offset.length_sq();
+
+        if dist_sq > radii * radii {
+            return None;
+        }
+
+        let dist = dist_sq.sqrt();
+        let normal = if dist > T::ZERO {
+            offset / dist
+        }
+        else {
+            // The segments cross, separate perpendicular to both.
+            let cross = (self.b - self.a) % (other.b - other.a);
+            if cross.length_sq() > T::ZERO { cross.get_normalized() } else { (self.b - self.a).any_perpendicular() }
+        };
+
+        let deepest_a = closest_a + normal * self.radius;
+        let deepest_b = closest_b - normal * other.radius;
+
+        Some(Contact::<T> {
+            normal,
+            depth: radii - dist,
+            point: (deepest_a + deepest_b) * T::HALF
+        })
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> +
+    Mul<Output = T> + Div<Output = T> + DivAssign<T> +
+    Neg<Output = T>>
+    Obb<T>
+{
+    pub fn new(center: Vec3<T>, half_extents: Vec3<T>, orientation: Mat33<T>) -> Obb<T> {
+        Obb::<T> { center, half_extents, orientation }
+    }
+
+    pub fn from_quat(center: Vec3<T>, half_extents: Vec3<T>, rotation: Quat<T>) -> Obb<T> {
+        Obb::<T> {
+            center,
+            half_extents,
+            orientation: Mat33::<T>::from_mat44(&rotation.to_matrix())
+        }
+    }
+
+    pub fn from_aabb(aabb: &Aabb3<T>) -> Obb<T> {
+        Obb::<T> {
+            center: aabb.center(),
+            half_extents: aabb.half_extents(),
+            orientation: Mat33::<T>::IDENTITY
+        }
+    }
+
+    /// Returns one of the box's unit local axes in world space.
+    pub fn axis(&self, index: usize) -> Vec3<T> {
+        self.orientation.column(index)
+    }
+
+    /// Half the length of the box's projection onto a unit axis.
+    pub fn projected_radius(&self, axis: Vec3<T>) -> T {
+        self.half_extents.x * (self.axis(0) * axis).abs() +
+            self.half_extents.y * (self.axis(1) * axis).abs() +
+            self.half_extents.z * (self.axis(2) * axis).abs()
+    }
+
+    /// Separating axis test over the 15 candidate axes. The axis of least
+    /// penetration gives the contact normal.
+    pub fn collide_obb(&self, other: &Obb<T>) -> Option<Contact<T>> {
+        let offset = other.center - self.center;
+        let mut best: Option<(Vec3<T>, T)> = None;
+
+        let mut axes = [Vec3::<T>::ZERO; 15];
+        for i in 0..3 {
+            axes[i] = self.axis(i);
+            axes[3 + i] = other.axis(i);
+
+            for j in 0..3 {
+                axes[6 + i * 3 + j] = self.axis(i) % other.axis(j);
+            }
+        }
+
+        for axis in axes {
+            // Edge pairs that are parallel are already covered by the face axes.
+            let len_sq = axis.length_sq();
+            if len_sq <= T::EPSILON {
+                continue;
+            }
+
+            let axis = axis / len_sq.sqrt();
+            let dist = offset * axis;
+            let overlap = self.projected_radius(axis) + other.projected_radius(axis) - dist.abs();
+
+            if overlap < T::ZERO {
+                return None;
+            }
+
+            if best.is_none_or(|(_, depth)| overlap < depth) {
+                best = Some((if dist < T::ZERO { -axis } else { axis }, overlap));
+            }
+        }
+
+        let (normal, depth) = best?;
+        let deepest_a = self.support(normal);
+        let deepest_b = other.support(-normal);
+
+        Some(Contact::<T> {
+            normal,
+            depth,
+            point: (deepest_a + deepest_b) * T::HALF
+        })
+    }
+}
+
+impl<T: PartialOrd + Copy> ConvexHull<T> {
+    /// Panics if points is empty, since an empty hull has no support point.
+    pub fn new(points: Vec<Vec3<T>>) -> ConvexHull<T> {
+        assert!(!points.is_empty(), "a convex hull needs at least one point");
+        ConvexHull::<T> { points }
+    }
+
+    pub fn points(&self) -> &[Vec3<T>] {
+        &self.points
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T>>
+    Support<T> for Sphere<T>
+{
+    fn support(&self, dir: Vec3<T>) -> Vec3<T> {
+        self.center + dir.get_normalized() * self.radius
+    }
+
+    fn center(&self) -> Vec3<T> {
+        self.center
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T>>
+    Support<T> for Capsule<T>
+{
+    fn support(&self, dir: Vec3<T>) -> Vec3<T> {
+        let end = if self.a * dir > self.b * dir { self.a } else { self.b };
+        end + dir.get_normalized() * self.radius
+    }
+
+    fn center(&self) -> Vec3<T> {
+        (self.a + self.b) * T::HALF
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+    Neg<Output = T>>
+    Support<T> for Obb<T>
+{
+    fn support(&self, dir: Vec3<T>) -> Vec3<T> {
+        let mut result = self.center;
+
+        for i in 0..3 {
+            let axis = self.orientation.column(i);
+            let extent = if axis * dir >= T::ZERO { self.half_extents[i] } else { -self.half_extents[i] };
+            result = result + axis * extent;
+        }
+
+        result
+    }
+
+    fn center(&self) -> Vec3<T> {
+        self.center
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + Sub<Output = T> + Mul<Output = T>>
+    Support<T> for Aabb3<T>
+{
+    fn support(&self, dir: Vec3<T>) -> Vec3<T> {
+        Vec3::<T>::new(
+            if dir.x >= T::ZERO { self.max.x } else { self.min.x },
+            if dir.y >= T::ZERO { self.max.y } else { self.min.y },
+            if dir.z >= T::ZERO { self.max.z } else { self.min.z })
+    }
+
+    fn center(&self) -> Vec3<T> {
+        Aabb3::<T>::center(self)
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + Mul<Output = T> + Div<Output = T>>
+    Support<T> for ConvexHull<T>
+{
+    fn support(&self, dir: Vec3<T>) -> Vec3<T> {
+        let mut best = self.points[0];
+        let mut best_dot = best * dir;
+
+        for point in &self.points[1..] {
+            let dot = *point * dir;
+
+            if dot > best_dot {
+                best = *point;
+                best_dot = dot;
+            }
+        }
+
+        best
+    }
+
+    fn center(&self) -> Vec3<T> {
+        let mut sum = self.points[0];
+        let mut count = T::ONE;
+
+        for point in &self.points[1..] {
+            sum = sum + *point;
+            count = count + T::ONE;
+        }
+
+        sum / count
+    }
+}
+
+/// A vertex of the Minkowski difference a - b along with the point
+/// of a it came from.
+#[derive(Copy, Clone)]
+struct MinkowskiPoint<T: PartialOrd + Copy> {
+    point: Vec3<T>,
+    on_a: Vec3<T>
+}
+
+const GJK_MAX_ITERATIONS: usize = 64;
+const EPA_MAX_ITERATIONS: usize = 64;
+
+fn minkowski_support<T, A, B>(a: &A, b: &B, dir: Vec3<T>) -> MinkowskiPoint<T>
+    where T: PartialOrd + Copy + Sub<Output = T> + Neg<Output = T>,
+    A: Support<T>, B: Support<T>
+{
+    let on_a = a.support(dir);
+
+    MinkowskiPoint::<T> {
+        point: on_a - b.support(-dir),
+        on_a
+    }
+}
+
+/// Tests two convex shapes for overlap with the GJK algorithm.
+/// Shapes that only touch count as overlapping.
+pub fn gjk_intersect<T, A, B>(a: &A, b: &B) -> bool
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T> + Neg<Output = T>,
+    A: Support<T>, B: Support<T>
+{
+    gjk(a, b).is_some()
+}
+
+/// Computes the penetration of two convex shapes using GJK to find
+/// overlap and EPA to find the depth. Shapes that only touch report
+/// no contact.
+pub fn collide_convex<T, A, B>(a: &A, b: &B) -> Option<Contact<T>>
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T> + Neg<Output = T>,
+    A: Support<T>, B: Support<T>
+{
+    let mut simplex = gjk(a, b)?;
+
+    if simplex.len() < 4 && !expand_simplex(a, b, &mut simplex) {
+        return None;
+    }
+
+    epa(a, b, simplex)
+}
+
+/// GJK stops early when the origin lies on a point, segment or triangle.
+/// EPA needs a tetrahedron, so grow the simplex with support points in
+/// directions away from its span. Returns false if the Minkowski
+/// difference is flat and has no volume.
+fn expand_simplex<T, A, B>(a: &A, b: &B, simplex: &mut Vec<MinkowskiPoint<T>>) -> bool
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T> + Neg<Output = T>,
+    A: Support<T>, B: Support<T>
+{
+    let tolerance = T::EPSILON.sqrt();
+    let axes = [Vec3::<T>::X, Vec3::<T>::Y, Vec3::<T>::Z];
+
+    if simplex.len() == 1 {
+        for dir in axes.iter().flat_map(|axis| [*axis, -*axis]) {
+            let next = minkowski_support(a, b, dir);
+
+            if (next.point - simplex[0].point).length_sq() > tolerance {
+                simplex.push(next);
+                break;
+            }
+        }
+    }
+
+    if simplex.len() == 2 {
+        let line = simplex[1].point - simplex[0].point;
+
+        for dir in axes.iter().map(|axis| line % *axis).flat_map(|dir| [dir, -dir]) {
+            if dir.length_sq() <= tolerance {
+                continue;
+            }
+
+            let next = minkowski_support(a, b, dir);
+
+            if ((next.point - simplex[0].point) % line).length_sq() > tolerance {
+                simplex.push(next);
+                break;
+            }
+        }
+    }
+
+    if simplex.len() == 3 {
+        let normal = (simplex[1].point - simplex[0].point) % (simplex[2].point - simplex[0].point);
+
+        for dir in [normal, -normal] {
+            let next = minkowski_support(a, b, dir);
+
+            if ((next.point - simplex[0].point) * normal).abs() > tolerance {
+                simplex.push(next);
+                break;
+            }
+        }
+    }
+
+    simplex.len() == 4
+}
+
+/// Returns the final simplex if the shapes overlap.
+fn gjk<T, A, B>(a: &A, b: &B) -> Option<Vec<MinkowskiPoint<T>>>
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T> + Neg<Output = T>,
+    A: Support<T>, B: Support<T>
+{
+    let mut dir = b.center() - a.center();
+    if dir.length_sq() == T::ZERO {
+        dir = Vec3::<T>::X;
+    }
+
+    let mut simplex = vec![minkowski_support(a, b, dir)];
+    dir = -simplex[0].point;
+
+    for _ in 0..GJK_MAX_ITERATIONS {
+        // The origin lies on the simplex.
+        if dir.length_sq() == T::ZERO {
+            return Some(simplex);
+        }
+
+        let next = minkowski_support(a, b, dir);
+
+        if next.point * dir < T::ZERO {
+            return None;
+        }
+
+        simplex.push(next);
+
+        if update_simplex(&mut simplex, &mut dir) {
+            return Some(simplex);
+        }
+    }
+
+    None
+}
+
+/// Reduces the simplex to the feature closest to the origin and picks the
+/// next search direction. The newest point is last. Returns true once the
+/// simplex encloses the origin.
+fn update_simplex<T>(simplex: &mut Vec<MinkowskiPoint<T>>, dir: &mut Vec3<T>) -> bool
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+        Neg<Output = T>
+{
+    match simplex.len() {
+        2 => {
+            let a = simplex[1];
+            let b = simplex[0];
+            update_line(simplex, dir, a, b);
+            false
+        }
+        3 => {
+            let a = simplex[2];
+            let b = simplex[1];
+            let c = simplex[0];
+            update_triangle(simplex, dir, a, b, c);
+            false
+        }
+        _ => {
+            let a = simplex[3];
+            let b = simplex[2];
+            let c = simplex[1];
+            let d = simplex[0];
+            let ao = -a.point;
+
+            let abc = (b.point - a.point) % (c.point - a.point);
+            let acd = (c.point - a.point) % (d.point - a.point);
+            let adb = (d.point - a.point) % (b.point - a.point);
+
+            if abc * ao > T::ZERO {
+                update_triangle(simplex, dir, a, b, c);
+                false
+            }
+            else if acd * ao > T::ZERO {
+                update_triangle(simplex, dir, a, c, d);
+                false
+            }
+            else if adb * ao > T::ZERO {
+                update_triangle(simplex, dir, a, d, b);
+                false
+            }
+            else {
+                true
+            }
+        }
+    }
+}
+
+fn update_line<T>(simplex: &mut Vec<MinkowskiPoint<T>>, dir: &mut Vec3<T>,
+    a: MinkowskiPoint<T>, b: MinkowskiPoint<T>)
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+        Neg<Output = T>
+{
+    let ab = b.point - a.point;
+    let ao = -a.point;
+
+    if ab * ao > T::ZERO {
+        *simplex = vec![b, a];
+        *dir = (ab % ao) % ab;
+    }
+    else {
+        *simplex = vec![a];
+        *dir = ao;
+    }
+}
+
+/// Handles the triangle abc where a is the newest point. The winding of
+/// the stored triangle keeps the origin above it for the tetrahedron case.
+fn update_triangle<T>(simplex: &mut Vec<MinkowskiPoint<T>>, dir: &mut Vec3<T>,
+    a: MinkowskiPoint<T>, b: MinkowskiPoint<T>, c: MinkowskiPoint<T>)
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+        Neg<Output = T>
+{
+    let ab = b.point - a.point;
+    let ac = c.point - a.point;
+    let ao = -a.point;
+    let abc = ab % ac;
+
+    if (abc % ac) * ao > T::ZERO {
+        if ac * ao > T::ZERO {
+            *simplex = vec![c, a];
+            *dir = (ac % ao) % ac;
+        }
+        else {
+            update_line(simplex, dir, a, b);
+        }
+    }
+    else if (ab % abc) * ao > T::ZERO {
+        update_line(simplex, dir, a, b);
+    }
+    else if abc * ao > T::ZERO {
+        *simplex = vec![c, b, a];
+        *dir = abc;
+    }
+    else {
+        *simplex = vec![b, c, a];
+        *dir = -abc;
+    }
+}
+
+struct EpaFace<T: PartialOrd + Copy> {
+    indices: [usize; 3],
+    normal: Vec3<T>,
+    distance: T
+}
+
+/// Builds the face ijk. Its normal follows the counter clockwise winding.
+fn epa_face<T>(points: &[MinkowskiPoint<T>], i: usize, j: usize, k: usize) -> Option<EpaFace<T>>
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T> + Neg<Output = T>
+{
+    let a = points[i].point;
+    let normal = (points[j].point - a) % (points[k].point - a);
+
+    if normal.length_sq() == T::ZERO {
+        return None;
+    }
+
+    let normal = normal.get_normalized();
+
+    Some(EpaFace::<T> {
+        indices: [i, j, k],
+        normal,
+        distance: normal * a
+    })
+}
+
+/// Expands the GJK tetrahedron towards the boundary of the Minkowski
+/// difference until the face closest to the origin is found.
+fn epa<T, A, B>(a: &A, b: &B, simplex: Vec<MinkowskiPoint<T>>) -> Option<Contact<T>>
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T> + Neg<Output = T>,
+    A: Support<T>, B: Support<T>
+{
+    let tolerance = T::EPSILON.sqrt();
+    let mut points = simplex;
+    let centroid = (points[0].point + points[1].point + points[2].point + points[3].point) * T::QUARTER;
+    let mut faces: Vec<EpaFace<T>> = Vec::new();
+
+    // Wind the faces of the tetrahedron so their normals point outwards.
+    for [i, j, k] in [[0, 1, 2], [0, 3, 1], [0, 2, 3], [1, 3, 2]] {
+        let outwards = ((points[j].point - points[i].point) % (points[k].point - points[i].point)) *
+            (points[i].point - centroid) >= T::ZERO;
+        let face = if outwards { epa_face(&points, i, j, k) } else { epa_face(&points, i, k, j) };
+
+        if let Some(face) = face {
+            faces.push(face);
+        }
+    }
+
+    for _ in 0..EPA_MAX_ITERATIONS {
+        let closest = closest_face(&faces)?;
+        let normal = faces[closest].normal;
+        let distance = faces[closest].distance;
+        let next = minkowski_support(a, b, normal);
+
+        if next.point * normal - distance <= tolerance {
+            break;
+        }
+
+        // Remove every face the new point can see and patch the hole
+        // with faces connecting its horizon to the new point.
+        let mut horizon: Vec<(usize, usize)> = Vec::new();
+        let mut index = 0;
+
+        while index < faces.len() {
+            let face = &faces[index];
+
+            if face.normal * (next.point - points[face.indices[0]].point) > T::ZERO {
+                for e in 0..3 {
+                    let edge = (face.indices[e], face.indices[(e + 1) % 3]);
+
+                    if let Some(shared) = horizon.iter().position(|h| *h == (edge.1, edge.0)) {
+                        horizon.remove(shared);
+                    }
+                    else {
+                        horizon.push(edge);
+                    }
+                }
+
+                faces.swap_remove(index);
+            }
+            else {
+                index += 1;
+            }
+        }
+
+        points.push(next);
+        let new_index = points.len() - 1;
+
+        for (i, j) in horizon {
+            if let Some(face) = epa_face(&points, i, j, new_index) {
+                faces.push(face);
+            }
+        }
+    }
+
+    let face = &faces[closest_face(&faces)?];
+    let [i, j, k] = face.indices;
+
+    // Barycentric coordinates of the origin's projection onto the face
+    // give the matching point on a.
+    let projected = face.normal * face.distance;
+    let (u, v, w) = barycentric(projected, points[i].point, points[j].point, points[k].point);
+    let on_a = points[i].on_a * u + points[j].on_a * v + points[k].on_a * w;
+    let on_b = on_a - face.normal * face.distance;
+
+    Some(Contact::<T> {
+        normal: face.normal,
+        depth: face.distance,
+        point: (on_a + on_b) * T::HALF
+    })
+}
+
+fn closest_face<T: PartialOrd + Copy>(faces: &[EpaFace<T>]) -> Option<usize> {
+    let mut best: Option<usize> = None;
+
+    for (index, face) in faces.iter().enumerate() {
+        if best.is_none_or(|b| face.distance < faces[b].distance) {
+            best = Some(index);
+        }
+    }
+
+    best
+}
+
+/// Barycentric coordinates of p with respect to the triangle abc.
+fn barycentric<T>(p: Vec3<T>, a: Vec3<T>, b: Vec3<T>, c: Vec3<T>) -> (T, T, T)
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T>
+{
+    let v0 = b - a;
+    let v1 = c - a;
+    let v2 = p - a;
+    let d00 = v0 * v0;
+    let d01 = v0 * v1;
+    let d11 = v1 * v1;
+    let d20 = v2 * v0;
+    let d21 = v2 * v1;
+    let denom = d00 * d11 - d01 * d01;
+
+    if denom == T::ZERO {
+        return (T::ONE, T::ZERO, T::ZERO);
+    }
+
+    let v = (d11 * d20 - d01 * d21) / denom;
+    let w = (d00 * d21 - d01 * d20) / denom;
+
+    (T::ONE - v - w, v, w)
+}
@@ -0,0 +1,782 @@
+use std::marker::PhantomData;
+use std::ops::{Add, Sub, Neg};
+
+use crate::glmath::*;
+use crate::glmath::kdtree::KdPoint;
+use crate::glmath::roots::quadratic_roots;
+
+/// A vector type curves can be built from: Vec2, Vec3 or Vec4.
+pub trait CurvePoint<T>: KdPoint<T> + Add<Output = Self> + Mul<T, Output = Self> + StandardVec<T>
+    where T: PartialOrd + Copy + Vectorable<T> + Mul<Output = T> + Div<Output = T>
+{
+}
+
+impl<T, P> CurvePoint<T> for P
+    where T: PartialOrd + Copy + Vectorable<T> + Mul<Output = T> + Div<Output = T>,
+    P: KdPoint<T> + Add<Output = P> + Mul<T, Output = P> + StandardVec<T>
+{
+}
+
+/// A curve defined over a range of parameters.
+pub trait Curve<T, P> {
+    /// The first and last parameter of the curve.
+    fn domain(&self) -> (T, T);
+    fn evaluate(&self, t: T) -> P;
+    /// The first derivative with respect to the parameter.
+    fn derivative(&self, t: T) -> P;
+}
+
+/// A cubic Bézier curve defined over [0, 1].
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct CubicBezier<T, P> {
+    pub points: [P; 4],
+    scalar: PhantomData<T>
+}
+
+/// A cubic Hermite curve given by its end points and end tangents,
+/// defined over [0, 1].
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct Hermite<T, P> {
+    pub p0: P,
+    pub m0: P,
+    pub p1: P,
+    pub m1: P,
+    scalar: PhantomData<T>
+}
+
+/// A centripetal Catmull-Rom spline passing through every point but the
+/// first and last. Segment i spans the parameters [i, i + 1], so the
+/// curve reaches points[i + 1] at parameter i.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CatmullRom<T, P> {
+    pub points: Vec<P>,
+    /// Centripetal knot values, spaced by the square root of the distance
+    /// between neighboring points. These avoid cusps and self intersections.
+    knots: Vec<T>
+}
+
+/// A B-spline curve of any degree over a non-decreasing knot vector.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BSpline<T, P> {
+    pub degree: usize,
+    pub points: Vec<P>,
+    pub knots: Vec<T>
+}
+
+/// A non-uniform rational B-spline. Each control point has a weight
+/// pulling the curve towards it, which allows exact conic sections.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Nurbs<T, P> {
+    /// The spline over the control points multiplied by their weights.
+    weighted: BSpline<T, P>,
+    pub weights: Vec<T>
+}
+
+/// Maps distance along a curve to the curve's parameter, so a curve can be
+/// traversed at constant speed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ArcLengthTable<T> {
+    params: Vec<T>,
+    /// Arc length from the start of the curve to each parameter.
+    lengths: Vec<T>
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>,
+    P: CurvePoint<T>>
+    CubicBezier<T, P>
+{
+    pub fn new(p0: P, p1: P, p2: P, p3: P) -> CubicBezier<T, P> {
+        CubicBezier::<T, P> { points: [p0, p1, p2, p3], scalar: PhantomData }
+    }
+
+    pub fn second_derivative(&self, t: T) -> P {
+        let [p0, p1, p2, p3] = self.points;
+        let six = T::TWO + T::TWO + T::TWO;
+
+        ((p2 - p1 - p1 + p0) * (T::ONE - t) + (p3 - p2 - p2 + p1) * t) * six
+    }
+
+    /// Splits the curve at t by de Casteljau's algorithm into the curves
+    /// over [0, t] and [t, 1], each reparameterized over [0, 1].
+    pub fn split(&self, t: T) -> (CubicBezier<T, P>, CubicBezier<T, P>) {
+        let lerp = |a: P, b: P| a + (b - a) * t;
+        let [p0, p1, p2, p3] = self.points;
+
+        let a = lerp(p0, p1);
+        let b = lerp(p1, p2);
+        let c = lerp(p2, p3);
+        let ab = lerp(a, b);
+        let bc = lerp(b, c);
+        let mid = lerp(ab, bc);
+
+        (CubicBezier::<T, P>::new(p0, a, ab, mid), CubicBezier::<T, P>::new(mid, bc, c, p3))
+    }
+
+    /// The exact bounds of the curve as its min and max corners, found from
+    /// the end points and the extrema of each component.
+    pub fn bounds(&self) -> (P, P) {
+        let [p0, p1, p2, p3] = self.points;
+        let (mut min, mut max) = point_bounds(&[p0, p3]);
+
+        for axis in 0..P::DIMENSIONS {
+            // The component's derivative is a quadratic, whose roots are the extrema.
+            let a = p1[axis] - p0[axis];
+            let b = p2[axis] - p1[axis];
+            let c = p3[axis] - p2[axis];
+            let qa = a - b - b + c;
+            let qb = (b - a) * T::TWO;
+
+            // A nearly vanishing leading term leaves a linear derivative.
+            let qa = if qa.abs() <= T::EPSILON { T::ZERO } else { qa };
+
+            for t in quadratic_roots(qa, qb, a) {
+                if t > T::ZERO && t < T::ONE {
+                    let value = self.evaluate(t)[axis];
+                    min[axis] = T::min(min[axis], value);
+                    max[axis] = T::max(max[axis], value);
+                }
+            }
+        }
+
+        (min, max)
+    }
+
+    /// The same curve in Hermite form.
+    pub fn to_hermite(&self) -> Hermite<T, P> {
+        let [p0, p1, p2, p3] = self.points;
+        let three = T::ONE + T::TWO;
+
+        Hermite::<T, P>::new(p0, (p1 - p0) * three, p3, (p3 - p2) * three)
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>,
+    P: CurvePoint<T>>
+    Curve<T, P> for CubicBezier<T, P>
+{
+    fn domain(&self) -> (T, T) {
+        (T::ZERO, T::ONE)
+    }
+
+    fn evaluate(&self, t: T) -> P {
+        let [p0, p1, p2, p3] = self.points;
+        let s = T::ONE - t;
+        let three = T::ONE + T::TWO;
+
+        p0 * (s * s * s) + p1 * (three * s * s * t) + p2 * (three * s * t * t) + p3 * (t * t * t)
+    }
+
+    fn derivative(&self, t: T) -> P {
+        let [p0, p1, p2, p3] = self.points;
+        let s = T::ONE - t;
+        let three = T::ONE + T::TWO;
+
+        ((p1 - p0) * (s * s) + (p2 - p1) * (T::TWO * s * t) + (p3 - p2) * (t * t)) * three
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>,
+    P: CurvePoint<T>>
+    Hermite<T, P>
+{
+    pub fn new(p0: P, m0: P, p1: P, m1: P) -> Hermite<T, P> {
+        Hermite::<T, P> { p0, m0, p1, m1, scalar: PhantomData }
+    }
+
+    /// The same curve in Bézier form.
+    pub fn to_bezier(&self) -> CubicBezier<T, P> {
+        let third = T::ONE / (T::ONE + T::TWO);
+
+        CubicBezier::<T, P>::new(self.p0, self.p0 + self.m0 * third, self.p1 - self.m1 * third, self.p1)
+    }
+
+    pub fn second_derivative(&self, t: T) -> P {
+        self.to_bezier().second_derivative(t)
+    }
+
+    /// Splits the curve at t into the curves over [0, t] and [t, 1], each
+    /// reparameterized over [0, 1].
+    pub fn split(&self, t: T) -> (Hermite<T, P>, Hermite<T, P>) {
+        let (first, second) = self.to_bezier().split(t);
+        (first.to_hermite(), second.to_hermite())
+    }
+
+    pub fn bounds(&self) -> (P, P) {
+        self.to_bezier().bounds()
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>,
+    P: CurvePoint<T>>
+    Curve<T, P> for Hermite<T, P>
+{
+    fn domain(&self) -> (T, T) {
+        (T::ZERO, T::ONE)
+    }
+
+    fn evaluate(&self, t: T) -> P {
+        let t2 = t * t;
+        let t3 = t2 * t;
+        let three = T::ONE + T::TWO;
+
+        self.p0 * (T::TWO * t3 - three * t2 + T::ONE) +
+            self.m0 * (t3 - T::TWO * t2 + t) +
+            self.p1 * (three * t2 - T::TWO * t3) +
+            self.m1 * (t3 - t2)
+    }
+
+    fn derivative(&self, t: T) -> P {
+        self.to_bezier().derivative(t)
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>,
+    P: CurvePoint<T>>
+    CatmullRom<T, P>
+{
+    /// Creates a spline through the points. Returns None if fewer than four
+    /// points are given.
+    pub fn new(points: &[P]) -> Option<CatmullRom<T, P>> {
+        if points.len() < 4 {
+            return None;
+        }
+
+        let mut knots = vec![T::ZERO];
+
+        for pair in points.windows(2) {
+            // Coincident points still get a small step to avoid dividing by zero.
+            let step = T::max((pair[1] - pair[0]).length().sqrt(), T::EPSILON);
+            knots.push(knots[knots.len() - 1] + step);
+        }
+
+        Some(CatmullRom::<T, P> { points: points.to_vec(), knots })
+    }
+
+    pub fn segment_count(&self) -> usize {
+        self.points.len() - 3
+    }
+
+    /// The segment between points[index + 1] and points[index + 2].
+    pub fn segment(&self, index: usize) -> Hermite<T, P> {
+        let p = &self.points[index..index + 4];
+        let k = &self.knots[index..index + 4];
+
+        let slope = |i: usize, j: usize| (p[j] - p[i]) * (T::ONE / (k[j] - k[i]));
+        let scale = k[2] - k[1];
+
+        // Tangents of the non-uniform spline, scaled to a unit parameter range.
+        let m1 = (slope(0, 1) - slope(0, 2) + slope(1, 2)) * scale;
+        let m2 = (slope(1, 2) - slope(1, 3) + slope(2, 3)) * scale;
+
+        Hermite::<T, P>::new(p[1], m1, p[2], m2)
+    }
+
+    /// Every segment in Bézier form.
+    pub fn to_beziers(&self) -> Vec<CubicBezier<T, P>> {
+        (0..self.segment_count()).map(|i| self.segment(i).to_bezier()).collect()
+    }
+
+    /// Splits the spline at t into the Bézier segments before and after it.
+    /// The segment holding t is split by de Casteljau's algorithm.
+    #[allow(clippy::type_complexity)]
+    pub fn split(&self, t: T) -> (Vec<CubicBezier<T, P>>, Vec<CubicBezier<T, P>>) {
+        let (index, local) = self.locate(t);
+        let beziers = self.to_beziers();
+        let (mut first, mut second) = (beziers[..index].to_vec(), beziers[index + 1..].to_vec());
+
+        if local <= T::ZERO {
+            second.insert(0, beziers[index]);
+        }
+        else if local >= T::ONE {
+            first.push(beziers[index]);
+        }
+        else {
+            let (before, after) = beziers[index].split(local);
+            first.push(before);
+            second.insert(0, after);
+        }
+
+        (first, second)
+    }
+
+    pub fn bounds(&self) -> (P, P) {
+        let corners: Vec<P> = self.to_beziers().iter()
+            .flat_map(|bezier| { let (min, max) = bezier.bounds(); [min, max] })
+            .collect();
+
+        point_bounds(&corners)
+    }
+
+    /// The segment holding the parameter and the parameter within it.
+    fn locate(&self, t: T) -> (usize, T) {
+        let last = self.segment_count() - 1;
+        let index = (t.to_f64().floor().max(0.0) as usize).min(last);
+
+        (index, t - T::from_f64(index as f64))
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>,
+    P: CurvePoint<T>>
+    Curve<T, P> for CatmullRom<T, P>
+{
+    fn domain(&self) -> (T, T) {
+        (T::ZERO, T::from_f64(self.segment_count() as f64))
+    }
+
+    fn evaluate(&self, t: T) -> P {
+        let (index, local) = self.locate(t);
+        self.segment(index).evaluate(local)
+    }
+
+    fn derivative(&self, t: T) -> P {
+        let (index, local) = self.locate(t);
+        self.segment(index).derivative(local)
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>,
+    P: CurvePoint<T>>
+    BSpline<T, P>
+{
+    /// Creates a spline from its control points and knots. Returns None
+    /// unless there are more points than the degree, exactly
+    /// points + degree + 1 knots, and the knots never decrease.
+    pub fn new(degree: usize, points: &[P], knots: &[T]) -> Option<BSpline<T, P>> {
+        if points.len() <= degree || knots.len() != points.len() + degree + 1 {
+            return None;
+        }
+
+        if knots.windows(2).any(|pair| pair[1] < pair[0]) {
+            return None;
+        }
+
+        Some(BSpline::<T, P> { degree, points: points.to_vec(), knots: knots.to_vec() })
+    }
+
+    /// A spline with evenly spaced knots. The curve does not reach the
+    /// first and last control points.
+    pub fn uniform(degree: usize, points: &[P]) -> Option<BSpline<T, P>> {
+        let knots: Vec<T> = (0..points.len() + degree + 1).map(|i| T::from_f64(i as f64)).collect();
+        BSpline::<T, P>::new(degree, points, &knots)
+    }
+
+    /// A spline with evenly spaced inner knots and repeated end knots, so
+    /// the curve starts and ends at the first and last control points.
+    pub fn clamped(degree: usize, points: &[P]) -> Option<BSpline<T, P>> {
+        let last = points.len().saturating_sub(degree);
+
+        let knots: Vec<T> = (0..points.len() + degree + 1)
+            .map(|i| T::from_f64(i.saturating_sub(degree).min(last) as f64))
+            .collect();
+
+        BSpline::<T, P>::new(degree, points, &knots)
+    }
+
+    /// The spline's derivative, a spline of one degree lower.
+    pub fn derivative_spline(&self) -> Option<BSpline<T, P>> {
+        if self.degree == 0 {
+            return None;
+        }
+
+        let p = self.degree;
+        let degree = T::from_f64(p as f64);
+
+        let points: Vec<P> = (0..self.points.len() - 1).map(|i| {
+            let span = self.knots[i + p + 1] - self.knots[i + 1];
+            if span == T::ZERO { P::default() } else { (self.points[i + 1] - self.points[i]) * (degree / span) }
+        }).collect();
+
+        BSpline::<T, P>::new(p - 1, &points, &self.knots[1..self.knots.len() - 1])
+    }
+
+    /// Inserts a knot without changing the curve's shape, adding a control
+    /// point. Repeated insertion splits the curve into Bézier segments.
+    pub fn insert_knot(&self, u: T) -> BSpline<T, P> {
+        let k = self.find_span(u);
+        let points = insert_knot(self.degree, &self.knots, &self.points, k, u);
+
+        let mut knots = self.knots.clone();
+        knots.insert(k + 1, u);
+
+        BSpline::<T, P> { degree: self.degree, points, knots }
+    }
+
+    /// Splits the curve at u into the curves over the parameters before
+    /// and after it, by inserting u until the curve passes through a
+    /// control point there. Returns None unless u lies inside the domain.
+    pub fn split(&self, u: T) -> Option<(BSpline<T, P>, BSpline<T, P>)> {
+        let (start, end) = self.domain();
+
+        if u <= start || u >= end {
+            return None;
+        }
+
+        let mut spline = self.clone();
+
+        while spline.knots.iter().filter(|knot| **knot == u).count() < self.degree {
+            spline = spline.insert_knot(u);
+        }
+
+        let ((count, first_knots), (from, second_knots)) = split_knots(&spline.knots, self.degree, u);
+
+        Some((
+            BSpline::<T, P> { degree: self.degree, points: spline.points[..count].to_vec(), knots: first_knots },
+            BSpline::<T, P> { degree: self.degree, points: spline.points[from..].to_vec(), knots: second_knots }
+        ))
+    }
+
+    /// The bounds of the control points, which contain the curve.
+    pub fn bounds(&self) -> (P, P) {
+        point_bounds(&self.points)
+    }
+
+    /// The index k of the knot span [knots[k], knots[k + 1]) holding u,
+    /// clamped to the spline's domain.
+    pub fn find_span(&self, u: T) -> usize {
+        let n = self.points.len();
+
+        if u >= self.knots[n] {
+            // The last non-empty span includes the end of the domain.
+            return (self.degree..n).rev().find(|k| self.knots[*k] < self.knots[*k + 1]).unwrap_or(n - 1);
+        }
+
+        (self.degree..n).take_while(|k| self.knots[*k] <= u).last().unwrap_or(self.degree)
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>,
+    P: CurvePoint<T>>
+    Curve<T, P> for BSpline<T, P>
+{
+    fn domain(&self) -> (T, T) {
+        (self.knots[self.degree], self.knots[self.points.len()])
+    }
+
+    /// Evaluates the curve by de Boor's algorithm.
+    fn evaluate(&self, u: T) -> P {
+        let p = self.degree;
+        let k = self.find_span(u);
+        let mut d: Vec<P> = self.points[k - p..=k].to_vec();
+
+        for r in 1..=p {
+            for j in (r..=p).rev() {
+                let i = j + k - p;
+                let span = self.knots[i + p + 1 - r] - self.knots[i];
+                let alpha = if span == T::ZERO { T::ZERO } else { (u - self.knots[i]) / span };
+
+                d[j] = d[j - 1] * (T::ONE - alpha) + d[j] * alpha;
+            }
+        }
+
+        d[p]
+    }
+
+    fn derivative(&self, u: T) -> P {
+        match self.derivative_spline() {
+            Some(spline) => spline.evaluate(u),
+            None => P::default()
+        }
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>,
+    P: CurvePoint<T>>
+    Nurbs<T, P>
+{
+    /// Creates a curve from its control points, their weights and the knots.
+    /// Returns None if the knots are invalid as for a BSpline, or the
+    /// weights do not match the points or are not positive.
+    pub fn new(degree: usize, points: &[P], weights: &[T], knots: &[T]) -> Option<Nurbs<T, P>> {
+        if weights.len() != points.len() || weights.iter().any(|w| *w <= T::ZERO) {
+            return None;
+        }
+
+        let weighted: Vec<P> = points.iter().zip(weights).map(|(point, weight)| *point * *weight).collect();
+
+        Some(Nurbs::<T, P> {
+            weighted: BSpline::<T, P>::new(degree, &weighted, knots)?,
+            weights: weights.to_vec()
+        })
+    }
+
+    pub fn degree(&self) -> usize {
+        self.weighted.degree
+    }
+
+    pub fn knots(&self) -> &[T] {
+        &self.weighted.knots
+    }
+
+    /// The control point at the index.
+    pub fn point(&self, index: usize) -> P {
+        self.weighted.points[index] * (T::ONE / self.weights[index])
+    }
+
+    /// Inserts a knot without changing the curve's shape, adding a control
+    /// point. The weighted points and the weights are refined together.
+    pub fn insert_knot(&self, u: T) -> Nurbs<T, P> {
+        let k = self.weighted.find_span(u);
+
+        Nurbs::<T, P> {
+            weights: insert_knot(self.weighted.degree, &self.weighted.knots, &self.weights, k, u),
+            weighted: self.weighted.insert_knot(u)
+        }
+    }
+
+    /// Splits the curve at u into the curves over the parameters before
+    /// and after it, as BSpline::split does. Returns None unless u lies
+    /// inside the domain.
+    pub fn split(&self, u: T) -> Option<(Nurbs<T, P>, Nurbs<T, P>)> {
+        let (start, end) = self.domain();
+
+        if u <= start || u >= end {
+            return None;
+        }
+
+        let p = self.weighted.degree;
+        let mut curve = self.clone();
+
+        while curve.knots().iter().filter(|knot| **knot == u).count() < p {
+            curve = curve.insert_knot(u);
+        }
+
+        let ((count, first_knots), (from, second_knots)) = split_knots(curve.knots(), p, u);
+
+        Some((
+            Nurbs::<T, P> {
+                weighted: BSpline::<T, P> { degree: p, points: curve.weighted.points[..count].to_vec(), knots: first_knots },
+                weights: curve.weights[..count].to_vec()
+            },
+            Nurbs::<T, P> {
+                weighted: BSpline::<T, P> { degree: p, points: curve.weighted.points[from..].to_vec(), knots: second_knots },
+                weights: curve.weights[from..].to_vec()
+            }
+        ))
+    }
+
+    /// The bounds of the control points, which contain the curve.
+    pub fn bounds(&self) -> (P, P) {
+        let points: Vec<P> = (0..self.weights.len()).map(|i| self.point(i)).collect();
+        point_bounds(&points)
+    }
+
+    /// The weight of the curve at u, a B-spline over the weights.
+    fn weight_at(&self, u: T, derivative: bool) -> T {
+        let p = self.weighted.degree;
+        let knots = &self.weighted.knots;
+        let k = self.weighted.find_span(u);
+
+        if derivative {
+            if p == 0 {
+                return T::ZERO;
+            }
+
+            // Coefficients of the derivative spline, as in derivative_spline.
+            let degree = T::from_f64(p as f64);
+            let basis = basis_functions(knots, p - 1, k, u, 1);
+
+            return (0..p).map(|j| {
+                let i = k - p + j;
+                let span = knots[i + p + 1] - knots[i + 1];
+                let coefficient = if span == T::ZERO { T::ZERO } else { (self.weights[i + 1] - self.weights[i]) * degree / span };
+                coefficient * basis[j]
+            }).fold(T::ZERO, |sum, value| sum + value);
+        }
+
+        let basis = basis_functions(knots, p, k, u, 0);
+        (0..=p).map(|j| self.weights[k - p + j] * basis[j]).fold(T::ZERO, |sum, value| sum + value)
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>,
+    P: CurvePoint<T>>
+    Curve<T, P> for Nurbs<T, P>
+{
+    fn domain(&self) -> (T, T) {
+        self.weighted.domain()
+    }
+
+    fn evaluate(&self, u: T) -> P {
+        self.weighted.evaluate(u) * (T::ONE / self.weight_at(u, false))
+    }
+
+    fn derivative(&self, u: T) -> P {
+        // Quotient rule on the weighted curve over the weight.
+        let weight = self.weight_at(u, false);
+        let point = self.weighted.evaluate(u) * (T::ONE / weight);
+
+        (self.weighted.derivative(u) - point * self.weight_at(u, true)) * (T::ONE / weight)
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    ArcLengthTable<T>
+{
+    /// Measures the curve over evenly spaced parameter intervals using
+    /// three point Gauss-Legendre quadrature on each.
+    pub fn new<P: CurvePoint<T>, C: Curve<T, P>>(curve: &C, intervals: usize) -> ArcLengthTable<T> {
+        let (start, end) = curve.domain();
+        let intervals = intervals.max(1);
+        let step = (end - start) / T::from_f64(intervals as f64);
+
+        let offset = T::from_f64(0.6f64.sqrt()) * T::HALF;
+        let outer = T::from_f64(5.0 / 18.0);
+        let inner = T::from_f64(8.0 / 18.0);
+        let speed = |t: T| curve.derivative(t).length();
+
+        let mut params = vec![start];
+        let mut lengths = vec![T::ZERO];
+
+        for i in 0..intervals {
+            let a = start + step * T::from_f64(i as f64);
+            let mid = a + step * T::HALF;
+
+            let segment = (speed(mid - step * offset) * outer + speed(mid) * inner + speed(mid + step * offset) * outer) * step;
+
+            params.push(if i + 1 == intervals { end } else { a + step });
+            lengths.push(lengths[i] + segment);
+        }
+
+        ArcLengthTable::<T> { params, lengths }
+    }
+
+    /// The total length of the curve.
+    pub fn length(&self) -> T {
+        self.lengths[self.lengths.len() - 1]
+    }
+
+    /// The parameter at the given distance along the curve, interpolated
+    /// between table entries. Distances outside the curve are clamped.
+    pub fn parameter_at(&self, distance: T) -> T {
+        if distance <= T::ZERO {
+            return self.params[0];
+        }
+
+        if distance >= self.length() {
+            return self.params[self.params.len() - 1];
+        }
+
+        let upper = self.lengths.partition_point(|length| *length < distance).max(1);
+        let (l0, l1) = (self.lengths[upper - 1], self.lengths[upper]);
+        let (t0, t1) = (self.params[upper - 1], self.params[upper]);
+
+        if l1 == l0 {
+            return t0;
+        }
+
+        t0 + (t1 - t0) * ((distance - l0) / (l1 - l0))
+    }
+
+    /// The parameter at a fraction of the curve's total length.
+    pub fn parameter_at_fraction(&self, fraction: T) -> T {
+        self.parameter_at(fraction * self.length())
+    }
+}
+
+/// The min and max corners of a set of points.
+fn point_bounds<T, P>(points: &[P]) -> (P, P)
+    where T: PartialOrd + Copy + Vectorable<T> + Mul<Output = T> + Div<Output = T>,
+    P: CurvePoint<T>
+{
+    let mut min = points[0];
+    let mut max = points[0];
+
+    for point in &points[1..] {
+        for axis in 0..P::DIMENSIONS {
+            min[axis] = T::min(min[axis], point[axis]);
+            max[axis] = T::max(max[axis], point[axis]);
+        }
+    }
+
+    (min, max)
+}
+
+/// The control values after inserting u into the knot span k by Boehm's
+/// algorithm. Only the degree values ending at k are blended.
+fn insert_knot<T, Q>(degree: usize, knots: &[T], values: &[Q], k: usize, u: T) -> Vec<Q>
+    where T: PartialOrd + Copy + Vectorable<T> + Sub<Output = T> + Div<Output = T>,
+    Q: Copy + Add<Output = Q> + Mul<T, Output = Q>
+{
+    (0..=values.len()).map(|i| {
+        if i + degree <= k {
+            values[i]
+        }
+        else if i > k {
+            values[i - 1]
+        }
+        else {
+            let alpha = (u - knots[i]) / (knots[i + degree] - knots[i]);
+            values[i - 1] * (T::ONE - alpha) + values[i] * alpha
+        }
+    }).collect()
+}
+
+/// Cuts a knot vector holding u at least degree times into the knots of
+/// the curves before and after u. Also returns the number of control
+/// points of the first curve and the first control point of the second.
+fn split_knots<T: PartialOrd + Copy>(knots: &[T], degree: usize, u: T) -> ((usize, Vec<T>), (usize, Vec<T>)) {
+    let count = knots.iter().take_while(|knot| **knot < u).count();
+    let multiplicity = knots.iter().filter(|knot| **knot == u).count();
+    let from = count + multiplicity - degree - 1;
+
+    ((count, [&knots[..count + degree], &[u]].concat()), (from, [&[u], &knots[from + 1..]].concat()))
+}
+
+/// The B-spline basis functions of a degree which are non-zero in the knot
+/// span k, evaluated at u as in The NURBS Book's algorithm A2.2. The knot
+/// offset shifts the knots for the derivative spline, whose knot vector
+/// drops the first knot.
+fn basis_functions<T>(knots: &[T], degree: usize, k: usize, u: T, offset: usize) -> Vec<T>
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>
+{
+    let knot = |i: usize| knots[i + offset];
+    // The span in the shifted knot vector.
+    let k = k - offset;
+
+    let mut basis = vec![T::ZERO; degree + 1];
+    let mut left = vec![T::ZERO; degree + 1];
+    let mut right = vec![T::ZERO; degree + 1];
+    basis[0] = T::ONE;
+
+    for j in 1..=degree {
+        left[j] = u - knot(k + 1 - j);
+        right[j] = knot(k + j) - u;
+        let mut saved = T::ZERO;
+
+        for r in 0..j {
+            let denom = right[r + 1] + left[j - r];
+            let temp = if denom == T::ZERO { T::ZERO } else { basis[r] / denom };
+
+            basis[r] = saved + right[r + 1] * temp;
+            saved = left[j - r] * temp;
+        }
+
+        basis[j] = saved;
+    }
+
+    basis
+}
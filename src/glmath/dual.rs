@@ -0,0 +1,261 @@
+use std::{fmt::Display, ops::{Add, AddAssign, Sub, SubAssign, MulAssign, Neg}};
+
+use crate::glmath::*;
+use crate::glmath::matrix::Matrix;
+
+/// A value with its partial derivatives with respect to N variables, for
+/// forward mode automatic differentiation. Since it implements Vectorable,
+/// any glmath type can be built over it and carries derivatives through
+/// its operations. Comparisons only look at the value.
+#[derive(Debug, Copy, Clone)]
+pub struct Jet<T: PartialOrd + Copy, const N: usize> {
+    pub value: T,
+    pub derivatives: [T; N]
+}
+
+/// A value with a single derivative.
+pub type Dual<T> = Jet<T, 1>;
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>, const N: usize>
+    Jet<T, N>
+{
+    /// A value that does not depend on any variable.
+    pub fn constant(value: T) -> Jet<T, N> {
+        Jet::<T, N> { value, derivatives: [T::ZERO; N] }
+    }
+
+    /// The variable with the given index, whose derivative with respect to
+    /// itself is one.
+    pub fn variable(value: T, index: usize) -> Jet<T, N> {
+        let mut derivatives = [T::ZERO; N];
+        derivatives[index] = T::ONE;
+        Jet::<T, N> { value, derivatives }
+    }
+
+    /// Seeds each value as the variable of the same index.
+    pub fn variables(values: [T; N]) -> [Jet<T, N>; N] {
+        std::array::from_fn(|i| Jet::<T, N>::variable(values[i], i))
+    }
+
+    /// Applies a function with value f and derivative slope at self.value
+    /// by the chain rule.
+    fn chain(&self, f: T, slope: T) -> Jet<T, N> {
+        Jet::<T, N> { value: f, derivatives: self.derivatives.map(|d| d * slope) }
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    Dual<T>
+{
+    pub fn derivative(&self) -> T {
+        self.derivatives[0]
+    }
+}
+
+/// The value and derivative of f at x.
+pub fn derivative<T, F>(f: F, x: T) -> (T, T)
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T> + Neg<Output = T>,
+        F: FnOnce(Dual<T>) -> Dual<T>
+{
+    let result = f(Dual::<T>::variable(x, 0));
+    (result.value, result.derivative())
+}
+
+/// The value and gradient of a scalar function of N variables at x.
+pub fn gradient<T, F, const N: usize>(f: F, x: [T; N]) -> (T, [T; N])
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T> + Neg<Output = T>,
+        F: FnOnce([Jet<T, N>; N]) -> Jet<T, N>
+{
+    let result = f(Jet::<T, N>::variables(x));
+    (result.value, result.derivatives)
+}
+
+/// The values and M x N Jacobian of a function from N to M variables at x,
+/// where row i holds the gradient of output i.
+pub fn jacobian<T, F, const N: usize, const M: usize>(f: F, x: [T; N]) -> ([T; M], Matrix<T, M, N>)
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T> + Neg<Output = T>,
+        F: FnOnce([Jet<T, N>; N]) -> [Jet<T, N>; M]
+{
+    let result = f(Jet::<T, N>::variables(x));
+    let rows = result.map(|output| output.derivatives);
+
+    (result.map(|output| output.value), Matrix::<T, M, N>::from_rows(rows))
+}
+
+impl<T: PartialOrd + Copy, const N: usize> PartialEq for Jet<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: PartialOrd + Copy, const N: usize> PartialOrd for Jet<T, N> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+impl<T: PartialOrd + Copy + Display, const N: usize> Display for Jet<T, N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.value)?;
+        for (i, d) in self.derivatives.iter().enumerate() {
+            write!(f, " + {}e{}", d, i)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: PartialOrd + Copy + Add<Output = T>, const N: usize> Add for Jet<T, N> {
+    type Output = Jet<T, N>;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Jet::<T, N> { value: self.value + rhs.value, derivatives: std::array::from_fn(|i| self.derivatives[i] + rhs.derivatives[i]) }
+    }
+}
+
+impl<T: PartialOrd + Copy + Add<Output = T>, const N: usize> AddAssign for Jet<T, N> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<T: PartialOrd + Copy + Sub<Output = T>, const N: usize> Sub for Jet<T, N> {
+    type Output = Jet<T, N>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Jet::<T, N> { value: self.value - rhs.value, derivatives: std::array::from_fn(|i| self.derivatives[i] - rhs.derivatives[i]) }
+    }
+}
+
+impl<T: PartialOrd + Copy + Sub<Output = T>, const N: usize> SubAssign for Jet<T, N> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<T: PartialOrd + Copy + Add<Output = T> + Mul<Output = T>, const N: usize> Mul for Jet<T, N> {
+    type Output = Jet<T, N>;
+
+    // The product rule.
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn mul(self, rhs: Self) -> Self::Output {
+        Jet::<T, N> {
+            value: self.value * rhs.value,
+            derivatives: std::array::from_fn(|i| self.derivatives[i] * rhs.value + self.value * rhs.derivatives[i])
+        }
+    }
+}
+
+impl<T: PartialOrd + Copy + Add<Output = T> + Mul<Output = T>, const N: usize> MulAssign for Jet<T, N> {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl<T: PartialOrd + Copy + Sub<Output = T> + Mul<Output = T> + Div<Output = T>, const N: usize> Div for Jet<T, N> {
+    type Output = Jet<T, N>;
+
+    // The quotient rule.
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Self) -> Self::Output {
+        let value = self.value / rhs.value;
+        Jet::<T, N> { value, derivatives: std::array::from_fn(|i| (self.derivatives[i] - value * rhs.derivatives[i]) / rhs.value) }
+    }
+}
+
+impl<T: PartialOrd + Copy + Sub<Output = T> + Mul<Output = T> + Div<Output = T>, const N: usize> DivAssign for Jet<T, N> {
+    fn div_assign(&mut self, rhs: Self) {
+        *self = *self / rhs;
+    }
+}
+
+impl<T: PartialOrd + Copy + Neg<Output = T>, const N: usize> Neg for Jet<T, N> {
+    type Output = Jet<T, N>;
+
+    fn neg(self) -> Self::Output {
+        Jet::<T, N> { value: -self.value, derivatives: self.derivatives.map(|d| -d) }
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>, const N: usize>
+    Vectorable<Jet<T, N>> for Jet<T, N>
+{
+    /// The derivative at zero is infinite and taken as zero instead, so
+    /// that the length of a zero vector stays finite.
+    fn sqrt(&self) -> Jet<T, N> {
+        let root = self.value.sqrt();
+        if root == T::ZERO {
+            return Jet::<T, N>::constant(root);
+        }
+        self.chain(root, T::HALF / root)
+    }
+
+    fn acos(&self) -> Jet<T, N> {
+        self.chain(self.value.acos(), -T::ONE / (T::ONE - self.value * self.value).sqrt())
+    }
+
+    fn sin(&self) -> Jet<T, N> {
+        self.chain(self.value.sin(), self.value.cos())
+    }
+
+    fn cos(&self) -> Jet<T, N> {
+        self.chain(self.value.cos(), -self.value.sin())
+    }
+
+    fn atan2(a: Jet<T, N>, b: Jet<T, N>) -> Jet<T, N> {
+        let length_squared = a.value * a.value + b.value * b.value;
+        Jet::<T, N> {
+            value: T::atan2(a.value, b.value),
+            derivatives: std::array::from_fn(|i| (b.value * a.derivatives[i] - a.value * b.derivatives[i]) / length_squared)
+        }
+    }
+
+    fn asin(&self) -> Jet<T, N> {
+        self.chain(self.value.asin(), T::ONE / (T::ONE - self.value * self.value).sqrt())
+    }
+
+    fn exp(&self) -> Jet<T, N> {
+        let value = self.value.exp();
+        self.chain(value, value)
+    }
+
+    fn max(a: Self, b: Self) -> Jet<T, N> {
+        if a.value > b.value { a } else { b }
+    }
+
+    fn min(a: Self, b: Self) -> Jet<T, N> {
+        if a.value < b.value { a } else { b }
+    }
+
+    fn abs(&self) -> Jet<T, N> {
+        if self.value < T::ZERO { -*self } else { *self }
+    }
+
+    fn from_f64(value: f64) -> Jet<T, N> {
+        Jet::<T, N>::constant(T::from_f64(value))
+    }
+
+    fn to_f64(&self) -> f64 {
+        self.value.to_f64()
+    }
+
+    const ZERO: Jet<T, N> = Jet::<T, N> { value: T::ZERO, derivatives: [T::ZERO; N] };
+    const ONE: Jet<T, N> = Jet::<T, N> { value: T::ONE, derivatives: [T::ZERO; N] };
+    const TWO: Jet<T, N> = Jet::<T, N> { value: T::TWO, derivatives: [T::ZERO; N] };
+    const HALF: Jet<T, N> = Jet::<T, N> { value: T::HALF, derivatives: [T::ZERO; N] };
+    const QUARTER: Jet<T, N> = Jet::<T, N> { value: T::QUARTER, derivatives: [T::ZERO; N] };
+    const PI: Jet<T, N> = Jet::<T, N> { value: T::PI, derivatives: [T::ZERO; N] };
+    const EPSILON: Jet<T, N> = Jet::<T, N> { value: T::EPSILON, derivatives: [T::ZERO; N] };
+    const TINY: Jet<T, N> = Jet::<T, N> { value: T::TINY, derivatives: [T::ZERO; N] };
+}
@@ -0,0 +1,368 @@
+use std::ops::{Add, AddAssign, Sub, Neg, Index, IndexMut};
+
+use crate::glmath::*;
+
+/// A vector whose length is only known at runtime.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DVector<T: PartialOrd + Copy> {
+    pub data: Vec<T>
+}
+
+/// A matrix whose size is only known at runtime, stored column major like
+/// the fixed size matrices.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DMatrix<T: PartialOrd + Copy> {
+    pub rows: usize,
+    pub columns: usize,
+    pub data: Vec<T>
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    DVector<T>
+{
+    pub fn zeros(len: usize) -> DVector<T> {
+        DVector::<T> { data: vec![T::ZERO; len] }
+    }
+
+    pub fn from_vec(data: Vec<T>) -> DVector<T> {
+        DVector::<T> { data }
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Packs points into one vector, three elements per point.
+    pub fn from_vec3s(points: &[Vec3<T>]) -> DVector<T> {
+        DVector::<T> { data: points.iter().flat_map(|p| [p.x, p.y, p.z]).collect() }
+    }
+
+    /// Unpacks consecutive triples into points, ignoring a partial triple at
+    /// the end.
+    pub fn to_vec3s(&self) -> Vec<Vec3<T>> {
+        self.data.chunks_exact(3).map(|c| Vec3::<T>::new(c[0], c[1], c[2])).collect()
+    }
+
+    /// The point stored at elements 3 * index to 3 * index + 2.
+    pub fn get_vec3(&self, index: usize) -> Vec3<T> {
+        Vec3::<T>::new(self.data[3 * index], self.data[3 * index + 1], self.data[3 * index + 2])
+    }
+
+    pub fn set_vec3(&mut self, index: usize, v: Vec3<T>) {
+        self.data[3 * index..3 * index + 3].copy_from_slice(&[v.x, v.y, v.z]);
+    }
+
+    /// BLAS dot.
+    pub fn dot(&self, other: &DVector<T>) -> T {
+        debug_assert_eq!(self.len(), other.len());
+        self.data.iter().zip(&other.data).fold(T::ZERO, |sum, (a, b)| sum + *a * *b)
+    }
+
+    pub fn length_squared(&self) -> T {
+        self.dot(self)
+    }
+
+    /// BLAS nrm2.
+    pub fn length(&self) -> T {
+        self.length_squared().sqrt()
+    }
+
+    /// BLAS asum, the sum of absolute values.
+    pub fn abs_sum(&self) -> T {
+        self.data.iter().fold(T::ZERO, |sum, v| sum + v.abs())
+    }
+
+    /// BLAS axpy, self += alpha * x.
+    pub fn axpy(&mut self, alpha: T, x: &DVector<T>) {
+        debug_assert_eq!(self.len(), x.len());
+        for (value, x) in self.data.iter_mut().zip(&x.data) {
+            *value += alpha * *x;
+        }
+    }
+
+    /// BLAS scal, self *= alpha.
+    pub fn scale(&mut self, alpha: T) {
+        for value in self.data.iter_mut() {
+            *value = *value * alpha;
+        }
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    DMatrix<T>
+{
+    pub fn zeros(rows: usize, columns: usize) -> DMatrix<T> {
+        DMatrix::<T> { rows, columns, data: vec![T::ZERO; rows * columns] }
+    }
+
+    pub fn identity(size: usize) -> DMatrix<T> {
+        let mut result = Self::zeros(size, size);
+        for i in 0..size {
+            result[(i, i)] = T::ONE;
+        }
+        result
+    }
+
+    /// Constructs a matrix from row major values, matching how it reads on
+    /// paper.
+    pub fn from_rows(rows: usize, columns: usize, values: &[T]) -> DMatrix<T> {
+        assert_eq!(values.len(), rows * columns);
+
+        let mut result = Self::zeros(rows, columns);
+        for row in 0..rows {
+            for column in 0..columns {
+                result[(row, column)] = values[row * columns + column];
+            }
+        }
+        result
+    }
+
+    pub fn get_transposed(&self) -> DMatrix<T> {
+        let mut result = Self::zeros(self.columns, self.rows);
+        for column in 0..self.columns {
+            for row in 0..self.rows {
+                result[(column, row)] = self[(row, column)];
+            }
+        }
+        result
+    }
+
+    /// The 4x4 block with its top left corner at (row, column).
+    pub fn get_mat44(&self, row: usize, column: usize) -> Mat44<T> {
+        let mut result = Mat44::<T>::ZERO;
+        for (j, block_column) in result.data.iter_mut().enumerate() {
+            for (i, value) in block_column.iter_mut().enumerate() {
+                *value = self[(row + i, column + j)];
+            }
+        }
+        result
+    }
+
+    pub fn set_mat44(&mut self, row: usize, column: usize, m: &Mat44<T>) {
+        for (j, block_column) in m.data.iter().enumerate() {
+            for (i, value) in block_column.iter().enumerate() {
+                self[(row + i, column + j)] = *value;
+            }
+        }
+    }
+
+    /// BLAS gemv, y = alpha * self * x + beta * y. As in BLAS, y is
+    /// overwritten without being read when beta is zero.
+    pub fn gemv(&self, alpha: T, x: &DVector<T>, beta: T, y: &mut DVector<T>) {
+        assert_eq!(self.columns, x.len());
+        assert_eq!(self.rows, y.len());
+
+        if beta == T::ZERO {
+            y.data.fill(T::ZERO);
+        }
+        else {
+            y.scale(beta);
+        }
+
+        if self.rows == 0 {
+            return;
+        }
+
+        for (column, x) in self.data.chunks_exact(self.rows).zip(&x.data) {
+            let scale = alpha * *x;
+            for (y, a) in y.data.iter_mut().zip(column) {
+                *y += scale * *a;
+            }
+        }
+    }
+
+    /// BLAS gemm, c = alpha * self * b + beta * c. As in BLAS, c is
+    /// overwritten without being read when beta is zero.
+    pub fn gemm(&self, alpha: T, b: &DMatrix<T>, beta: T, c: &mut DMatrix<T>) {
+        assert_eq!(self.columns, b.rows);
+        assert_eq!((self.rows, b.columns), (c.rows, c.columns));
+
+        for column in 0..b.columns {
+            for row in 0..self.rows {
+                let sum = (0..self.columns).fold(T::ZERO, |sum, k| sum + self[(row, k)] * b[(k, column)]);
+                c[(row, column)] = if beta == T::ZERO { alpha * sum } else { alpha * sum + beta * c[(row, column)] };
+            }
+        }
+    }
+
+    /// Solves self * x = b by LU decomposition with partial pivoting.
+    /// Returns None if the matrix is singular.
+    pub fn solve(&self, b: &DVector<T>) -> Option<DVector<T>> {
+        assert_eq!(self.rows, self.columns);
+        assert_eq!(self.rows, b.len());
+
+        let n = self.rows;
+        let mut a = self.clone();
+        let mut x = b.clone();
+
+        let scale = self.data.iter().fold(T::ZERO, |max, v| T::max(max, v.abs()));
+        let tolerance = scale * T::EPSILON * T::from_f64(n as f64);
+
+        for k in 0..n {
+            let pivot = (k..n).fold(k, |best, row| if a[(row, k)].abs() > a[(best, k)].abs() { row } else { best });
+
+            if a[(pivot, k)].abs() <= tolerance || scale == T::ZERO {
+                return None;
+            }
+
+            if pivot != k {
+                for column in 0..n {
+                    let (upper, lower) = (a[(k, column)], a[(pivot, column)]);
+                    a[(k, column)] = lower;
+                    a[(pivot, column)] = upper;
+                }
+                x.data.swap(k, pivot);
+            }
+
+            // Eliminate below the pivot, applying the same steps to x.
+            for row in k + 1..n {
+                let factor = a[(row, k)] / a[(k, k)];
+                for column in k..n {
+                    let value = a[(k, column)];
+                    a[(row, column)] = a[(row, column)] - factor * value;
+                }
+                x.data[row] = x.data[row] - factor * x.data[k];
+            }
+        }
+
+        for row in (0..n).rev() {
+            let sum = (row + 1..n).fold(x.data[row], |sum, column| sum - a[(row, column)] * x.data[column]);
+            x.data[row] = sum / a[(row, row)];
+        }
+
+        Some(x)
+    }
+
+    /// Solves self * x = b for a symmetric positive definite matrix by
+    /// conjugate gradients, starting from zero. Returns None if the residual
+    /// is not below tolerance * |b| after max_iterations.
+    pub fn conjugate_gradient(&self, b: &DVector<T>, tolerance: T, max_iterations: usize) -> Option<DVector<T>> {
+        assert_eq!(self.rows, self.columns);
+
+        let mut x = DVector::<T>::zeros(b.len());
+        let mut residual = b.clone();
+        let mut direction = residual.clone();
+        let mut residual_squared = residual.length_squared();
+        let threshold = tolerance * tolerance * b.length_squared();
+
+        for _ in 0..max_iterations {
+            if residual_squared <= threshold {
+                return Some(x);
+            }
+
+            let product = self * &direction;
+            let curvature = direction.dot(&product);
+            if curvature <= T::ZERO {
+                return None;
+            }
+
+            let step = residual_squared / curvature;
+            x.axpy(step, &direction);
+            residual.axpy(-step, &product);
+
+            let next_squared = residual.length_squared();
+            direction.scale(next_squared / residual_squared);
+            direction.axpy(T::ONE, &residual);
+            residual_squared = next_squared;
+        }
+
+        if residual_squared <= threshold { Some(x) } else { None }
+    }
+}
+
+impl<T: PartialOrd + Copy> Index<usize> for DVector<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.data[index]
+    }
+}
+
+impl<T: PartialOrd + Copy> IndexMut<usize> for DVector<T> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.data[index]
+    }
+}
+
+impl<T: PartialOrd + Copy> Index<(usize, usize)> for DMatrix<T> {
+    type Output = T;
+
+    /// Indexes by (row, column).
+    fn index(&self, index: (usize, usize)) -> &Self::Output {
+        debug_assert!(index.0 < self.rows && index.1 < self.columns);
+        &self.data[index.1 * self.rows + index.0]
+    }
+}
+
+impl<T: PartialOrd + Copy> IndexMut<(usize, usize)> for DMatrix<T> {
+    fn index_mut(&mut self, index: (usize, usize)) -> &mut Self::Output {
+        debug_assert!(index.0 < self.rows && index.1 < self.columns);
+        &mut self.data[index.1 * self.rows + index.0]
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    Mul<&DVector<T>> for &DMatrix<T>
+{
+    type Output = DVector<T>;
+
+    fn mul(self, rhs: &DVector<T>) -> Self::Output {
+        let mut result = DVector::<T>::zeros(self.rows);
+        self.gemv(T::ONE, rhs, T::ZERO, &mut result);
+        result
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    Mul<&DMatrix<T>> for &DMatrix<T>
+{
+    type Output = DMatrix<T>;
+
+    fn mul(self, rhs: &DMatrix<T>) -> Self::Output {
+        let mut result = DMatrix::<T>::zeros(self.rows, rhs.columns);
+        self.gemm(T::ONE, rhs, T::ZERO, &mut result);
+        result
+    }
+}
+
+impl<T: PartialOrd + Copy + Add<Output = T>> Add<&DVector<T>> for &DVector<T> {
+    type Output = DVector<T>;
+
+    fn add(self, rhs: &DVector<T>) -> Self::Output {
+        assert_eq!(self.data.len(), rhs.data.len());
+        DVector::<T> { data: self.data.iter().zip(&rhs.data).map(|(a, b)| *a + *b).collect() }
+    }
+}
+
+impl<T: PartialOrd + Copy + Sub<Output = T>> Sub<&DVector<T>> for &DVector<T> {
+    type Output = DVector<T>;
+
+    fn sub(self, rhs: &DVector<T>) -> Self::Output {
+        assert_eq!(self.data.len(), rhs.data.len());
+        DVector::<T> { data: self.data.iter().zip(&rhs.data).map(|(a, b)| *a - *b).collect() }
+    }
+}
+
+impl<T: PartialOrd + Copy> From<Vec3<T>> for DVector<T> {
+    fn from(v: Vec3<T>) -> Self {
+        DVector::<T> { data: vec![v.x, v.y, v.z] }
+    }
+}
+
+impl<T: PartialOrd + Copy> From<Mat44<T>> for DMatrix<T> {
+    fn from(m: Mat44<T>) -> Self {
+        DMatrix::<T> { rows: 4, columns: 4, data: m.data.concat() }
+    }
+}
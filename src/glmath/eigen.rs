@@ -0,0 +1,135 @@
+use std::ops::{Add, AddAssign, Sub, Neg};
+
+use crate::glmath::*;
+
+/// Sweeps of the Jacobi method before giving up. Symmetric 3x3 matrices
+/// converge in a handful.
+const MAX_SWEEPS: usize = 32;
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    Mat22<T>
+{
+    /// Eigenvalues and eigenvectors of a symmetric matrix, found with a
+    /// single Jacobi rotation. Only the upper triangle is read. The values
+    /// are sorted largest first and the columns of the returned rotation
+    /// matrix are the matching unit eigenvectors.
+    pub fn symmetric_eigen(&self) -> (Vec2<T>, Mat22<T>) {
+        let (a, b, d) = (self.data[0][0], self.data[1][0], self.data[1][1]);
+
+        // The angle that zeroes the off diagonal term.
+        let angle = T::atan2(T::TWO * b, a - d) * T::HALF;
+        let (c, s) = (angle.cos(), angle.sin());
+
+        let first = a * c * c + T::TWO * b * c * s + d * s * s;
+        let second = a * s * s - T::TWO * b * c * s + d * c * c;
+        let (u, v) = (Vec2::<T>::new(c, s), Vec2::<T>::new(-s, c));
+
+        if first >= second {
+            (Vec2::<T>::new(first, second), Mat22::<T>::from_axes(u, v))
+        }
+        else {
+            (Vec2::<T>::new(second, first), Mat22::<T>::from_axes(v, -u))
+        }
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    Mat33<T>
+{
+    /// Eigenvalues and eigenvectors of a symmetric matrix by the cyclic
+    /// Jacobi method. Only the upper triangle is read. The values are sorted
+    /// largest first and the columns of the returned rotation matrix are the
+    /// matching unit eigenvectors.
+    pub fn symmetric_eigen(&self) -> (Vec3<T>, Mat33<T>) {
+        // a[row][column], filled from the upper triangle.
+        let upper = |row: usize, column: usize| if row <= column { self.data[column][row] } else { self.data[row][column] };
+        let mut a = [0, 1, 2].map(|row| [0, 1, 2].map(|column| upper(row, column)));
+
+        let mut vectors = Mat33::<T>::IDENTITY;
+
+        for _ in 0..MAX_SWEEPS {
+            let off = a[0][1] * a[0][1] + a[0][2] * a[0][2] + a[1][2] * a[1][2];
+            let diagonal = a[0][0] * a[0][0] + a[1][1] * a[1][1] + a[2][2] * a[2][2];
+
+            if off <= T::EPSILON * T::EPSILON * diagonal || off == T::ZERO {
+                break;
+            }
+
+            for (p, q) in [(0, 1), (0, 2), (1, 2)] {
+                if a[p][q] == T::ZERO {
+                    continue;
+                }
+
+                // The rotation zeroing a[p][q], with |t| <= 1 for stability.
+                let theta = (a[q][q] - a[p][p]) / (T::TWO * a[p][q]);
+                let sign = if theta < T::ZERO { -T::ONE } else { T::ONE };
+                let t = sign / (theta.abs() + (theta * theta + T::ONE).sqrt());
+                let c = T::ONE / (t * t + T::ONE).sqrt();
+                let s = t * c;
+
+                let shift = t * a[p][q];
+                (a[p][p], a[q][q]) = (a[p][p] - shift, a[q][q] + shift);
+                a[p][q] = T::ZERO;
+                a[q][p] = T::ZERO;
+
+                let r = 3 - p - q;
+                let (a_rp, a_rq) = (a[r][p], a[r][q]);
+                a[r][p] = c * a_rp - s * a_rq;
+                a[p][r] = a[r][p];
+                a[r][q] = s * a_rp + c * a_rq;
+                a[q][r] = a[r][q];
+
+                for row in 0..3 {
+                    let (v_p, v_q) = (vectors.data[p][row], vectors.data[q][row]);
+                    vectors.data[p][row] = c * v_p - s * v_q;
+                    vectors.data[q][row] = s * v_p + c * v_q;
+                }
+            }
+        }
+
+        // Sort largest first, then flip the last vector if needed so the
+        // vectors form a rotation.
+        let mut order = [0, 1, 2];
+        order.sort_by(|&i, &j| a[j][j].partial_cmp(&a[i][i]).unwrap_or(std::cmp::Ordering::Equal));
+
+        let values = Vec3::<T>::new(a[order[0]][order[0]], a[order[1]][order[1]], a[order[2]][order[2]]);
+        let (x, y, mut z) = (vectors.column(order[0]), vectors.column(order[1]), vectors.column(order[2]));
+
+        if x * (y % z) < T::ZERO {
+            z = -z;
+        }
+
+        (values, Mat33::<T>::from_axes(x, y, z))
+    }
+}
+
+/// The covariance matrix of a set of points about their mean. Empty sets
+/// give zero.
+pub fn covariance<T>(points: &[Vec3<T>]) -> Mat33<T>
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T> + Neg<Output = T>
+{
+    if points.is_empty() {
+        return Mat33::<T>::ZERO;
+    }
+
+    let count = T::from_f64(points.len() as f64);
+    let mean = points.iter().fold(Vec3::<T>::ZERO, |sum, p| sum + *p) / count;
+
+    let mut result = Mat33::<T>::ZERO;
+    for p in points {
+        let d = *p - mean;
+        for column in 0..3 {
+            for row in 0..3 {
+                result.data[column][row] += d[row] * d[column] / count;
+            }
+        }
+    }
+
+    result
+}
@@ -0,0 +1,209 @@
+use std::ops::{Add, Sub, Neg};
+
+use crate::glmath::*;
+use crate::glmath::closest::point_triangle;
+
+/// Where a shape lies relative to a frustum.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Containment {
+    Inside,
+    Intersect,
+    Outside
+}
+
+/// A view volume bounded by six planes facing inwards.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct Frustum<T: PartialOrd + Copy> {
+    /// Left, right, bottom, top, near and far planes in that order.
+    pub planes: [Plane<T>; 6],
+    /// The eight corners, near plane first. None when the planes do not
+    /// meet in eight points, as with an infinitely far plane.
+    pub corners: Option<[Vec3<T>; 8]>
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    Frustum<T>
+{
+    pub const LEFT: usize = 0;
+    pub const RIGHT: usize = 1;
+    pub const BOTTOM: usize = 2;
+    pub const TOP: usize = 3;
+    pub const NEAR: usize = 4;
+    pub const FAR: usize = 5;
+
+    /// Builds a frustum from six inward facing planes.
+    pub fn from_planes(planes: [Plane<T>; 6]) -> Frustum<T> {
+        let corners: [Option<Vec3<T>>; 8] = std::array::from_fn(|i| {
+            let x = if i & 1 == 0 { &planes[Self::LEFT] } else { &planes[Self::RIGHT] };
+            let y = if i & 2 == 0 { &planes[Self::BOTTOM] } else { &planes[Self::TOP] };
+            let z = if i & 4 == 0 { &planes[Self::NEAR] } else { &planes[Self::FAR] };
+
+            Plane::<T>::intersect_planes(x, y, z)
+        });
+
+        let corners = corners.iter().all(Option::is_some).then(|| corners.map(Option::unwrap));
+
+        Frustum::<T> { planes, corners }
+    }
+
+    /// Extracts the planes of a view projection matrix with the Gribb–Hartmann
+    /// method. Clip space depth is expected to range from -w to w.
+    pub fn from_view_projection(view_proj: &Mat44<T>) -> Frustum<T> {
+        let m = &view_proj.data;
+        let row = |i: usize| Vec4::<T>::new(m[0][i], m[1][i], m[2][i], m[3][i]);
+        let to_plane = |v: Vec4<T>| Plane::<T>::new(v.xyz(), v.w).get_normalized();
+
+        let (r0, r1, r2, r3) = (row(0), row(1), row(2), row(3));
+
+        Frustum::<T>::from_planes([
+            to_plane(r3 + r0),
+            to_plane(r3 - r0),
+            to_plane(r3 + r1),
+            to_plane(r3 - r1),
+            to_plane(r3 + r2),
+            to_plane(r3 - r2)
+        ])
+    }
+
+    /// Points are either inside or outside, never intersecting.
+    pub fn test_point(&self, point: Vec3<T>) -> Containment {
+        for plane in &self.planes {
+            if plane.signed_distance(point) < T::ZERO {
+                return Containment::Outside;
+            }
+        }
+
+        Containment::Inside
+    }
+
+    /// Tests a sphere against each plane. Spheres near the edges of the
+    /// frustum may be reported as intersecting while lying outside of it.
+    pub fn test_sphere(&self, center: Vec3<T>, radius: T) -> Containment {
+        let mut result = Containment::Inside;
+
+        for plane in &self.planes {
+            let dist = plane.signed_distance(center);
+
+            if dist < -radius {
+                return Containment::Outside;
+            }
+
+            if dist < radius {
+                result = Containment::Intersect;
+            }
+        }
+
+        result
+    }
+
+    /// Conservative box test against each plane using the box's closest
+    /// and furthest corners. Boxes near the edges of the frustum may be
+    /// reported as intersecting while lying outside of it.
+    pub fn test_aabb(&self, aabb: &Aabb3<T>) -> Containment {
+        let mut result = Containment::Inside;
+
+        for plane in &self.planes {
+            let mut positive = aabb.min;
+            let mut negative = aabb.max;
+
+            for axis in 0..3 {
+                if plane.normal[axis] >= T::ZERO {
+                    positive[axis] = aabb.max[axis];
+                    negative[axis] = aabb.min[axis];
+                }
+            }
+
+            if plane.signed_distance(positive) < T::ZERO {
+                return Containment::Outside;
+            }
+
+            if plane.signed_distance(negative) < T::ZERO {
+                result = Containment::Intersect;
+            }
+        }
+
+        result
+    }
+
+    /// Exact box test by the separating axis theorem. Refines the
+    /// conservative test with the faces of the box and the cross products
+    /// of the frustum's edges with the box's axes. Falls back to the
+    /// conservative test when the frustum has no corners.
+    pub fn test_aabb_exact(&self, aabb: &Aabb3<T>) -> Containment {
+        let result = self.test_aabb(aabb);
+
+        let Some(corners) = &self.corners else {
+            return result;
+        };
+
+        if result != Containment::Intersect {
+            return result;
+        }
+
+        for axis in 0..3 {
+            if corners.iter().all(|c| c[axis] > aabb.max[axis]) ||
+                corners.iter().all(|c| c[axis] < aabb.min[axis]) {
+                return Containment::Outside;
+            }
+        }
+
+        let (center, half_extents) = (aabb.center(), aabb.half_extents());
+
+        for edge in Self::edges(corners) {
+            for box_axis in [Vec3::<T>::X, Vec3::<T>::Y, Vec3::<T>::Z] {
+                let axis = edge % box_axis;
+                let middle = center * axis;
+                let radius = half_extents.x * axis.x.abs() + half_extents.y * axis.y.abs() + half_extents.z * axis.z.abs();
+
+                let (mut min, mut max) = (corners[0] * axis, corners[0] * axis);
+                for corner in &corners[1..] {
+                    min = T::min(min, *corner * axis);
+                    max = T::max(max, *corner * axis);
+                }
+
+                if max < middle - radius || min > middle + radius {
+                    return Containment::Outside;
+                }
+            }
+        }
+
+        Containment::Intersect
+    }
+
+    /// Exact sphere test. Refines the conservative test with the distance
+    /// from the center to the closest point on the frustum's faces. Falls
+    /// back to the conservative test when the frustum has no corners.
+    pub fn test_sphere_exact(&self, center: Vec3<T>, radius: T) -> Containment {
+        let result = self.test_sphere(center, radius);
+
+        let Some(c) = &self.corners else {
+            return result;
+        };
+
+        if result != Containment::Intersect || self.test_point(center) == Containment::Inside {
+            return result;
+        }
+
+        // The center is outside, so the closest point lies on a face.
+        let faces = [[0, 2, 6, 4], [1, 3, 7, 5], [0, 1, 5, 4], [2, 3, 7, 6], [0, 1, 3, 2], [4, 5, 7, 6]];
+
+        for face in faces {
+            for (a, b, d) in [(face[0], face[1], face[2]), (face[0], face[2], face[3])] {
+                if point_triangle(center, c[a], c[b], c[d]).distance_sq <= radius * radius {
+                    return Containment::Intersect;
+                }
+            }
+        }
+
+        Containment::Outside
+    }
+
+    /// The twelve edges of the frustum as vectors between its corners.
+    fn edges(corners: &[Vec3<T>; 8]) -> impl Iterator<Item = Vec3<T>> + '_ {
+        [1, 2, 4].into_iter().flat_map(move |bit| {
+            (0..8).filter(move |i| i & bit == 0).map(move |i| corners[i | bit] - corners[i])
+        })
+    }
+}
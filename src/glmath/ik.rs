@@ -0,0 +1,228 @@
+use std::ops::{Add, AddAssign, Sub, Neg};
+
+use crate::glmath::*;
+
+/// A limit on the local rotation of a joint. Axes are given in the joint's
+/// local space.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub enum JointLimit<T: PartialOrd + Copy> {
+    #[default]
+    None,
+    /// Only rotation about the axis, between min and max radians.
+    Hinge { axis: Vec3<T>, min: T, max: T },
+    /// The axis may swing at most angle radians away from its rest direction.
+    Cone { axis: Vec3<T>, angle: T },
+    /// Rotation about the axis is kept between min and max radians.
+    Twist { axis: Vec3<T>, min: T, max: T }
+}
+
+/// A chain of joints, each rotated relative to the one before it. Joint i
+/// sits at offsets[i - 1] in the local space of joint i - 1, and the end
+/// effector at the last offset from the last joint.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IkChain<T: PartialOrd + Copy> {
+    pub root_position: Vec3<T>,
+    /// Rotation of the space the first joint's rotation is relative to.
+    pub root_rotation: Quat<T>,
+    pub offsets: Vec<Vec3<T>>,
+    /// Local rotation of each joint.
+    pub rotations: Vec<Quat<T>>,
+    pub limits: Vec<JointLimit<T>>
+}
+
+/// The local rotations found by a solver.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IkSolution<T: PartialOrd + Copy> {
+    pub rotations: Vec<Quat<T>>,
+    /// Distance left between the end effector and the target.
+    pub error: T
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    JointLimit<T>
+{
+    /// The closest rotation to the given one allowed by the limit.
+    pub fn apply(&self, rotation: Quat<T>) -> Quat<T> {
+        match *self {
+            JointLimit::None => rotation,
+            JointLimit::Hinge { axis, min, max } => {
+                let angle = T::min(T::max(rotation.twist_angle(axis), min), max);
+                Quat::<T>::from_axis_angle(axis, angle)
+            }
+            JointLimit::Cone { axis, angle } => rotation.clamp_swing_cone(axis, angle),
+            JointLimit::Twist { axis, min, max } => rotation.clamp_twist(axis, min, max)
+        }
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    IkChain<T>
+{
+    /// Creates an unlimited chain.
+    pub fn new(root_position: Vec3<T>, root_rotation: Quat<T>, offsets: &[Vec3<T>], rotations: &[Quat<T>]) -> IkChain<T> {
+        IkChain::<T> {
+            root_position,
+            root_rotation,
+            offsets: offsets.to_vec(),
+            rotations: rotations.to_vec(),
+            limits: vec![JointLimit::None; offsets.len()]
+        }
+    }
+
+    /// The sum of the bone lengths.
+    pub fn length(&self) -> T {
+        self.offsets.iter().fold(T::ZERO, |sum, offset| sum + offset.length())
+    }
+
+    /// The world rotation of every joint.
+    pub fn world_rotations(&self) -> Vec<Quat<T>> {
+        let mut parent = self.root_rotation;
+
+        self.rotations.iter().map(|local| {
+            parent *= *local;
+            parent
+        }).collect()
+    }
+
+    /// The world position of every joint followed by the end effector.
+    pub fn positions(&self) -> Vec<Vec3<T>> {
+        let mut positions = vec![self.root_position];
+
+        for (rotation, offset) in self.world_rotations().iter().zip(&self.offsets) {
+            positions.push(positions[positions.len() - 1] + *rotation * *offset);
+        }
+
+        positions
+    }
+
+    pub fn end_effector(&self) -> Vec3<T> {
+        self.positions()[self.offsets.len()]
+    }
+
+    /// Analytic solver for the first two bones, keeping the middle joint in
+    /// the plane towards the pole. Joint limits are not applied. Targets out
+    /// of reach straighten the chain towards them.
+    pub fn solve_two_bone(&self, target: Vec3<T>, pole: Vec3<T>) -> IkSolution<T> {
+        let mut chain = self.clone();
+        let positions = chain.positions();
+        let (a, b, c) = (positions[0], positions[1], positions[2]);
+
+        let upper = (b - a).length();
+        let lower = (c - b).length();
+        let reach = T::min(T::max((target - a).length(), (upper - lower).abs()), upper + lower);
+
+        // Bend the middle joint to the angle given by the law of cosines.
+        let current = (a - b).angle_between(&(c - b));
+        let cos = (upper * upper + lower * lower - reach * reach) / (T::TWO * upper * lower);
+        let desired = T::min(T::max(cos, -T::ONE), T::ONE).acos();
+
+        let mut bend_axis = (c - b) % (a - b);
+        if bend_axis.length_sq() <= T::EPSILON {
+            bend_axis = (c - b) % (pole - b);
+        }
+        if bend_axis.length_sq() <= T::EPSILON {
+            bend_axis = (c - b).any_perpendicular();
+        }
+
+        chain.rotate_world(1, Quat::<T>::from_axis_angle(bend_axis.get_normalized(), current - desired));
+
+        // Aim the root so the end effector points at the target.
+        let positions = chain.positions();
+        chain.rotate_world(0, Quat::<T>::from_to(positions[2] - a, target - a));
+
+        // Swing the middle joint around the root to end axis towards the pole.
+        let positions = chain.positions();
+        let axis = (positions[2] - a).get_normalized();
+        let flatten = |v: Vec3<T>| v - axis * (v * axis);
+        let (mid, pole) = (flatten(positions[1] - a), flatten(pole - a));
+
+        if mid.length_sq() > T::EPSILON && pole.length_sq() > T::EPSILON {
+            let angle = T::atan2((mid % pole) * axis, mid * pole);
+            chain.rotate_world(0, Quat::<T>::from_axis_angle(axis, angle));
+        }
+
+        chain.solution(target)
+    }
+
+    /// Forward and backward reaching inverse kinematics. Joint limits are
+    /// applied after every iteration.
+    pub fn solve_fabrik(&self, target: Vec3<T>, iterations: usize, tolerance: T) -> IkSolution<T> {
+        let mut chain = self.clone();
+        let lengths: Vec<T> = self.offsets.iter().map(|offset| offset.length()).collect();
+        let count = lengths.len();
+
+        for _ in 0..iterations {
+            let mut positions = chain.positions();
+
+            if (positions[count] - target).length() <= tolerance {
+                break;
+            }
+
+            // Backward: pin the end effector to the target.
+            positions[count] = target;
+            for i in (0..count).rev() {
+                let direction = (positions[i] - positions[i + 1]).get_normalized();
+                positions[i] = positions[i + 1] + direction * lengths[i];
+            }
+
+            // Forward: pin the root back in place.
+            positions[0] = chain.root_position;
+            for i in 0..count {
+                let direction = (positions[i + 1] - positions[i]).get_normalized();
+                positions[i + 1] = positions[i] + direction * lengths[i];
+            }
+
+            // Turn each bone towards its new position, root first.
+            for i in 0..count {
+                let current = chain.positions();
+                chain.rotate_world(i, Quat::<T>::from_to(current[i + 1] - current[i], positions[i + 1] - current[i]));
+                chain.rotations[i] = chain.limits[i].apply(chain.rotations[i]);
+            }
+        }
+
+        chain.solution(target)
+    }
+
+    /// Cyclic coordinate descent, turning each joint from the end towards the
+    /// target in turn. Joint limits are applied after every step.
+    pub fn solve_ccd(&self, target: Vec3<T>, iterations: usize, tolerance: T) -> IkSolution<T> {
+        let mut chain = self.clone();
+        let count = self.offsets.len();
+
+        for _ in 0..iterations {
+            if (chain.end_effector() - target).length() <= tolerance {
+                break;
+            }
+
+            for i in (0..count).rev() {
+                let positions = chain.positions();
+                let to_end = positions[count] - positions[i];
+                let to_target = target - positions[i];
+
+                if to_end.length_sq() > T::EPSILON && to_target.length_sq() > T::EPSILON {
+                    chain.rotate_world(i, Quat::<T>::from_to(to_end, to_target));
+                    chain.rotations[i] = chain.limits[i].apply(chain.rotations[i]);
+                }
+            }
+        }
+
+        chain.solution(target)
+    }
+
+    /// Applies a world space rotation to a joint, carrying its children.
+    fn rotate_world(&mut self, joint: usize, rotation: Quat<T>) {
+        let parent = if joint == 0 { self.root_rotation } else { self.world_rotations()[joint - 1] };
+
+        // The world rotation parent * local becomes rotation * parent * local.
+        self.rotations[joint] = (parent.conjugate() * rotation * parent * self.rotations[joint]).get_normalized();
+    }
+
+    fn solution(self, target: Vec3<T>) -> IkSolution<T> {
+        let error = (self.end_effector() - target).length();
+        IkSolution::<T> { rotations: self.rotations, error }
+    }
+}
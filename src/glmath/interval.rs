@@ -0,0 +1,353 @@
+use std::{fmt::Display, ops::{Add, AddAssign, Sub, SubAssign, MulAssign, Neg}};
+
+use crate::glmath::*;
+
+/// A closed range [lo, hi] guaranteed to contain the exact result of the
+/// operations that produced it. Rust cannot switch the rounding mode, so
+/// results are widened outward by a relative epsilon and the smallest
+/// subnormal instead, which covers the rounding error of the operation.
+/// Comparisons are only ordered when the intervals do not overlap.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct Interval<T: PartialOrd + Copy> {
+    pub lo: T,
+    pub hi: T
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    Interval<T>
+{
+    /// The interval [lo, hi], swapping the bounds if they are reversed.
+    pub fn new(lo: T, hi: T) -> Interval<T> {
+        if lo <= hi { Interval::<T> { lo, hi } } else { Interval::<T> { lo: hi, hi: lo } }
+    }
+
+    /// The degenerate interval holding exactly one value.
+    pub fn point(value: T) -> Interval<T> {
+        Interval::<T> { lo: value, hi: value }
+    }
+
+    pub fn width(&self) -> T {
+        self.hi - self.lo
+    }
+
+    pub fn midpoint(&self) -> T {
+        (self.lo + self.hi) * T::HALF
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        self.lo <= value && value <= self.hi
+    }
+
+    pub fn contains_zero(&self) -> bool {
+        self.contains(T::ZERO)
+    }
+
+    /// The smallest interval holding both.
+    pub fn hull(&self, other: &Interval<T>) -> Interval<T> {
+        Interval::<T> { lo: T::min(self.lo, other.lo), hi: T::max(self.hi, other.hi) }
+    }
+
+    /// Widens the bounds by one relative epsilon, enough to cover the
+    /// rounding of a correctly rounded operation, plus the smallest
+    /// subnormal for results that underflow toward zero.
+    fn outward(lo: T, hi: T) -> Interval<T> {
+        Interval::<T> { lo: lo - lo.abs() * T::EPSILON - T::TINY, hi: hi + hi.abs() * T::EPSILON + T::TINY }
+    }
+
+    /// Widens further for library functions, which are not correctly
+    /// rounded but stay within an ulp or two.
+    fn outward_transcendental(lo: T, hi: T) -> Interval<T> {
+        let slack = T::TWO * T::TWO * T::EPSILON;
+        Interval::<T> { lo: lo - lo.abs() * slack - T::EPSILON * T::EPSILON, hi: hi + hi.abs() * slack + T::EPSILON * T::EPSILON }
+    }
+
+    /// True if offset + 2 k pi lies in the interval for some integer k. Close
+    /// calls count as inside, which only widens the result.
+    fn contains_period_point(&self, offset: f64) -> bool {
+        let period = std::f64::consts::TAU;
+        let k = ((self.lo.to_f64() - offset) / period).ceil();
+        let point = offset + k * period;
+        let slack = 1e-9 * (1.0 + point.abs());
+
+        point - slack <= self.hi.to_f64() || offset + (k - 1.0) * period + slack >= self.lo.to_f64()
+    }
+}
+
+impl<T: PartialOrd + Copy + Display> Display for Interval<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}, {}]", self.lo, self.hi)
+    }
+}
+
+impl<T: PartialOrd + Copy> PartialOrd for Interval<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        if self.lo == other.lo && self.hi == other.hi {
+            Some(std::cmp::Ordering::Equal)
+        }
+        else if self.hi < other.lo {
+            Some(std::cmp::Ordering::Less)
+        }
+        else if self.lo > other.hi {
+            Some(std::cmp::Ordering::Greater)
+        }
+        else {
+            None
+        }
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    Add for Interval<T>
+{
+    type Output = Interval<T>;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Interval::<T>::outward(self.lo + rhs.lo, self.hi + rhs.hi)
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    AddAssign for Interval<T>
+{
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    Sub for Interval<T>
+{
+    type Output = Interval<T>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Interval::<T>::outward(self.lo - rhs.hi, self.hi - rhs.lo)
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    SubAssign for Interval<T>
+{
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    Mul for Interval<T>
+{
+    type Output = Interval<T>;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        let products = [self.lo * rhs.lo, self.lo * rhs.hi, self.hi * rhs.lo, self.hi * rhs.hi];
+        let lo = products.iter().fold(products[0], |min, p| T::min(min, *p));
+        let hi = products.iter().fold(products[0], |max, p| T::max(max, *p));
+
+        Interval::<T>::outward(lo, hi)
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    MulAssign for Interval<T>
+{
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    Div for Interval<T>
+{
+    type Output = Interval<T>;
+
+    /// Dividing by an interval holding zero gives the whole line.
+    fn div(self, rhs: Self) -> Self::Output {
+        if rhs.contains_zero() {
+            let infinity = T::ONE / T::ZERO;
+            return Interval::<T> { lo: -infinity, hi: infinity };
+        }
+
+        let quotients = [self.lo / rhs.lo, self.lo / rhs.hi, self.hi / rhs.lo, self.hi / rhs.hi];
+        let lo = quotients.iter().fold(quotients[0], |min, q| T::min(min, *q));
+        let hi = quotients.iter().fold(quotients[0], |max, q| T::max(max, *q));
+
+        Interval::<T>::outward(lo, hi)
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    DivAssign for Interval<T>
+{
+    fn div_assign(&mut self, rhs: Self) {
+        *self = *self / rhs;
+    }
+}
+
+impl<T: PartialOrd + Copy + Neg<Output = T>> Neg for Interval<T> {
+    type Output = Interval<T>;
+
+    fn neg(self) -> Self::Output {
+        Interval::<T> { lo: -self.hi, hi: -self.lo }
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    Vectorable<Interval<T>> for Interval<T>
+{
+    /// Negative parts are clipped, as sqrt is only defined from zero.
+    fn sqrt(&self) -> Interval<T> {
+        let lo = T::max(self.lo, T::ZERO).sqrt();
+        let hi = T::max(self.hi, T::ZERO).sqrt();
+        Interval::<T>::outward_transcendental(lo, hi).clamped(T::ZERO, hi + hi)
+    }
+
+    fn acos(&self) -> Interval<T> {
+        let (lo, hi) = (T::max(self.lo, -T::ONE), T::min(self.hi, T::ONE));
+        Interval::<T>::outward_transcendental(hi.acos(), lo.acos()).clamped(T::ZERO, T::PI + T::PI * T::EPSILON)
+    }
+
+    fn sin(&self) -> Interval<T> {
+        if self.width().to_f64() >= std::f64::consts::TAU {
+            return Interval::<T> { lo: -T::ONE, hi: T::ONE };
+        }
+
+        let (a, b) = (self.lo.sin(), self.hi.sin());
+        let top = self.contains_period_point(std::f64::consts::FRAC_PI_2);
+        let bottom = self.contains_period_point(-std::f64::consts::FRAC_PI_2);
+
+        let lo = if bottom { -T::ONE } else { T::min(a, b) };
+        let hi = if top { T::ONE } else { T::max(a, b) };
+        Interval::<T>::outward_transcendental(lo, hi).clamped(-T::ONE, T::ONE)
+    }
+
+    fn cos(&self) -> Interval<T> {
+        if self.width().to_f64() >= std::f64::consts::TAU {
+            return Interval::<T> { lo: -T::ONE, hi: T::ONE };
+        }
+
+        let (a, b) = (self.lo.cos(), self.hi.cos());
+        let top = self.contains_period_point(0.0);
+        let bottom = self.contains_period_point(std::f64::consts::PI);
+
+        let lo = if bottom { -T::ONE } else { T::min(a, b) };
+        let hi = if top { T::ONE } else { T::max(a, b) };
+        Interval::<T>::outward_transcendental(lo, hi).clamped(-T::ONE, T::ONE)
+    }
+
+    /// Boxes around the origin or across the negative x axis give the full
+    /// range, otherwise the extremes are at the corners.
+    fn atan2(a: Interval<T>, b: Interval<T>) -> Interval<T> {
+        if a.contains_zero() && b.lo <= T::ZERO {
+            let pi = Interval::<T>::outward(T::PI, T::PI);
+            return Interval::<T> { lo: -pi.hi, hi: pi.hi };
+        }
+
+        let corners = [T::atan2(a.lo, b.lo), T::atan2(a.lo, b.hi), T::atan2(a.hi, b.lo), T::atan2(a.hi, b.hi)];
+        let lo = corners.iter().fold(corners[0], |min, c| T::min(min, *c));
+        let hi = corners.iter().fold(corners[0], |max, c| T::max(max, *c));
+        Interval::<T>::outward_transcendental(lo, hi)
+    }
+
+    fn asin(&self) -> Interval<T> {
+        let (lo, hi) = (T::max(self.lo, -T::ONE), T::min(self.hi, T::ONE));
+        Interval::<T>::outward_transcendental(lo.asin(), hi.asin())
+    }
+
+    fn exp(&self) -> Interval<T> {
+        let hi = self.hi.exp();
+        Interval::<T>::outward_transcendental(self.lo.exp(), hi).clamped(T::ZERO, hi + hi)
+    }
+
+    fn max(a: Self, b: Self) -> Interval<T> {
+        Interval::<T> { lo: T::max(a.lo, b.lo), hi: T::max(a.hi, b.hi) }
+    }
+
+    fn min(a: Self, b: Self) -> Interval<T> {
+        Interval::<T> { lo: T::min(a.lo, b.lo), hi: T::min(a.hi, b.hi) }
+    }
+
+    fn abs(&self) -> Interval<T> {
+        if self.lo >= T::ZERO {
+            *self
+        }
+        else if self.hi <= T::ZERO {
+            -*self
+        }
+        else {
+            Interval::<T> { lo: T::ZERO, hi: T::max(-self.lo, self.hi) }
+        }
+    }
+
+    /// Values that T cannot represent exactly get bounds on both sides.
+    fn from_f64(value: f64) -> Interval<T> {
+        let rounded = T::from_f64(value);
+        let converted = rounded.to_f64();
+
+        if converted == value {
+            Interval::<T>::point(rounded)
+        }
+        else {
+            Interval::<T>::outward(rounded, rounded)
+        }
+    }
+
+    /// The midpoint.
+    fn to_f64(&self) -> f64 {
+        (self.lo.to_f64() + self.hi.to_f64()) * 0.5
+    }
+
+    const ZERO: Interval<T> = Interval::<T> { lo: T::ZERO, hi: T::ZERO };
+    const ONE: Interval<T> = Interval::<T> { lo: T::ONE, hi: T::ONE };
+    const TWO: Interval<T> = Interval::<T> { lo: T::TWO, hi: T::TWO };
+    const HALF: Interval<T> = Interval::<T> { lo: T::HALF, hi: T::HALF };
+    const QUARTER: Interval<T> = Interval::<T> { lo: T::QUARTER, hi: T::QUARTER };
+    /// The nearest representable value, since constants cannot be widened.
+    /// It is within half an ulp of pi.
+    const PI: Interval<T> = Interval::<T> { lo: T::PI, hi: T::PI };
+    const EPSILON: Interval<T> = Interval::<T> { lo: T::EPSILON, hi: T::EPSILON };
+    const TINY: Interval<T> = Interval::<T> { lo: T::TINY, hi: T::TINY };
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T>> Interval<T> {
+    /// Keeps widened bounds within the known range of a function.
+    fn clamped(self, min: T, max: T) -> Interval<T> {
+        Interval::<T> { lo: T::max(self.lo, min), hi: T::min(self.hi, max) }
+    }
+}
+
+/// The box as one interval per axis.
+impl<T: PartialOrd + Copy> From<Aabb3<T>> for Vec3<Interval<T>> {
+    fn from(aabb: Aabb3<T>) -> Self {
+        Vec3::<Interval<T>> {
+            x: Interval::<T> { lo: aabb.min.x, hi: aabb.max.x },
+            y: Interval::<T> { lo: aabb.min.y, hi: aabb.max.y },
+            z: Interval::<T> { lo: aabb.min.z, hi: aabb.max.z }
+        }
+    }
+}
+
+/// The box spanned by an interval per axis.
+impl<T: PartialOrd + Copy> From<Vec3<Interval<T>>> for Aabb3<T> {
+    fn from(v: Vec3<Interval<T>>) -> Self {
+        Aabb3::<T> { min: Vec3::<T> { x: v.x.lo, y: v.y.lo, z: v.z.lo }, max: Vec3::<T> { x: v.x.hi, y: v.y.hi, z: v.z.hi } }
+    }
+}
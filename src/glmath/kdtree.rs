@@ -0,0 +1,208 @@
+use std::ops::{Add, Sub, Index, IndexMut};
+
+use crate::glmath::*;
+
+/// A point the tree can split along its axes.
+pub trait KdPoint<T>: Copy + Default + Index<usize, Output = T> + IndexMut<usize> + Sub<Output = Self> {
+    const DIMENSIONS: usize;
+}
+
+impl<T: PartialOrd + Copy + Default + Vectorable<T> + Sub<Output = T>> KdPoint<T> for Vec2<T> {
+    const DIMENSIONS: usize = 2;
+}
+
+impl<T: PartialOrd + Copy + Default + Sub<Output = T>> KdPoint<T> for Vec3<T> {
+    const DIMENSIONS: usize = 3;
+}
+
+impl<T: PartialOrd + Copy + Default + Sub<Output = T>> KdPoint<T> for Vec4<T> {
+    const DIMENSIONS: usize = 4;
+}
+
+/// A balanced k-d tree over a fixed set of points. The tree is stored
+/// implicitly: the median of every range of indices splits that range.
+///
+/// Distances are measured by a metric applied to the difference of two
+/// points, the squared length by default. Queries report distances as
+/// returned by the metric.
+#[derive(Debug, Clone)]
+pub struct KdTree<T: PartialOrd + Copy, P: KdPoint<T>> {
+    pub points: Vec<P>,
+    /// Point indices ordered so the median of every range splits it.
+    indices: Vec<usize>,
+    /// The split axis of the median at each position.
+    axes: Vec<usize>,
+    metric: fn(P) -> T
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T>,
+    P: KdPoint<T>>
+    KdTree<T, P>
+{
+    /// Builds a tree measuring distance by squared length.
+    pub fn build(points: &[P]) -> KdTree<T, P>
+        where P: StandardVec<T>
+    {
+        KdTree::<T, P>::with_metric(points, |offset| offset.length_sq())
+    }
+
+    /// Builds a tree with a custom metric of the offset between two points.
+    /// The metric must not decrease when any component of the offset grows
+    /// in magnitude, such as a weighted squared length, for the search to
+    /// stay exact.
+    pub fn with_metric(points: &[P], metric: fn(P) -> T) -> KdTree<T, P> {
+        let mut tree = KdTree::<T, P> {
+            points: points.to_vec(),
+            indices: (0..points.len()).collect(),
+            axes: vec![0; points.len()],
+            metric
+        };
+
+        tree.build_range(0, points.len());
+        tree
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// The index and distance of the point nearest to the query.
+    pub fn nearest(&self, query: P) -> Option<(usize, T)> {
+        self.k_nearest(query, 1).first().copied()
+    }
+
+    /// The indices and distances of the k points nearest to the query,
+    /// sorted from nearest to furthest.
+    pub fn k_nearest(&self, query: P, k: usize) -> Vec<(usize, T)> {
+        self.k_nearest_approx(query, k, T::ZERO)
+    }
+
+    /// Approximate nearest neighbors. Subtrees are skipped unless they could
+    /// hold a point closer than the current kth distance divided by
+    /// (1 + epsilon) squared, so for the default metric every reported
+    /// point is within (1 + epsilon) times the true kth nearest distance.
+    pub fn k_nearest_approx(&self, query: P, k: usize, epsilon: T) -> Vec<(usize, T)> {
+        let mut result: Vec<(usize, T)> = Vec::with_capacity(k + 1);
+        let scale = (T::ONE + epsilon) * (T::ONE + epsilon);
+
+        if k > 0 {
+            self.search(0, self.indices.len(), query, k, scale, &mut result);
+        }
+
+        result
+    }
+
+    /// Calls back with the index and distance of every point within radius
+    /// of the query. The radius is squared before comparing against the
+    /// metric.
+    pub fn within_radius<F>(&self, query: P, radius: T, mut callback: F)
+        where F: FnMut(usize, T)
+    {
+        let radius_sq = radius * radius;
+        let mut stack: Vec<(usize, usize)> = vec![(0, self.indices.len())];
+
+        while let Some((start, end)) = stack.pop() {
+            if start >= end {
+                continue;
+            }
+
+            let mid = start + (end - start) / 2;
+            let index = self.indices[mid];
+            let dist = (self.metric)(self.points[index] - query);
+
+            if dist <= radius_sq {
+                callback(index, dist);
+            }
+
+            let (near, far, plane_dist) = self.split_ranges(start, mid, end, query);
+            stack.push(near);
+
+            if plane_dist <= radius_sq {
+                stack.push(far);
+            }
+        }
+    }
+
+    fn build_range(&mut self, start: usize, end: usize) {
+        if end - start < 2 {
+            return;
+        }
+
+        // Split along the axis with the largest spread.
+        let mut axis = 0;
+        let mut widest = T::ZERO;
+
+        for candidate in 0..P::DIMENSIONS {
+            let mut min = self.points[self.indices[start]][candidate];
+            let mut max = min;
+
+            for index in &self.indices[start..end] {
+                min = T::min(min, self.points[*index][candidate]);
+                max = T::max(max, self.points[*index][candidate]);
+            }
+
+            if max - min > widest {
+                widest = max - min;
+                axis = candidate;
+            }
+        }
+
+        let mid = start + (end - start) / 2;
+        let points = &self.points;
+
+        self.indices[start..end].select_nth_unstable_by(mid - start, |a, b| {
+            points[*a][axis].partial_cmp(&points[*b][axis]).unwrap_or(std::cmp::Ordering::Equal)
+        });
+        self.axes[mid] = axis;
+
+        self.build_range(start, mid);
+        self.build_range(mid + 1, end);
+    }
+
+    /// Orders the two halves of a range by which side of the split the query
+    /// lies on, and measures the query's distance to the splitting plane.
+    fn split_ranges(&self, start: usize, mid: usize, end: usize, query: P) -> ((usize, usize), (usize, usize), T) {
+        let axis = self.axes[mid];
+        let diff = query[axis] - self.points[self.indices[mid]][axis];
+
+        let mut offset = P::default();
+        offset[axis] = diff;
+        let plane_dist = (self.metric)(offset);
+
+        if diff < T::ZERO {
+            ((start, mid), (mid + 1, end), plane_dist)
+        }
+        else {
+            ((mid + 1, end), (start, mid), plane_dist)
+        }
+    }
+
+    fn search(&self, start: usize, end: usize, query: P, k: usize, scale: T, result: &mut Vec<(usize, T)>) {
+        if start >= end {
+            return;
+        }
+
+        let mid = start + (end - start) / 2;
+        let index = self.indices[mid];
+        let dist = (self.metric)(self.points[index] - query);
+
+        if result.len() < k || dist < result[k - 1].1 {
+            let position = result.iter().position(|(_, other)| dist < *other).unwrap_or(result.len());
+            result.insert(position, (index, dist));
+            result.truncate(k);
+        }
+
+        let (near, far, plane_dist) = self.split_ranges(start, mid, end, query);
+        self.search(near.0, near.1, query, k, scale, result);
+
+        if result.len() < k || plane_dist * scale < result[k - 1].1 {
+            self.search(far.0, far.1, query, k, scale, result);
+        }
+    }
+}
@@ -0,0 +1,399 @@
+use std::ops::{Add, AddAssign, Sub, Neg, Index};
+
+use crate::glmath::*;
+
+/// Factorizations and linear system solves for a square N x N matrix whose
+/// matching vector type is V. Implementors only convert to and from rows.
+pub trait Factorize<T, V, const N: usize>: Sized
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T> + Neg<Output = T>,
+    V: Copy + Index<usize, Output = T>
+{
+    /// The matrix as rows[row][column].
+    fn rows(&self) -> [[T; N]; N];
+    fn from_rows(rows: [[T; N]; N]) -> Self;
+    fn vector(values: [T; N]) -> V;
+
+    /// LU decomposition with partial pivoting, returning (P, L, U) with
+    /// P * self = L * U. L has a unit diagonal. Singular matrices give a U
+    /// with zeros on its diagonal.
+    fn lu(&self) -> (Self, Self, Self) {
+        let (lu, permutation) = lu_decompose(self.rows());
+
+        let mut p = [[T::ZERO; N]; N];
+        let mut l = [[T::ZERO; N]; N];
+        let mut u = [[T::ZERO; N]; N];
+
+        for row in 0..N {
+            p[row][permutation[row]] = T::ONE;
+            l[row][row] = T::ONE;
+            l[row][..row].copy_from_slice(&lu[row][..row]);
+            u[row][row..].copy_from_slice(&lu[row][row..]);
+        }
+
+        (Self::from_rows(p), Self::from_rows(l), Self::from_rows(u))
+    }
+
+    /// QR decomposition by Householder reflections, returning (Q, R) with
+    /// Q orthogonal and R upper triangular.
+    fn qr(&self) -> (Self, Self) {
+        let mut r = self.rows();
+        let mut q = identity_rows();
+
+        for k in 0..N {
+            let Some(v) = householder(&r, k) else { continue };
+
+            // R = H * R and Q = Q * H, with H = I - 2 v v^T.
+            reflect(&mut r, &v, k);
+
+            for row in q.iter_mut() {
+                let dot = (k..N).fold(T::ZERO, |sum, i| sum + row[i] * v[i]);
+                for i in k..N {
+                    row[i] = row[i] - T::TWO * v[i] * dot;
+                }
+            }
+
+            for row in r.iter_mut().skip(k + 1) {
+                row[k] = T::ZERO;
+            }
+        }
+
+        (Self::from_rows(q), Self::from_rows(r))
+    }
+
+    /// The lower triangular L with self = L * L^T, reading only the lower
+    /// triangle. Returns None if the matrix is not positive definite.
+    fn cholesky(&self) -> Option<Self> {
+        cholesky_decompose(self.rows()).map(Self::from_rows)
+    }
+
+    /// Solves self * x = b by LU decomposition. Returns None if the matrix
+    /// is singular.
+    fn solve(&self, b: V) -> Option<V> {
+        let rows = self.rows();
+        let (lu, permutation) = lu_decompose(rows);
+
+        if is_singular(&rows, &lu) {
+            return None;
+        }
+
+        Some(Self::vector(lu_solve(&lu, &permutation, to_array(b))))
+    }
+
+    /// Solves self * x = b for a symmetric positive definite matrix by
+    /// Cholesky decomposition.
+    fn solve_cholesky(&self, b: V) -> Option<V> {
+        let l = cholesky_decompose(self.rows())?;
+        let mut x: [T; N] = to_array(b);
+
+        // L y = b, then L^T x = y.
+        for row in 0..N {
+            let sum = (0..row).fold(x[row], |sum, column| sum - l[row][column] * x[column]);
+            x[row] = sum / l[row][row];
+        }
+
+        for row in (0..N).rev() {
+            let sum = (row + 1..N).fold(x[row], |sum, below| sum - l[below][row] * x[below]);
+            x[row] = sum / l[row][row];
+        }
+
+        Some(Self::vector(x))
+    }
+
+    /// The x minimizing |self * x - b|. For rank deficient matrices this is
+    /// the basic solution, with the components of dependent columns zero.
+    fn solve_least_squares(&self, b: V) -> V {
+        let (x, _) = pivoted_least_squares(self.rows().to_vec(), (0..N).map(|i| b[i]).collect());
+        Self::vector(x)
+    }
+
+    /// The x minimizing the sum of (rows[i] * x - values[i])^2, for fitting
+    /// more equations than unknowns. Panics if rows and values differ in
+    /// length.
+    fn least_squares(rows: &[V], values: &[T]) -> V {
+        assert_eq!(rows.len(), values.len());
+
+        let rows = rows.iter().map(|row| to_array(*row)).collect();
+        let (x, _) = pivoted_least_squares(rows, values.to_vec());
+        Self::vector(x)
+    }
+
+    /// The number of linearly independent columns, from QR with column
+    /// pivoting.
+    fn rank(&self) -> usize {
+        pivoted_least_squares(self.rows().to_vec(), vec![T::ZERO; N]).1
+    }
+
+    /// The condition number in the 1-norm, |A| * |A^-1|. Large values mean
+    /// solves lose precision. Returns None if the matrix is singular.
+    fn condition_number(&self) -> Option<T> {
+        let rows = self.rows();
+        let (lu, permutation) = lu_decompose(rows);
+
+        if is_singular(&rows, &lu) {
+            return None;
+        }
+
+        // The inverse one column at a time.
+        let mut norm = T::ZERO;
+        let mut inverse_norm = T::ZERO;
+
+        for column in 0..N {
+            let mut unit = [T::ZERO; N];
+            unit[column] = T::ONE;
+
+            let inverse_column = lu_solve(&lu, &permutation, unit);
+            inverse_norm = T::max(inverse_norm, inverse_column.iter().fold(T::ZERO, |sum, v| sum + v.abs()));
+            norm = T::max(norm, rows.iter().fold(T::ZERO, |sum, row| sum + row[column].abs()));
+        }
+
+        Some(norm * inverse_norm)
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    Factorize<T, Vec2<T>, 2> for Mat22<T>
+{
+    fn rows(&self) -> [[T; 2]; 2] {
+        [0, 1].map(|row| [0, 1].map(|column| self.data[column][row]))
+    }
+
+    fn from_rows(rows: [[T; 2]; 2]) -> Mat22<T> {
+        Mat22::<T> { data: [0, 1].map(|column| [0, 1].map(|row| rows[row][column])) }
+    }
+
+    fn vector(values: [T; 2]) -> Vec2<T> {
+        Vec2::<T>::new(values[0], values[1])
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    Factorize<T, Vec3<T>, 3> for Mat33<T>
+{
+    fn rows(&self) -> [[T; 3]; 3] {
+        [0, 1, 2].map(|row| [0, 1, 2].map(|column| self.data[column][row]))
+    }
+
+    fn from_rows(rows: [[T; 3]; 3]) -> Mat33<T> {
+        Mat33::<T> { data: [0, 1, 2].map(|column| [0, 1, 2].map(|row| rows[row][column])) }
+    }
+
+    fn vector(values: [T; 3]) -> Vec3<T> {
+        Vec3::<T>::new(values[0], values[1], values[2])
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    Factorize<T, Vec4<T>, 4> for Mat44<T>
+{
+    fn rows(&self) -> [[T; 4]; 4] {
+        [0, 1, 2, 3].map(|row| [0, 1, 2, 3].map(|column| self.data[column][row]))
+    }
+
+    fn from_rows(rows: [[T; 4]; 4]) -> Mat44<T> {
+        Mat44::<T> { data: [0, 1, 2, 3].map(|column| [0, 1, 2, 3].map(|row| rows[row][column])) }
+    }
+
+    fn vector(values: [T; 4]) -> Vec4<T> {
+        Vec4::<T>::new(values[0], values[1], values[2], values[3])
+    }
+}
+
+fn to_array<T: Copy, V: Index<usize, Output = T>, const N: usize>(v: V) -> [T; N] {
+    std::array::from_fn(|i| v[i])
+}
+
+fn identity_rows<T: Copy + Vectorable<T>, const N: usize>() -> [[T; N]; N] {
+    std::array::from_fn(|row| std::array::from_fn(|column| if row == column { T::ONE } else { T::ZERO }))
+}
+
+/// Doolittle LU with partial pivoting, stored in one matrix with the unit
+/// diagonal of L left out. permutation[i] is the original row now at row i.
+fn lu_decompose<T, const N: usize>(mut a: [[T; N]; N]) -> ([[T; N]; N], [usize; N])
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>
+{
+    let mut permutation: [usize; N] = std::array::from_fn(|i| i);
+
+    for k in 0..N {
+        let pivot = (k..N).fold(k, |best, row| if a[row][k].abs() > a[best][k].abs() { row } else { best });
+        a.swap(k, pivot);
+        permutation.swap(k, pivot);
+
+        if a[k][k] == T::ZERO {
+            continue;
+        }
+
+        let pivot_row = a[k];
+        for row in a.iter_mut().skip(k + 1) {
+            let factor = row[k] / pivot_row[k];
+            row[k] = factor;
+
+            for (value, pivot_value) in row.iter_mut().zip(pivot_row).skip(k + 1) {
+                *value = *value - factor * pivot_value;
+            }
+        }
+    }
+
+    (a, permutation)
+}
+
+fn lu_solve<T, const N: usize>(lu: &[[T; N]; N], permutation: &[usize; N], b: [T; N]) -> [T; N]
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>
+{
+    let mut x: [T; N] = std::array::from_fn(|i| b[permutation[i]]);
+
+    for row in 0..N {
+        x[row] = (0..row).fold(x[row], |sum, column| sum - lu[row][column] * x[column]);
+    }
+
+    for row in (0..N).rev() {
+        x[row] = (row + 1..N).fold(x[row], |sum, column| sum - lu[row][column] * x[column]) / lu[row][row];
+    }
+
+    x
+}
+
+/// True if a pivot of U is negligible next to the largest entry of A.
+fn is_singular<T, const N: usize>(a: &[[T; N]; N], lu: &[[T; N]; N]) -> bool
+    where T: PartialOrd + Copy + Vectorable<T> + Mul<Output = T>
+{
+    let scale = a.iter().flatten().fold(T::ZERO, |max, v| T::max(max, v.abs()));
+    let tolerance = scale * T::EPSILON * T::from_f64(N as f64);
+
+    scale == T::ZERO || (0..N).any(|i| lu[i][i].abs() <= tolerance)
+}
+
+fn cholesky_decompose<T, const N: usize>(a: [[T; N]; N]) -> Option<[[T; N]; N]>
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>
+{
+    let mut l = [[T::ZERO; N]; N];
+
+    for row in 0..N {
+        for column in 0..=row {
+            let sum = (0..column).fold(a[row][column], |sum, k| sum - l[row][k] * l[column][k]);
+
+            if row == column {
+                if sum <= T::ZERO {
+                    return None;
+                }
+                l[row][row] = sum.sqrt();
+            }
+            else {
+                l[row][column] = sum / l[column][column];
+            }
+        }
+    }
+
+    Some(l)
+}
+
+/// The unit Householder vector reflecting column k of a below row k onto
+/// the diagonal, or None if that part of the column is already zero.
+fn householder<T, const N: usize>(a: &[[T; N]], k: usize) -> Option<Vec<T>>
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T> + Neg<Output = T>
+{
+    let norm = a.iter().skip(k).fold(T::ZERO, |sum, row| sum + row[k] * row[k]).sqrt();
+
+    if norm == T::ZERO {
+        return None;
+    }
+
+    // Reflect away from the diagonal's sign to avoid cancellation.
+    let alpha = if a[k][k] > T::ZERO { -norm } else { norm };
+    let mut v: Vec<T> = a.iter().map(|row| row[k]).collect();
+    v.iter_mut().take(k).for_each(|x| *x = T::ZERO);
+    v[k] = v[k] - alpha;
+
+    let length = v.iter().fold(T::ZERO, |sum, x| sum + *x * *x).sqrt();
+    if length == T::ZERO {
+        return None;
+    }
+
+    Some(v.into_iter().map(|x| x / length).collect())
+}
+
+/// Applies the reflection I - 2 v v^T from the left to columns k and up
+/// of a, where v is zero above row k.
+fn reflect<T, const N: usize>(a: &mut [[T; N]], v: &[T], k: usize)
+    where T: PartialOrd + Copy + Vectorable<T> + Add<Output = T> + Sub<Output = T> + Mul<Output = T>
+{
+    let mut dots = [T::ZERO; N];
+    for (row, scale) in a.iter().zip(v).skip(k) {
+        for (dot, value) in dots.iter_mut().zip(row).skip(k) {
+            *dot = *dot + *scale * *value;
+        }
+    }
+
+    for (row, scale) in a.iter_mut().zip(v).skip(k) {
+        for (value, dot) in row.iter_mut().zip(dots).skip(k) {
+            *value = *value - T::TWO * *scale * dot;
+        }
+    }
+}
+
+/// Householder QR with column pivoting applied to the rows of a and to b,
+/// giving the basic least squares solution and the numerical rank.
+fn pivoted_least_squares<T, const N: usize>(mut a: Vec<[T; N]>, mut b: Vec<T>) -> ([T; N], usize)
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T> + Neg<Output = T>
+{
+    let m = a.len();
+    let mut permutation: [usize; N] = std::array::from_fn(|i| i);
+    let mut rank = 0;
+    let mut tolerance = T::ZERO;
+
+    for k in 0..usize::min(m, N) {
+        // Bring the remaining column with the largest norm to the front.
+        let column_norm = |a: &Vec<[T; N]>, column: usize| a.iter().skip(k).fold(T::ZERO, |sum, row| sum + row[column] * row[column]);
+        let pivot = (k..N).fold(k, |best, column| if column_norm(&a, column) > column_norm(&a, best) { column } else { best });
+
+        for row in a.iter_mut() {
+            row.swap(k, pivot);
+        }
+        permutation.swap(k, pivot);
+
+        let norm = column_norm(&a, k).sqrt();
+        if k == 0 {
+            tolerance = norm * T::EPSILON * T::from_f64(usize::max(m, N) as f64);
+        }
+
+        if norm <= tolerance {
+            break;
+        }
+
+        let Some(v) = householder(&a, k) else { break };
+
+        reflect(&mut a, &v, k);
+
+        let dot = (k..m).fold(T::ZERO, |sum, i| sum + v[i] * b[i]);
+        for i in k..m {
+            b[i] = b[i] - T::TWO * v[i] * dot;
+        }
+
+        rank += 1;
+    }
+
+    // Back substitute through the independent part of R.
+    let mut y = [T::ZERO; N];
+    for row in (0..rank).rev() {
+        y[row] = (row + 1..rank).fold(b[row], |sum, column| sum - a[row][column] * y[column]) / a[row][row];
+    }
+
+    let mut x = [T::ZERO; N];
+    for (i, value) in y.into_iter().enumerate() {
+        x[permutation[i]] = value;
+    }
+
+    (x, rank)
+}
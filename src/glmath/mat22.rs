@@ -199,7 +199,7 @@ impl<T: PartialOrd + Copy + Vectorable<T> +
     }
 
     fn det(&self) -> T {
-        return self.data[0][0] * self.data[1][1] - 
-            self.data[0][1] * self.data[1][0];
+        self.data[0][0] * self.data[1][1] - 
+            self.data[0][1] * self.data[1][0]
     }
 }
\ No newline at end of file
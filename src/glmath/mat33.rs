@@ -24,6 +24,31 @@ impl<T: Vectorable<T> + PartialOrd + Copy> Mat33<T> {
         }
     }
 
+    /// Takes the upper left 3x3 part of a Mat44, dropping translation.
+    pub fn from_mat44(mat: &Mat44<T>) -> Mat33<T> {
+        Mat33::<T> {
+            data: [
+                [ mat.data[0][0], mat.data[0][1], mat.data[0][2] ],
+                [ mat.data[1][0], mat.data[1][1], mat.data[1][2] ],
+                [ mat.data[2][0], mat.data[2][1], mat.data[2][2] ]
+            ]
+        }
+    }
+
+    /// Places the matrix in the upper left of an identity Mat44.
+    pub fn to_mat44(&self) -> Mat44<T> {
+        let mut mat = Mat44::<T>::IDENTITY;
+        for column in 0..3 {
+            mat.data[column][..3].copy_from_slice(&self.data[column]);
+        }
+        mat
+    }
+
+    /// Returns a column of the matrix, the image of one of the unit axes.
+    pub fn column(&self, index: usize) -> Vec3<T> {
+        Vec3::<T>::new(self.data[index][0], self.data[index][1], self.data[index][2])
+    }
+
     pub const IDENTITY: Mat33<T> = Mat33::<T> {
         data: [
             [ T::ONE, T::ZERO, T::ZERO ],
@@ -388,15 +388,16 @@ impl<T: PartialOrd + Copy + Vectorable<T> +
     }
 }        
 
+#[allow(clippy::too_many_arguments)]
 fn det33<T: PartialOrd + Copy + Mul<Output = T> + 
     Sub<Output = T> + Add<Output = T>>
     (t00: T, t01: T, t02: T,
      t10: T, t11: T, t12: T,
      t20: T, t21: T, t22: T) -> T
 {
-    return t00 * (t11 * t22 - t12 * t21)
+    t00 * (t11 * t22 - t12 * t21)
         + t01 * (t12 * t20 - t10 * t22)
-        + t02 * (t10 * t21 - t11 * t20);
+        + t02 * (t10 * t21 - t11 * t20)
 }
 
 impl<T: PartialOrd + Copy + Vectorable<T> +
@@ -0,0 +1,311 @@
+use std::ops::{Add, AddAssign, Sub, SubAssign, Neg, Index, IndexMut};
+
+use crate::glmath::*;
+use crate::glmath::linear::Factorize;
+
+/// A matrix of any size with R rows and C columns, stored column major
+/// like the fixed size matrices. Multiplication only compiles when the
+/// inner dimensions agree.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Matrix<T: PartialOrd + Copy, const R: usize, const C: usize> {
+    pub data: [[T; R]; C]
+}
+
+/// A column vector of any size.
+pub type Vector<T, const N: usize> = Matrix<T, N, 1>;
+
+impl<T: Vectorable<T> + PartialOrd + Copy, const R: usize, const C: usize> Matrix<T, R, C> {
+    pub const ZERO: Matrix<T, R, C> = Matrix::<T, R, C> { data: [[T::ZERO; R]; C] };
+
+    /// Constructs a matrix from its columns.
+    pub fn from_columns(columns: [[T; R]; C]) -> Matrix<T, R, C> {
+        Matrix::<T, R, C> { data: columns }
+    }
+
+    /// Constructs a matrix from its rows, matching how it reads on paper.
+    pub fn from_rows(rows: [[T; C]; R]) -> Matrix<T, R, C> {
+        Matrix::<T, R, C> { data: std::array::from_fn(|column| std::array::from_fn(|row| rows[row][column])) }
+    }
+
+    pub fn column(&self, column: usize) -> Vector<T, R> {
+        Vector::<T, R> { data: [self.data[column]] }
+    }
+
+    pub fn row(&self, row: usize) -> Vector<T, C> {
+        Vector::<T, C> { data: [std::array::from_fn(|column| self.data[column][row])] }
+    }
+
+    pub fn get_transposed(&self) -> Matrix<T, C, R> {
+        Matrix::<T, C, R> { data: std::array::from_fn(|row| std::array::from_fn(|column| self.data[column][row])) }
+    }
+
+    /// Applies f to every element.
+    pub fn map<F: FnMut(T) -> T>(&self, mut f: F) -> Matrix<T, R, C> {
+        Matrix::<T, R, C> { data: self.data.map(|column| column.map(&mut f)) }
+    }
+}
+
+impl<T: Vectorable<T> + PartialOrd + Copy, const N: usize> Matrix<T, N, N> {
+    pub const IDENTITY: Matrix<T, N, N> = {
+        let mut data = [[T::ZERO; N]; N];
+        let mut i = 0;
+        while i < N {
+            data[i][i] = T::ONE;
+            i += 1;
+        }
+        Matrix::<T, N, N> { data }
+    };
+
+    /// A square matrix with the given diagonal.
+    pub fn from_diagonal(diagonal: Vector<T, N>) -> Matrix<T, N, N> {
+        let mut result = Self::ZERO;
+        for (i, column) in result.data.iter_mut().enumerate() {
+            column[i] = diagonal.data[0][i];
+        }
+        result
+    }
+
+    pub fn trace(&self) -> T
+        where T: Add<Output = T>
+    {
+        (0..N).fold(T::ZERO, |sum, i| sum + self.data[i][i])
+    }
+}
+
+impl<T: Vectorable<T> + PartialOrd + Copy + Add<Output = T> + Mul<Output = T>, const N: usize> Vector<T, N> {
+    /// Constructs a vector from its elements.
+    pub fn from_array(values: [T; N]) -> Vector<T, N> {
+        Vector::<T, N> { data: [values] }
+    }
+
+    pub fn dot(&self, other: &Vector<T, N>) -> T {
+        self.data[0].iter().zip(other.data[0]).fold(T::ZERO, |sum, (a, b)| sum + *a * b)
+    }
+
+    pub fn length_squared(&self) -> T {
+        self.dot(self)
+    }
+
+    pub fn length(&self) -> T {
+        self.length_squared().sqrt()
+    }
+}
+
+impl<T: PartialOrd + Copy, const R: usize, const C: usize> Index<(usize, usize)> for Matrix<T, R, C> {
+    type Output = T;
+
+    /// Indexes by (row, column).
+    fn index(&self, index: (usize, usize)) -> &Self::Output {
+        &self.data[index.1][index.0]
+    }
+}
+
+impl<T: PartialOrd + Copy, const R: usize, const C: usize> IndexMut<(usize, usize)> for Matrix<T, R, C> {
+    fn index_mut(&mut self, index: (usize, usize)) -> &mut Self::Output {
+        &mut self.data[index.1][index.0]
+    }
+}
+
+impl<T: PartialOrd + Copy, const N: usize> Index<usize> for Vector<T, N> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.data[0][index]
+    }
+}
+
+impl<T: PartialOrd + Copy, const N: usize> IndexMut<usize> for Vector<T, N> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.data[0][index]
+    }
+}
+
+impl<T: PartialOrd + Copy + Add<Output = T>, const R: usize, const C: usize> Add for Matrix<T, R, C> {
+    type Output = Matrix<T, R, C>;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Matrix::<T, R, C> { data: std::array::from_fn(|column| std::array::from_fn(|row| self.data[column][row] + rhs.data[column][row])) }
+    }
+}
+
+impl<T: PartialOrd + Copy + AddAssign<T>, const R: usize, const C: usize> AddAssign for Matrix<T, R, C> {
+    fn add_assign(&mut self, rhs: Self) {
+        for (column, rhs_column) in self.data.iter_mut().zip(rhs.data) {
+            for (value, rhs_value) in column.iter_mut().zip(rhs_column) {
+                *value += rhs_value;
+            }
+        }
+    }
+}
+
+impl<T: PartialOrd + Copy + Sub<Output = T>, const R: usize, const C: usize> Sub for Matrix<T, R, C> {
+    type Output = Matrix<T, R, C>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Matrix::<T, R, C> { data: std::array::from_fn(|column| std::array::from_fn(|row| self.data[column][row] - rhs.data[column][row])) }
+    }
+}
+
+impl<T: PartialOrd + Copy + SubAssign<T>, const R: usize, const C: usize> SubAssign for Matrix<T, R, C> {
+    fn sub_assign(&mut self, rhs: Self) {
+        for (column, rhs_column) in self.data.iter_mut().zip(rhs.data) {
+            for (value, rhs_value) in column.iter_mut().zip(rhs_column) {
+                *value -= rhs_value;
+            }
+        }
+    }
+}
+
+impl<T: PartialOrd + Copy + Neg<Output = T>, const R: usize, const C: usize> Neg for Matrix<T, R, C> {
+    type Output = Matrix<T, R, C>;
+
+    fn neg(self) -> Self::Output {
+        Matrix::<T, R, C> { data: self.data.map(|column| column.map(|value| -value)) }
+    }
+}
+
+impl<T: PartialOrd + Copy + Mul<Output = T>, const R: usize, const C: usize> Mul<T> for Matrix<T, R, C> {
+    type Output = Matrix<T, R, C>;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        Matrix::<T, R, C> { data: self.data.map(|column| column.map(|value| value * rhs)) }
+    }
+}
+
+impl<T: PartialOrd + Copy + Div<Output = T>, const R: usize, const C: usize> Div<T> for Matrix<T, R, C> {
+    type Output = Matrix<T, R, C>;
+
+    fn div(self, rhs: T) -> Self::Output {
+        Matrix::<T, R, C> { data: self.data.map(|column| column.map(|value| value / rhs)) }
+    }
+}
+
+/// (R x C) * (C x K) gives R x K.
+impl<T: PartialOrd + Copy + Vectorable<T> + Mul<Output = T> + Add<Output = T>, const R: usize, const C: usize, const K: usize>
+    Mul<Matrix<T, C, K>> for Matrix<T, R, C>
+{
+    type Output = Matrix<T, R, K>;
+
+    fn mul(self, rhs: Matrix<T, C, K>) -> Self::Output {
+        Matrix::<T, R, K> {
+            data: std::array::from_fn(|column| std::array::from_fn(|row| {
+                (0..C).fold(T::ZERO, |sum, k| sum + self.data[k][row] * rhs.data[column][k])
+            }))
+        }
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>, const N: usize>
+    Factorize<T, Vector<T, N>, N> for Matrix<T, N, N>
+{
+    fn rows(&self) -> [[T; N]; N] {
+        self.get_transposed().data
+    }
+
+    fn from_rows(rows: [[T; N]; N]) -> Matrix<T, N, N> {
+        Matrix::<T, N, N> { data: rows }.get_transposed()
+    }
+
+    fn vector(values: [T; N]) -> Vector<T, N> {
+        Vector::<T, N> { data: [values] }
+    }
+}
+
+impl<T: PartialOrd + Copy> From<Mat22<T>> for Matrix<T, 2, 2> {
+    fn from(m: Mat22<T>) -> Self {
+        Matrix::<T, 2, 2> { data: m.data }
+    }
+}
+
+impl<T: PartialOrd + Copy> From<Matrix<T, 2, 2>> for Mat22<T> {
+    fn from(m: Matrix<T, 2, 2>) -> Self {
+        Mat22::<T> { data: m.data }
+    }
+}
+
+impl<T: PartialOrd + Copy> From<Mat33<T>> for Matrix<T, 3, 3> {
+    fn from(m: Mat33<T>) -> Self {
+        Matrix::<T, 3, 3> { data: m.data }
+    }
+}
+
+impl<T: PartialOrd + Copy> From<Matrix<T, 3, 3>> for Mat33<T> {
+    fn from(m: Matrix<T, 3, 3>) -> Self {
+        Mat33::<T> { data: m.data }
+    }
+}
+
+impl<T: PartialOrd + Copy> From<Mat44<T>> for Matrix<T, 4, 4> {
+    fn from(m: Mat44<T>) -> Self {
+        Matrix::<T, 4, 4> { data: m.data }
+    }
+}
+
+impl<T: PartialOrd + Copy> From<Matrix<T, 4, 4>> for Mat44<T> {
+    fn from(m: Matrix<T, 4, 4>) -> Self {
+        Mat44::<T> { data: m.data }
+    }
+}
+
+impl<T: PartialOrd + Copy> From<Vec2<T>> for Vector<T, 2> {
+    fn from(v: Vec2<T>) -> Self {
+        Vector::<T, 2> { data: [[v.x, v.y]] }
+    }
+}
+
+impl<T: PartialOrd + Copy> From<Vector<T, 2>> for Vec2<T> {
+    fn from(v: Vector<T, 2>) -> Self {
+        let [x, y] = v.data[0];
+        Vec2::<T> { x, y }
+    }
+}
+
+impl<T: PartialOrd + Copy> From<Vec3<T>> for Vector<T, 3> {
+    fn from(v: Vec3<T>) -> Self {
+        Vector::<T, 3> { data: [[v.x, v.y, v.z]] }
+    }
+}
+
+impl<T: PartialOrd + Copy> From<Vector<T, 3>> for Vec3<T> {
+    fn from(v: Vector<T, 3>) -> Self {
+        let [x, y, z] = v.data[0];
+        Vec3::<T> { x, y, z }
+    }
+}
+
+impl<T: PartialOrd + Copy> From<Vec4<T>> for Vector<T, 4> {
+    fn from(v: Vec4<T>) -> Self {
+        Vector::<T, 4> { data: [[v.x, v.y, v.z, v.w]] }
+    }
+}
+
+impl<T: PartialOrd + Copy> From<Vector<T, 4>> for Vec4<T> {
+    fn from(v: Vector<T, 4>) -> Self {
+        let [x, y, z, w] = v.data[0];
+        Vec4::<T> { x, y, z, w }
+    }
+}
+
+/// The upper 3x4 block of an affine matrix, dropping the constant last row.
+impl<T: PartialOrd + Copy> From<Mat44<T>> for Matrix<T, 3, 4> {
+    fn from(m: Mat44<T>) -> Self {
+        Matrix::<T, 3, 4> { data: m.data.map(|column| [column[0], column[1], column[2]]) }
+    }
+}
+
+/// Completes a 3x4 affine matrix with the row (0, 0, 0, 1).
+impl<T: PartialOrd + Copy + Vectorable<T>> From<Matrix<T, 3, 4>> for Mat44<T> {
+    fn from(m: Matrix<T, 3, 4>) -> Self {
+        let [x, y, z, w] = m.data;
+        Mat44::<T> {
+            data: [
+                [x[0], x[1], x[2], T::ZERO],
+                [y[0], y[1], y[2], T::ZERO],
+                [z[0], z[1], z[2], T::ZERO],
+                [w[0], w[1], w[2], T::ONE]
+            ]
+        }
+    }
+}
@@ -6,6 +6,34 @@ pub mod quat;
 pub mod mat22;
 pub mod mat33;
 pub mod mat44;
+pub mod aabb3;
+pub mod ray3;
+pub mod plane;
+pub mod frustum;
+pub mod collision;
+pub mod closest;
+pub mod bvh;
+pub mod spatial_grid;
+pub mod octree;
+pub mod kdtree;
+pub mod curves;
+pub mod quat_spline;
+pub mod transform;
+pub mod animation;
+pub mod skeleton;
+pub mod ik;
+pub mod physics;
+pub mod solver;
+pub mod eigen;
+pub mod svd;
+pub mod linear;
+pub mod matrix;
+pub mod dynamic;
+pub mod sparse;
+pub mod roots;
+pub mod dual;
+pub mod interval;
+pub mod predicates;
 
 use std::ops::{Mul, Div, DivAssign};
 use vec2::Vec2;
@@ -15,6 +43,14 @@ use quat::Quat;
 use mat22::Mat22;
 use mat33::Mat33;
 use mat44::Mat44;
+use aabb3::Aabb3;
+use ray3::Ray3;
+use plane::Plane;
+use frustum::Frustum;
+use collision::{Sphere, Capsule, Obb};
+use transform::Transform;
+use matrix::{Matrix, Vector};
+use dynamic::{DVector, DMatrix};
 use self::vectorable::Vectorable;
 
 pub type Vec2f = Vec2<f32>;
@@ -24,6 +60,18 @@ pub type Quatf = Quat<f32>;
 pub type Mat22f = Mat22<f32>;
 pub type Mat33f = Mat33<f32>;
 pub type Mat44f = Mat44<f32>;
+pub type Aabb3f = Aabb3<f32>;
+pub type Ray3f = Ray3<f32>;
+pub type Planef = Plane<f32>;
+pub type Frustumf = Frustum<f32>;
+pub type Spheref = Sphere<f32>;
+pub type Capsulef = Capsule<f32>;
+pub type Obbf = Obb<f32>;
+pub type Transformf = Transform<f32>;
+pub type Matrixf<const R: usize, const C: usize> = Matrix<f32, R, C>;
+pub type Vectorf<const N: usize> = Vector<f32, N>;
+pub type DVectorf = DVector<f32>;
+pub type DMatrixf = DMatrix<f32>;
 
 pub trait StandardMat<T: PartialOrd + Copy + Vectorable<T>> 
     where Self: Sized + Copy
@@ -152,7 +200,8 @@ pub trait StandardVec<T: PartialEq + Vectorable<T>>
 
         let dot = *self * *other;
 
-        let intermediate_value = dot / combined_len;
+        // Rounding can push the cosine of nearly parallel vectors past one.
+        let intermediate_value = T::min(T::max(dot / combined_len, T::from_f64(-1.0)), T::ONE);
         T::acos(&intermediate_value)
     }
 }
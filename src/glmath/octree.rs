@@ -0,0 +1,294 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::ops::{Add, Sub, Neg};
+
+use crate::glmath::*;
+use crate::glmath::frustum::Containment;
+
+/// A node of the octree. A node's loose bounds are twice the size of the
+/// cube it covers, so an item only needs its center inside the cube and
+/// its extent below the cube's half size to fit.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OctreeNode<T: PartialOrd + Copy> {
+    pub center: Vec3<T>,
+    /// Half the size of the cube covered by the node.
+    pub half_size: T,
+    /// Index of the first of eight consecutive children, zero for leaves.
+    pub first_child: usize,
+    pub items: Vec<usize>
+}
+
+/// A loose octree over items given by caller chosen ids and their bounds.
+/// Items are kept at the deepest node whose loose bounds contain them,
+/// so moving an item never requires rebuilding the tree. Items that fit no
+/// node are kept in the root, and queries always test the root's items.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Octree<T: PartialOrd + Copy> {
+    pub nodes: Vec<OctreeNode<T>>,
+    /// The bounds and node of every item.
+    items: HashMap<usize, (Aabb3<T>, usize)>,
+    max_depth: usize
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    Octree<T>
+{
+    /// Creates an empty tree covering the given world bounds. Items centered
+    /// outside of the bounds or larger than them are kept in the root.
+    pub fn new(bounds: &Aabb3<T>, max_depth: usize) -> Octree<T> {
+        let extents = bounds.half_extents();
+
+        let root = OctreeNode::<T> {
+            center: bounds.center(),
+            half_size: T::max(extents.x, T::max(extents.y, extents.z)),
+            first_child: 0,
+            items: Vec::new()
+        };
+
+        Octree::<T> {
+            nodes: vec![root],
+            items: HashMap::new(),
+            max_depth
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// The bounds an item was inserted with.
+    pub fn bounds(&self, id: usize) -> Option<Aabb3<T>> {
+        self.items.get(&id).map(|(bounds, _)| *bounds)
+    }
+
+    /// The loose bounds of a node, which contain every item stored in it
+    /// except for the root's items that fit no node.
+    pub fn loose_bounds(&self, node_index: usize) -> Aabb3<T> {
+        let node = &self.nodes[node_index];
+        let size = node.half_size * T::TWO;
+        let extent = Vec3::<T>::new(size, size, size);
+
+        Aabb3::<T>::new(node.center - extent, node.center + extent)
+    }
+
+    /// Inserts an item, replacing any item already using the id.
+    pub fn insert(&mut self, id: usize, bounds: Aabb3<T>) {
+        self.remove(id);
+
+        let node_index = self.find_node(&bounds);
+        self.nodes[node_index].items.push(id);
+        self.items.insert(id, (bounds, node_index));
+    }
+
+    pub fn insert_point(&mut self, id: usize, point: Vec3<T>) {
+        self.insert(id, Aabb3::<T>::new(point, point));
+    }
+
+    /// Removes an item. Returns false if there was no item with the id.
+    pub fn remove(&mut self, id: usize) -> bool {
+        match self.items.remove(&id) {
+            Some((_, node_index)) => {
+                let items = &mut self.nodes[node_index].items;
+
+                if let Some(position) = items.iter().position(|other| *other == id) {
+                    items.swap_remove(position);
+                }
+
+                true
+            }
+            None => false
+        }
+    }
+
+    /// Moves an item to new bounds. Returns false if there was no item
+    /// with the id.
+    pub fn move_item(&mut self, id: usize, bounds: Aabb3<T>) -> bool {
+        let old_node = match self.items.get(&id) {
+            Some((_, node_index)) => *node_index,
+            None => return false
+        };
+
+        let node_index = self.find_node(&bounds);
+
+        if node_index != old_node {
+            let items = &mut self.nodes[old_node].items;
+
+            if let Some(position) = items.iter().position(|other| *other == id) {
+                items.swap_remove(position);
+            }
+
+            self.nodes[node_index].items.push(id);
+        }
+
+        self.items.insert(id, (bounds, node_index));
+        true
+    }
+
+    /// Calls back with every item whose bounds are within radius of the center.
+    pub fn query_radius<F>(&self, center: Vec3<T>, radius: T, mut callback: F)
+        where F: FnMut(usize)
+    {
+        let radius_sq = radius * radius;
+        let mut stack: Vec<usize> = vec![0];
+
+        while let Some(node_index) = stack.pop() {
+            // The root's items may lie outside of its loose bounds.
+            let outside = self.loose_bounds(node_index).distance_sq(center) > radius_sq;
+            if outside && node_index != 0 {
+                continue;
+            }
+
+            let node = &self.nodes[node_index];
+
+            for id in &node.items {
+                if self.items[id].0.distance_sq(center) <= radius_sq {
+                    callback(*id);
+                }
+            }
+
+            if node.first_child != 0 && !outside {
+                stack.extend(node.first_child..node.first_child + 8);
+            }
+        }
+    }
+
+    /// Finds the k items whose bounds are closest to the point, sorted
+    /// from nearest to furthest, along with their squared distances.
+    pub fn k_nearest(&self, point: Vec3<T>, k: usize) -> Vec<(usize, T)> {
+        let mut result: Vec<(usize, T)> = Vec::with_capacity(k + 1);
+
+        if k > 0 {
+            self.k_nearest_node(0, point, k, &mut result);
+        }
+
+        result
+    }
+
+    /// Calls back with every item whose bounds are inside or intersect the
+    /// frustum. Items of nodes entirely inside are reported without testing.
+    pub fn query_frustum<F>(&self, frustum: &Frustum<T>, mut callback: F)
+        where F: FnMut(usize)
+    {
+        let mut stack: Vec<(usize, bool)> = vec![(0, false)];
+
+        while let Some((node_index, inside)) = stack.pop() {
+            let containment = if inside { Containment::Inside } else { frustum.test_aabb(&self.loose_bounds(node_index)) };
+
+            // The root's items may lie outside of its loose bounds, so they
+            // are always tested.
+            if containment == Containment::Outside && node_index != 0 {
+                continue;
+            }
+
+            let node = &self.nodes[node_index];
+            let items_inside = containment == Containment::Inside && node_index != 0;
+
+            for id in &node.items {
+                if items_inside || frustum.test_aabb(&self.items[id].0) != Containment::Outside {
+                    callback(*id);
+                }
+            }
+
+            if node.first_child != 0 && containment != Containment::Outside {
+                let inside = containment == Containment::Inside;
+                stack.extend((node.first_child..node.first_child + 8).map(|child| (child, inside)));
+            }
+        }
+    }
+
+    /// Finds the deepest node able to hold the bounds, splitting nodes on the way.
+    fn find_node(&mut self, bounds: &Aabb3<T>) -> usize {
+        let center = bounds.center();
+        let extents = bounds.half_extents();
+        let extent = T::max(extents.x, T::max(extents.y, extents.z));
+
+        // Items centered outside of the root's cube would not fit in its children.
+        let root = &self.nodes[0];
+        let offset = center - root.center;
+        if T::max(offset.x.abs(), T::max(offset.y.abs(), offset.z.abs())) > root.half_size {
+            return 0;
+        }
+
+        let mut node_index = 0;
+
+        for _ in 0..self.max_depth {
+            let child_half = self.nodes[node_index].half_size * T::HALF;
+
+            if extent > child_half {
+                break;
+            }
+
+            if self.nodes[node_index].first_child == 0 {
+                self.split(node_index);
+            }
+
+            let node = &self.nodes[node_index];
+            let octant = (center.x >= node.center.x) as usize |
+                ((center.y >= node.center.y) as usize) << 1 |
+                ((center.z >= node.center.z) as usize) << 2;
+
+            node_index = node.first_child + octant;
+        }
+
+        node_index
+    }
+
+    /// Creates the eight children of a node. Bit 0 of a child's index
+    /// selects positive x, bit 1 positive y and bit 2 positive z.
+    fn split(&mut self, node_index: usize) {
+        let parent_center = self.nodes[node_index].center;
+        let half_size = self.nodes[node_index].half_size * T::HALF;
+        let first_child = self.nodes.len();
+
+        for octant in 0..8 {
+            let sign = |bit: usize| if octant & bit != 0 { half_size } else { -half_size };
+
+            self.nodes.push(OctreeNode::<T> {
+                center: parent_center + Vec3::<T>::new(sign(1), sign(2), sign(4)),
+                half_size,
+                first_child: 0,
+                items: Vec::new()
+            });
+        }
+
+        self.nodes[node_index].first_child = first_child;
+    }
+
+    fn k_nearest_node(&self, node_index: usize, point: Vec3<T>, k: usize, result: &mut Vec<(usize, T)>) {
+        let node = &self.nodes[node_index];
+
+        for id in &node.items {
+            let dist = self.items[id].0.distance_sq(point);
+
+            if result.len() < k || dist < result[k - 1].1 {
+                let position = result.iter().position(|(_, other)| dist < *other).unwrap_or(result.len());
+                result.insert(position, (*id, dist));
+                result.truncate(k);
+            }
+        }
+
+        if node.first_child == 0 {
+            return;
+        }
+
+        // Visit the nearest children first so the rest are more likely pruned.
+        let mut children: Vec<(usize, T)> = (node.first_child..node.first_child + 8)
+            .map(|child| (child, self.loose_bounds(child).distance_sq(point)))
+            .collect();
+        children.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal));
+
+        for (child, dist) in children {
+            if result.len() == k && dist >= result[k - 1].1 {
+                break;
+            }
+
+            self.k_nearest_node(child, point, k, result);
+        }
+    }
+}
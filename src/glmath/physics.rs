@@ -0,0 +1,315 @@
+use std::ops::{Add, AddAssign, Sub, Neg};
+
+use crate::glmath::*;
+
+/// The mass, center of mass and inertia tensor about the center of mass of
+/// a solid.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct MassProperties<T: PartialOrd + Copy> {
+    pub mass: T,
+    pub center: Vec3<T>,
+    pub inertia: Mat33<T>
+}
+
+/// A rigid body moving under forces and impulses. Velocities are in world
+/// space, the inertia tensors in body space about the center of mass.
+/// Bodies with zero inverse mass and inertia are static.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct RigidBody<T: PartialOrd + Copy> {
+    pub position: Vec3<T>,
+    pub orientation: Quat<T>,
+    pub linear_velocity: Vec3<T>,
+    pub angular_velocity: Vec3<T>,
+    pub inverse_mass: T,
+    pub inertia: Mat33<T>,
+    pub inverse_inertia: Mat33<T>
+}
+
+/// The rate of change of each part of a rigid body's state.
+#[derive(Debug, Copy, Clone)]
+struct Derivative<T: PartialOrd + Copy> {
+    velocity: Vec3<T>,
+    spin: Quat<T>,
+    acceleration: Vec3<T>,
+    angular_acceleration: Vec3<T>
+}
+
+/// Inertia of a solid box about its center.
+pub fn box_inertia<T>(mass: T, half_extents: Vec3<T>) -> Mat33<T>
+    where T: PartialOrd + Copy + Vectorable<T> + Add<Output = T> + Mul<Output = T> + Div<Output = T>
+{
+    let (x, y, z) = (half_extents.x * half_extents.x, half_extents.y * half_extents.y, half_extents.z * half_extents.z);
+    let k = mass / T::from_f64(3.0);
+
+    diagonal(k * (y + z), k * (x + z), k * (x + y))
+}
+
+/// Inertia of a solid sphere about its center.
+pub fn sphere_inertia<T>(mass: T, radius: T) -> Mat33<T>
+    where T: PartialOrd + Copy + Vectorable<T> + Mul<Output = T>
+{
+    let i = T::from_f64(0.4) * mass * radius * radius;
+    diagonal(i, i, i)
+}
+
+/// Inertia of a solid cylinder about its center, with its axis along y.
+pub fn cylinder_inertia<T>(mass: T, radius: T, half_height: T) -> Mat33<T>
+    where T: PartialOrd + Copy + Vectorable<T> + Add<Output = T> + Mul<Output = T> + Div<Output = T>
+{
+    let r = radius * radius;
+    let side = mass * (T::from_f64(3.0) * r + T::from_f64(4.0) * half_height * half_height) / T::from_f64(12.0);
+
+    diagonal(side, mass * r * T::HALF, side)
+}
+
+/// Inertia of a solid capsule about its center, with its axis along y.
+/// Half height is half the length of the cylinder between the caps.
+pub fn capsule_inertia<T>(mass: T, radius: T, half_height: T) -> Mat33<T>
+    where T: PartialOrd + Copy + Vectorable<T> + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>
+{
+    // Split the mass between the cylinder and the two caps by volume.
+    let r = radius * radius;
+    let cylinder_volume = T::TWO * r * half_height;
+    let caps_volume = T::from_f64(4.0 / 3.0) * r * radius;
+    let cylinder_mass = mass * cylinder_volume / (cylinder_volume + caps_volume);
+    let caps_mass = mass - cylinder_mass;
+
+    let cylinder = cylinder_inertia(cylinder_mass, radius, half_height);
+
+    // Each cap about its own center of mass, moved out to the end of the
+    // cylinder with the parallel axis theorem.
+    let axial = T::from_f64(0.4) * caps_mass * r;
+    let side = caps_mass * (T::from_f64(0.4) * r + half_height * half_height + T::from_f64(0.75) * half_height * radius);
+
+    cylinder + diagonal(side, axial, side)
+}
+
+/// Mass properties of a closed triangle mesh of uniform density, with
+/// triangles wound counter clockwise seen from outside. Returns None if the
+/// mesh encloses no volume.
+pub fn mesh_inertia<T>(vertices: &[Vec3<T>], triangles: &[[usize; 3]], density: T) -> Option<MassProperties<T>>
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T> + Neg<Output = T>
+{
+    // Sum the covariance of the tetrahedra between each triangle and the
+    // origin, signed by their orientation.
+    let mut volume = T::ZERO;
+    let mut weighted_center = Vec3::<T>::ZERO;
+    let mut covariance = Mat33::<T>::ZERO;
+
+    let canonical = Mat33::<T>::from_axes(
+        Vec3::<T>::new(T::TWO, T::ONE, T::ONE),
+        Vec3::<T>::new(T::ONE, T::TWO, T::ONE),
+        Vec3::<T>::new(T::ONE, T::ONE, T::TWO));
+
+    for triangle in triangles {
+        let (a, b, c) = (vertices[triangle[0]], vertices[triangle[1]], vertices[triangle[2]]);
+        let corners = Mat33::<T>::from_axes(a, b, c);
+        let det = a * (b % c);
+
+        volume += det / T::from_f64(6.0);
+        weighted_center += (a + b + c) * (det / T::from_f64(24.0));
+        covariance += scale_mat(corners * canonical * corners.get_transposed(), det / T::from_f64(120.0));
+    }
+
+    if volume <= T::ZERO {
+        return None;
+    }
+
+    let center = weighted_center / volume;
+    let mass = volume * density;
+
+    // Move the covariance to the center of mass and turn it into inertia.
+    let covariance = scale_mat(covariance, density) - scale_mat(outer(center, center), mass);
+    let trace = covariance.data[0][0] + covariance.data[1][1] + covariance.data[2][2];
+
+    Some(MassProperties::<T> {
+        mass,
+        center,
+        inertia: diagonal(trace, trace, trace) - covariance
+    })
+}
+
+/// Moves an inertia tensor about the center of mass to a point offset from
+/// it, by the parallel axis theorem.
+pub fn parallel_axis<T>(inertia: Mat33<T>, mass: T, offset: Vec3<T>) -> Mat33<T>
+    where T: PartialOrd + Copy + Vectorable<T> + Add<Output = T> + Sub<Output = T> + Mul<Output = T>
+{
+    let d = offset * offset;
+    inertia + scale_mat(diagonal(d, d, d) - outer(offset, offset), mass)
+}
+
+/// Expresses an inertia tensor in a frame rotated by the rotation.
+pub fn rotate_inertia<T>(inertia: Mat33<T>, rotation: Quat<T>) -> Mat33<T>
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T> + Neg<Output = T>
+{
+    let rotation = Mat33::<T>::from_mat44(&rotation.to_matrix());
+    rotation * inertia * rotation.get_transposed()
+}
+
+/// The rate of change of an orientation spinning at the world space angular
+/// velocity.
+pub fn orientation_derivative<T>(orientation: Quat<T>, angular_velocity: Vec3<T>) -> Quat<T>
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T> + Neg<Output = T>
+{
+    Quat::<T>::new(angular_velocity.x, angular_velocity.y, angular_velocity.z, T::ZERO) * orientation * T::HALF
+}
+
+/// Steps an orientation forward by its derivative, renormalizing to keep it
+/// a rotation.
+pub fn integrate_orientation<T>(orientation: Quat<T>, angular_velocity: Vec3<T>, dt: T) -> Quat<T>
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T> + Neg<Output = T>
+{
+    (orientation + orientation_derivative(orientation, angular_velocity) * dt).get_normalized()
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    RigidBody<T>
+{
+    /// Creates a body at rest. A mass of zero or less makes it static.
+    pub fn new(position: Vec3<T>, orientation: Quat<T>, mass: T, inertia: Mat33<T>) -> RigidBody<T> {
+        let is_static = mass <= T::ZERO;
+
+        RigidBody::<T> {
+            position,
+            orientation,
+            linear_velocity: Vec3::<T>::ZERO,
+            angular_velocity: Vec3::<T>::ZERO,
+            inverse_mass: if is_static { T::ZERO } else { T::ONE / mass },
+            inertia,
+            inverse_inertia: if is_static { Mat33::<T>::ZERO } else { inertia.get_inverted() }
+        }
+    }
+
+    pub fn is_static(&self) -> bool {
+        self.inverse_mass == T::ZERO
+    }
+
+    /// The inertia tensor in world space.
+    pub fn world_inertia(&self) -> Mat33<T> {
+        rotate_inertia(self.inertia, self.orientation)
+    }
+
+    /// The inverse inertia tensor in world space.
+    pub fn world_inverse_inertia(&self) -> Mat33<T> {
+        rotate_inertia(self.inverse_inertia, self.orientation)
+    }
+
+    /// The velocity of a world space point moving with the body.
+    pub fn velocity_at(&self, point: Vec3<T>) -> Vec3<T> {
+        self.linear_velocity + self.angular_velocity % (point - self.position)
+    }
+
+    /// Applies an impulse at a world space point.
+    pub fn apply_impulse(&mut self, impulse: Vec3<T>, point: Vec3<T>) {
+        self.linear_velocity += impulse * self.inverse_mass;
+        self.angular_velocity += self.world_inverse_inertia() * ((point - self.position) % impulse);
+    }
+
+    pub fn angular_momentum(&self) -> Vec3<T> {
+        self.world_inertia() * self.angular_velocity
+    }
+
+    pub fn kinetic_energy(&self) -> T {
+        let linear = if self.is_static() { T::ZERO } else { self.linear_velocity.length_sq() / self.inverse_mass };
+        (linear + self.angular_velocity * self.angular_momentum()) * T::HALF
+    }
+
+    /// Semi-implicit Euler: the velocities are stepped first and the new
+    /// velocities move the body. Force and torque are in world space.
+    pub fn integrate_euler(&mut self, force: Vec3<T>, torque: Vec3<T>, dt: T) {
+        self.linear_velocity += force * (self.inverse_mass * dt);
+        self.angular_velocity += self.angular_acceleration(torque) * dt;
+
+        self.position += self.linear_velocity * dt;
+        self.orientation = integrate_orientation(self.orientation, self.angular_velocity, dt);
+    }
+
+    /// Fourth order Runge-Kutta. The forces callback gives the world space
+    /// force and torque on the body in a given state at a given time.
+    pub fn integrate_rk4<F>(&mut self, time: T, dt: T, mut forces: F)
+        where F: FnMut(&RigidBody<T>, T) -> (Vec3<T>, Vec3<T>)
+    {
+        let half = dt * T::HALF;
+
+        let k1 = self.derivative(time, &mut forces);
+        let k2 = self.advanced(&k1, half).derivative(time + half, &mut forces);
+        let k3 = self.advanced(&k2, half).derivative(time + half, &mut forces);
+        let k4 = self.advanced(&k3, dt).derivative(time + dt, &mut forces);
+
+        let sixth = T::ONE / T::from_f64(6.0);
+        let average = Derivative::<T> {
+            velocity: (k1.velocity + (k2.velocity + k3.velocity) * T::TWO + k4.velocity) * sixth,
+            spin: (k1.spin + (k2.spin + k3.spin) * T::TWO + k4.spin) * sixth,
+            acceleration: (k1.acceleration + (k2.acceleration + k3.acceleration) * T::TWO + k4.acceleration) * sixth,
+            angular_acceleration: (k1.angular_acceleration + (k2.angular_acceleration + k3.angular_acceleration) * T::TWO
+                + k4.angular_acceleration) * sixth
+        };
+
+        *self = self.advanced(&average, dt);
+    }
+
+    /// Euler's rotation equation with the gyroscopic term, in world space.
+    fn angular_acceleration(&self, torque: Vec3<T>) -> Vec3<T> {
+        let momentum = self.angular_momentum();
+        self.world_inverse_inertia() * (torque - self.angular_velocity % momentum)
+    }
+
+    fn derivative<F>(&self, time: T, forces: &mut F) -> Derivative<T>
+        where F: FnMut(&RigidBody<T>, T) -> (Vec3<T>, Vec3<T>)
+    {
+        let (force, torque) = forces(self, time);
+
+        Derivative::<T> {
+            velocity: self.linear_velocity,
+            spin: orientation_derivative(self.orientation, self.angular_velocity),
+            acceleration: force * self.inverse_mass,
+            angular_acceleration: self.angular_acceleration(torque)
+        }
+    }
+
+    /// The state a step of dt along the derivative.
+    fn advanced(&self, derivative: &Derivative<T>, dt: T) -> RigidBody<T> {
+        RigidBody::<T> {
+            position: self.position + derivative.velocity * dt,
+            orientation: (self.orientation + derivative.spin * dt).get_normalized(),
+            linear_velocity: self.linear_velocity + derivative.acceleration * dt,
+            angular_velocity: self.angular_velocity + derivative.angular_acceleration * dt,
+            ..*self
+        }
+    }
+}
+
+fn diagonal<T: PartialOrd + Copy + Vectorable<T>>(x: T, y: T, z: T) -> Mat33<T> {
+    Mat33::<T> {
+        data: [
+            [ x, T::ZERO, T::ZERO ],
+            [ T::ZERO, y, T::ZERO ],
+            [ T::ZERO, T::ZERO, z ]
+        ]
+    }
+}
+
+/// The outer product a * b^T.
+fn outer<T: PartialOrd + Copy + Mul<Output = T>>(a: Vec3<T>, b: Vec3<T>) -> Mat33<T> {
+    Mat33::<T> {
+        data: [
+            [ a.x * b.x, a.y * b.x, a.z * b.x ],
+            [ a.x * b.y, a.y * b.y, a.z * b.y ],
+            [ a.x * b.z, a.y * b.z, a.z * b.z ]
+        ]
+    }
+}
+
+fn scale_mat<T: PartialOrd + Copy + Mul<Output = T>>(m: Mat33<T>, s: T) -> Mat33<T> {
+    Mat33::<T> { data: m.data.map(|column| column.map(|v| v * s)) }
+}
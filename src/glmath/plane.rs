@@ -0,0 +1,113 @@
+use std::{ops::{Add, Sub, Neg}, fmt::Display};
+
+use crate::glmath::*;
+
+/// The plane of all points p with normal * p + d = 0.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct Plane<T: PartialOrd + Copy> {
+    pub normal: Vec3<T>,
+    pub d: T
+}
+
+impl<T: PartialOrd + Copy + Display> Display for Plane<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}, {}]", self.normal, self.d)
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    Plane<T>
+{
+    pub fn new(normal: Vec3<T>, d: T) -> Plane<T> {
+        Plane::<T> { normal, d }
+    }
+
+    /// Constructs the plane through a point facing along normal.
+    pub fn from_point_normal(point: Vec3<T>, normal: Vec3<T>) -> Plane<T> {
+        Plane::<T> {
+            normal,
+            d: -(normal * point)
+        }
+    }
+
+    /// Constructs the plane through three points. The normal follows
+    /// the counter clockwise winding of abc.
+    pub fn from_points(a: Vec3<T>, b: Vec3<T>, c: Vec3<T>) -> Plane<T> {
+        let normal = ((b - a) % (c - a)).get_normalized();
+        Plane::<T>::from_point_normal(a, normal)
+    }
+
+    /// Scales the plane so its normal has unit length.
+    /// Signed distances are only true distances for normalized planes.
+    pub fn normalize(&mut self) {
+        let len = self.normal.length();
+
+        if len != T::ZERO {
+            self.normal /= len;
+            self.d /= len;
+        }
+    }
+
+    pub fn get_normalized(&self) -> Plane<T> {
+        let mut plane = *self;
+        plane.normalize();
+        plane
+    }
+
+    /// Positive in front of the plane, negative behind it.
+    pub fn signed_distance(&self, point: Vec3<T>) -> T {
+        self.normal * point + self.d
+    }
+
+    /// Returns the point on the plane closest to the given point.
+    pub fn project_point(&self, point: Vec3<T>) -> Vec3<T> {
+        let normal_len_sq = self.normal.length_sq();
+
+        if normal_len_sq == T::ZERO {
+            return point;
+        }
+
+        point - self.normal * (self.signed_distance(point) / normal_len_sq)
+    }
+
+    /// Intersects the infinite line through point along dir.
+    /// Returns None if the line is parallel to the plane.
+    pub fn intersect_line(&self, point: Vec3<T>, dir: Vec3<T>) -> Option<Vec3<T>> {
+        let denom = self.normal * dir;
+
+        if denom.abs() <= T::EPSILON * self.normal.length() * dir.length() {
+            return None;
+        }
+
+        Some(point - dir * (self.signed_distance(point) / denom))
+    }
+
+    /// Intersects the segment from a to b.
+    pub fn intersect_segment(&self, a: Vec3<T>, b: Vec3<T>) -> Option<Vec3<T>> {
+        let dist_a = self.signed_distance(a);
+        let dist_b = self.signed_distance(b);
+
+        if (dist_a > T::ZERO && dist_b > T::ZERO) || (dist_a < T::ZERO && dist_b < T::ZERO) {
+            return None;
+        }
+
+        self.intersect_line(a, b - a)
+    }
+
+    /// Returns the single point shared by three planes.
+    pub fn intersect_planes(a: &Plane<T>, b: &Plane<T>, c: &Plane<T>) -> Option<Vec3<T>> {
+        let bc = b.normal % c.normal;
+        let denom = a.normal * bc;
+
+        if denom.abs() <= T::EPSILON * a.normal.length() * b.normal.length() * c.normal.length() {
+            return None;
+        }
+
+        let ca = c.normal % a.normal;
+        let ab = a.normal % b.normal;
+
+        Some(-(bc * a.d + ca * b.d + ab * c.d) / denom)
+    }
+}
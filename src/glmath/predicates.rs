@@ -0,0 +1,298 @@
+use std::ops::{Add, Sub, Mul, Neg};
+
+use crate::glmath::*;
+
+// Bounds on the relative rounding error of the plain floating point
+// determinants, from Shewchuk's "Adaptive Precision Floating-Point
+// Arithmetic and Fast Robust Geometric Predicates". When a determinant is
+// larger than its bound times the permanent its sign is certain, otherwise
+// it is recomputed exactly.
+const UNIT_ROUNDOFF: f64 = f64::EPSILON * 0.5;
+const ORIENT2D_BOUND: f64 = (3.0 + 16.0 * UNIT_ROUNDOFF) * UNIT_ROUNDOFF;
+const ORIENT3D_BOUND: f64 = (7.0 + 56.0 * UNIT_ROUNDOFF) * UNIT_ROUNDOFF;
+const INCIRCLE_BOUND: f64 = (10.0 + 96.0 * UNIT_ROUNDOFF) * UNIT_ROUNDOFF;
+const INSPHERE_BOUND: f64 = (16.0 + 224.0 * UNIT_ROUNDOFF) * UNIT_ROUNDOFF;
+
+/// Positive if a, b and c are in counterclockwise order, negative if
+/// clockwise and zero if they are collinear. The sign is always exact and
+/// the value approximates twice the signed area of the triangle.
+pub fn orient2d(a: Vec2<f64>, b: Vec2<f64>, c: Vec2<f64>) -> f64 {
+    let left = (a.x - c.x) * (b.y - c.y);
+    let right = (a.y - c.y) * (b.x - c.x);
+    let det = left - right;
+
+    // Products of opposite sign cannot cancel.
+    if (left > 0.0 && right <= 0.0) || (left < 0.0 && right >= 0.0) || (left == 0.0 && right == 0.0) {
+        return det;
+    }
+
+    if det.abs() >= ORIENT2D_BOUND * (left.abs() + right.abs()) {
+        return det;
+    }
+
+    let (acx, bcx) = (Expansion::difference(a.x, c.x), Expansion::difference(b.x, c.x));
+    let (acy, bcy) = (Expansion::difference(a.y, c.y), Expansion::difference(b.y, c.y));
+
+    (&(&acx * &bcy) - &(&acy * &bcx)).estimate()
+}
+
+/// Positive if d lies below the plane through a, b and c, where below means
+/// a, b and c appear counterclockwise when seen from above. Negative if d
+/// lies above and zero if the four points are coplanar. The sign is always
+/// exact and the value approximates six times the signed volume.
+pub fn orient3d(a: Vec3<f64>, b: Vec3<f64>, c: Vec3<f64>, d: Vec3<f64>) -> f64 {
+    let (ad, bd, cd) = (a - d, b - d, c - d);
+
+    let (bdxcdy, cdxbdy) = (bd.x * cd.y, cd.x * bd.y);
+    let (cdxady, adxcdy) = (cd.x * ad.y, ad.x * cd.y);
+    let (adxbdy, bdxady) = (ad.x * bd.y, bd.x * ad.y);
+
+    let det = ad.z * (bdxcdy - cdxbdy) + bd.z * (cdxady - adxcdy) + cd.z * (adxbdy - bdxady);
+    let permanent = (bdxcdy.abs() + cdxbdy.abs()) * ad.z.abs()
+        + (cdxady.abs() + adxcdy.abs()) * bd.z.abs()
+        + (adxbdy.abs() + bdxady.abs()) * cd.z.abs();
+
+    if det.abs() >= ORIENT3D_BOUND * permanent {
+        return det;
+    }
+
+    let [ad, bd, cd] = [a, b, c].map(|p| Exact3::difference(p, d));
+    let minor = |p: &Exact3, q: &Exact3| &(&p.x * &q.y) - &(&q.x * &p.y);
+
+    (&(&(&ad.z * &minor(&bd, &cd)) + &(&bd.z * &minor(&cd, &ad))) + &(&cd.z * &minor(&ad, &bd))).estimate()
+}
+
+/// Positive if d lies inside the circle through a, b and c, negative if it
+/// lies outside and zero if the four points are cocircular, provided a, b
+/// and c are in counterclockwise order. The sign flips when they are
+/// clockwise. The sign is always exact.
+pub fn incircle(a: Vec2<f64>, b: Vec2<f64>, c: Vec2<f64>, d: Vec2<f64>) -> f64 {
+    let (ad, bd, cd) = (a - d, b - d, c - d);
+
+    let (bdxcdy, cdxbdy) = (bd.x * cd.y, cd.x * bd.y);
+    let (cdxady, adxcdy) = (cd.x * ad.y, ad.x * cd.y);
+    let (adxbdy, bdxady) = (ad.x * bd.y, bd.x * ad.y);
+    let (alift, blift, clift) = (ad.x * ad.x + ad.y * ad.y, bd.x * bd.x + bd.y * bd.y, cd.x * cd.x + cd.y * cd.y);
+
+    let det = alift * (bdxcdy - cdxbdy) + blift * (cdxady - adxcdy) + clift * (adxbdy - bdxady);
+    let permanent = (bdxcdy.abs() + cdxbdy.abs()) * alift
+        + (cdxady.abs() + adxcdy.abs()) * blift
+        + (adxbdy.abs() + bdxady.abs()) * clift;
+
+    if det.abs() >= INCIRCLE_BOUND * permanent {
+        return det;
+    }
+
+    let [ad, bd, cd] = [a, b, c].map(|p| Exact3::difference(Vec3::<f64>::new(p.x, p.y, 0.0), Vec3::<f64>::new(d.x, d.y, 0.0)));
+    let minor = |p: &Exact3, q: &Exact3| &(&p.x * &q.y) - &(&q.x * &p.y);
+
+    (&(&(&ad.lift() * &minor(&bd, &cd)) + &(&bd.lift() * &minor(&cd, &ad))) + &(&cd.lift() * &minor(&ad, &bd))).estimate()
+}
+
+/// Positive if e lies inside the sphere through a, b, c and d, negative if
+/// it lies outside and zero if the five points are cospherical, provided
+/// orient3d(a, b, c, d) is positive. The sign flips when it is negative.
+/// The sign is always exact.
+pub fn insphere(a: Vec3<f64>, b: Vec3<f64>, c: Vec3<f64>, d: Vec3<f64>, e: Vec3<f64>) -> f64 {
+    let (ae, be, ce, de) = (a - e, b - e, c - e, d - e);
+
+    let (aexbey, bexaey) = (ae.x * be.y, be.x * ae.y);
+    let (bexcey, cexbey) = (be.x * ce.y, ce.x * be.y);
+    let (cexdey, dexcey) = (ce.x * de.y, de.x * ce.y);
+    let (dexaey, aexdey) = (de.x * ae.y, ae.x * de.y);
+    let (aexcey, cexaey) = (ae.x * ce.y, ce.x * ae.y);
+    let (bexdey, dexbey) = (be.x * de.y, de.x * be.y);
+
+    let (ab, bc, cd, da) = (aexbey - bexaey, bexcey - cexbey, cexdey - dexcey, dexaey - aexdey);
+    let (ac, bd) = (aexcey - cexaey, bexdey - dexbey);
+
+    let abc = ae.z * bc - be.z * ac + ce.z * ab;
+    let bcd = be.z * cd - ce.z * bd + de.z * bc;
+    let cda = ce.z * da + de.z * ac + ae.z * cd;
+    let dab = de.z * ab + ae.z * bd + be.z * da;
+
+    let [alift, blift, clift, dlift] = [ae, be, ce, de].map(|p| p.x * p.x + p.y * p.y + p.z * p.z);
+    let det = (dlift * abc - clift * dab) + (blift * cda - alift * bcd);
+
+    let (abp, bcp, cdp) = (aexbey.abs() + bexaey.abs(), bexcey.abs() + cexbey.abs(), cexdey.abs() + dexcey.abs());
+    let (dap, acp, bdp) = (dexaey.abs() + aexdey.abs(), aexcey.abs() + cexaey.abs(), bexdey.abs() + dexbey.abs());
+    let [aez, bez, cez, dez] = [ae.z, be.z, ce.z, de.z].map(f64::abs);
+
+    let permanent = (cdp * bez + bdp * cez + bcp * dez) * alift
+        + (dap * cez + acp * dez + cdp * aez) * blift
+        + (abp * dez + bdp * aez + dap * bez) * clift
+        + (bcp * aez + acp * bez + abp * cez) * dlift;
+
+    if det.abs() >= INSPHERE_BOUND * permanent {
+        return det;
+    }
+
+    let [ae, be, ce, de] = [a, b, c, d].map(|p| Exact3::difference(p, e));
+    let minor = |p: &Exact3, q: &Exact3| &(&p.x * &q.y) - &(&q.x * &p.y);
+
+    let (ab, bc, cd, da) = (minor(&ae, &be), minor(&be, &ce), minor(&ce, &de), minor(&de, &ae));
+    let (ac, bd) = (minor(&ae, &ce), minor(&be, &de));
+
+    let abc = &(&(&ae.z * &bc) - &(&be.z * &ac)) + &(&ce.z * &ab);
+    let bcd = &(&(&be.z * &cd) - &(&ce.z * &bd)) + &(&de.z * &bc);
+    let cda = &(&(&ce.z * &da) + &(&de.z * &ac)) + &(&ae.z * &cd);
+    let dab = &(&(&de.z * &ab) + &(&ae.z * &bd)) + &(&be.z * &da);
+
+    let left = &(&de.lift() * &abc) - &(&ce.lift() * &dab);
+    let right = &(&be.lift() * &cda) - &(&ae.lift() * &bcd);
+
+    (&left + &right).estimate()
+}
+
+/// An exact real number as a sum of doubles that do not overlap, ordered by
+/// increasing magnitude with zeros removed. Empty means zero.
+#[derive(Debug, Clone, PartialEq)]
+struct Expansion(Vec<f64>);
+
+/// The differences between two points as exact expansions.
+struct Exact3 {
+    x: Expansion,
+    y: Expansion,
+    z: Expansion
+}
+
+impl Exact3 {
+    fn difference(p: Vec3<f64>, q: Vec3<f64>) -> Exact3 {
+        Exact3 {
+            x: Expansion::difference(p.x, q.x),
+            y: Expansion::difference(p.y, q.y),
+            z: Expansion::difference(p.z, q.z)
+        }
+    }
+
+    /// The squared length.
+    fn lift(&self) -> Expansion {
+        &(&(&self.x * &self.x) + &(&self.y * &self.y)) + &(&self.z * &self.z)
+    }
+}
+
+impl Expansion {
+    /// a - b without rounding.
+    fn difference(a: f64, b: f64) -> Expansion {
+        let (sum, error) = two_sum(a, -b);
+        Expansion([error, sum].into_iter().filter(|x| *x != 0.0).collect())
+    }
+
+    /// Multiplies by a single double without rounding.
+    fn scaled(&self, b: f64) -> Expansion {
+        let mut components = Vec::with_capacity(self.0.len() * 2);
+        let mut iter = self.0.iter();
+
+        let Some(first) = iter.next() else {
+            return Expansion(components);
+        };
+
+        let (mut q, error) = two_product(*first, b);
+        components.push(error);
+
+        for e in iter {
+            let (product, product_error) = two_product(*e, b);
+            let (sum, error) = two_sum(q, product_error);
+            components.push(error);
+            let (next, error) = two_sum(product, sum);
+            components.push(error);
+            q = next;
+        }
+
+        components.push(q);
+        components.retain(|x| *x != 0.0);
+        Expansion(components)
+    }
+
+    /// Approximates the value with a double of the exact sign.
+    fn estimate(&self) -> f64 {
+        let Some(largest) = self.0.last() else {
+            return 0.0;
+        };
+
+        let sum: f64 = self.0.iter().sum();
+        if sum.signum() == largest.signum() { sum } else { *largest }
+    }
+}
+
+impl Add for &Expansion {
+    type Output = Expansion;
+
+    // Merges both by magnitude and accumulates with exact sums, keeping the
+    // rounding error of each step as a component.
+    fn add(self, rhs: Self) -> Self::Output {
+        let mut merged = Vec::with_capacity(self.0.len() + rhs.0.len());
+        let (mut i, mut j) = (0, 0);
+        while i < self.0.len() || j < rhs.0.len() {
+            if j == rhs.0.len() || (i < self.0.len() && self.0[i].abs() < rhs.0[j].abs()) {
+                merged.push(self.0[i]);
+                i += 1;
+            }
+            else {
+                merged.push(rhs.0[j]);
+                j += 1;
+            }
+        }
+
+        let mut components = Vec::with_capacity(merged.len());
+        let mut iter = merged.into_iter();
+        let Some(mut q) = iter.next() else {
+            return Expansion(components);
+        };
+
+        for g in iter {
+            let (sum, error) = two_sum(q, g);
+            if error != 0.0 {
+                components.push(error);
+            }
+            q = sum;
+        }
+
+        if q != 0.0 {
+            components.push(q);
+        }
+        Expansion(components)
+    }
+}
+
+impl Neg for &Expansion {
+    type Output = Expansion;
+
+    fn neg(self) -> Self::Output {
+        Expansion(self.0.iter().map(|x| -x).collect())
+    }
+}
+
+impl Sub for &Expansion {
+    type Output = Expansion;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + &-rhs
+    }
+}
+
+impl Mul for &Expansion {
+    type Output = Expansion;
+
+    // Sums the products with each component of the shorter expansion.
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn mul(self, rhs: Self) -> Self::Output {
+        let (long, short) = if self.0.len() >= rhs.0.len() { (self, rhs) } else { (rhs, self) };
+        short.0.iter().fold(Expansion(Vec::new()), |sum, b| &sum + &long.scaled(*b))
+    }
+}
+
+/// The rounded sum and its rounding error, by Knuth's branch free method.
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let sum = a + b;
+    let b_virtual = sum - a;
+    let a_virtual = sum - b_virtual;
+    (sum, (a - a_virtual) + (b - b_virtual))
+}
+
+/// The rounded product and its rounding error, which a fused multiply-add
+/// gives exactly.
+fn two_product(a: f64, b: f64) -> (f64, f64) {
+    let product = a * b;
+    (product, a.mul_add(b, -product))
+}
@@ -48,7 +48,7 @@ impl
     fn invert(&mut self) {
         let mag = self.length_sq();
 
-        self.x = self.x / mag;
+        self.x /= mag;
         self.y = -self.y / mag;
         self.z = -self.z / mag;
         self.w = -self.w / mag;
@@ -83,32 +83,31 @@ impl
         let y_sq = self.y * self.y;
         let z_sq = self.z * self.z;
 
+        // The data is stored column major, data[column][row].
         matrix.data[0][0] = T::ONE - T::TWO * (y_sq + z_sq);
-        matrix.data[0][1] = T::TWO * (xy - zw);
-        matrix.data[0][2] = T::TWO * (xz + yw);
+        matrix.data[1][0] = T::TWO * (xy - zw);
+        matrix.data[2][0] = T::TWO * (xz + yw);
         
-        matrix.data[1][0] = T::TWO * (xy + zw);
+        matrix.data[0][1] = T::TWO * (xy + zw);
         matrix.data[1][1] = T::ONE - T::TWO * (x_sq + z_sq);
-        matrix.data[1][2] = T::TWO * (yz - xw);
+        matrix.data[2][1] = T::TWO * (yz - xw);
 
-        matrix.data[2][0] = T::TWO * (xz - yw);
-        matrix.data[2][1] = T::TWO * (yz + xw);
+        matrix.data[0][2] = T::TWO * (xz - yw);
+        matrix.data[1][2] = T::TWO * (yz + xw);
         matrix.data[2][2] = T::ONE - T::TWO * (x_sq + y_sq);
 
         matrix.data[3][3] = T::ONE;
 
-        return matrix;
+        matrix
     }
 
     fn to_euler(&self) -> Vec3<T> {
-        let yaw;
         let mut pitch;
-        let roll;
 
         // Roll x
         let sinr_cosp = T::TWO * (self.w * self.x + self.y * self.z);
         let cosr_cosp = T::ONE - T::TWO * (self.x * self.x + self.y * self.y);
-        roll = T::atan2(sinr_cosp, cosr_cosp);
+        let roll = T::atan2(sinr_cosp, cosr_cosp);
 
         // Pitch (y-axis rotation)
         let sinp = T::TWO * (self.w * self.y - self.z * self.x);
@@ -127,7 +126,7 @@ impl
         // Yaw (z-axis rotation)
         let siny_cosp = T::TWO * (self.w * self.z + self.x * self.y);
         let cosy_cosp = T::ONE - T::TWO * (self.y * self.y + self.z * self.z);
-        yaw = T::atan2(siny_cosp, cosy_cosp);
+        let yaw = T::atan2(siny_cosp, cosy_cosp);
 
         Vec3::<T> {
             x: roll,
@@ -140,51 +139,22 @@ impl
         let mut matrix = self.to_matrix();
         matrix.rotate(axis, angle);
 
-        let trace = matrix.data[0][0] + matrix.data[1][1] + matrix.data[2][2]; 
-
-        if trace > T::ZERO {
-            let s = T::HALF / (trace + T::ONE).sqrt();
-            self.w = T::QUARTER / s;
-            self.x = (matrix.data[2][1] - matrix.data[1][2]) * s;
-            self.y = (matrix.data[0][2] - matrix.data[2][0]) * s;
-            self.z = (matrix.data[1][0] - matrix.data[0][1]) * s;
-        } else {
-            if matrix.data[0][0] > matrix.data[1][1] && matrix.data[0][0] > matrix.data[2][2] {
-                let s = T::TWO * (T::ONE + matrix.data[0][0] - matrix.data[1][1] - matrix.data[2][2]).sqrt();
-                self.w = (matrix.data[2][1] - matrix.data[1][2]) / s;
-                self.x = T::QUARTER * s;
-                self.y = (matrix.data[0][1] + matrix.data[1][0]) / s;
-                self.z = (matrix.data[0][2] + matrix.data[2][0]) / s;
-            } else if matrix.data[1][1] > matrix.data[2][2] {
-                let s = T::TWO * (T::ONE + matrix.data[1][1] - matrix.data[0][0] - matrix.data[2][2]).sqrt();
-                self.w = (matrix.data[0][2] - matrix.data[2][0]) / s;
-                self.x = (matrix.data[0][1] + matrix.data[1][0]) / s;
-                self.y = T::QUARTER * s;
-                self.z = (matrix.data[1][2] + matrix.data[2][1]) / s;
-            }
-            else {
-                let s = T::TWO * (T::ONE + matrix.data[2][2] - matrix.data[0][0] - matrix.data[1][1]).sqrt();
-                self.w = (matrix.data[1][0] - matrix.data[0][1]) / s;
-                self.x = (matrix.data[0][2] + matrix.data[2][0]) / s;
-                self.y = (matrix.data[1][2] + matrix.data[2][1]) / s;
-                self.z = T::QUARTER * s;
-            }
-        }
-
+        *self = Quat::<T>::from_matrix(&matrix);
         self.normalize();
     }
 
     fn from_matrix(rot_mat: &Mat44<T>) -> Quat<T> {
+        // mRC is the element in row R and column C.
         let m00 = rot_mat.data[0][0];
-        let m01 = rot_mat.data[0][1]; 
-        let m02 = rot_mat.data[0][2];
+        let m01 = rot_mat.data[1][0]; 
+        let m02 = rot_mat.data[2][0];
 
-        let m10 = rot_mat.data[1][0];
+        let m10 = rot_mat.data[0][1];
         let m11 = rot_mat.data[1][1];
-        let m12 = rot_mat.data[1][2];
+        let m12 = rot_mat.data[2][1];
 
-        let m20 = rot_mat.data[2][0]; 
-        let m21 = rot_mat.data[2][1];
+        let m20 = rot_mat.data[0][2]; 
+        let m21 = rot_mat.data[1][2];
         let m22 = rot_mat.data[2][2];
 
         let mut s;
@@ -328,6 +298,145 @@ impl<T: PartialOrd + Copy + Vectorable<T>> Quat<T> {
     }
 }
 
+impl
+    <T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> +
+        AddAssign<T> +
+        Sub<Output = T> +
+        std::ops::Mul<Output = T> +
+        std::ops::Div<Output = T> +
+        Neg<Output = T> +
+        std::ops::DivAssign<T>>
+    Quat<T>
+{
+    /// The inverse rotation of a unit quaternion.
+    pub fn conjugate(&self) -> Quat<T> {
+        Quat::<T>::new(-self.x, -self.y, -self.z, self.w)
+    }
+
+    pub fn dot(&self, other: &Quat<T>) -> T {
+        self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+    }
+
+    /// The logarithm of a unit quaternion: its rotation axis scaled by
+    /// half of its rotation angle.
+    pub fn log(&self) -> Vec3<T> {
+        let v = Vec3::<T>::new(self.x, self.y, self.z);
+        let s = v.length();
+
+        if s <= T::EPSILON {
+            return v;
+        }
+
+        v * (T::atan2(s, self.w) / s)
+    }
+
+    /// The unit quaternion whose logarithm is v.
+    pub fn exp(v: Vec3<T>) -> Quat<T> {
+        let angle = v.length();
+
+        if angle <= T::EPSILON {
+            return Quat::<T>::new(v.x, v.y, v.z, T::ONE).get_normalized();
+        }
+
+        let s = angle.sin() / angle;
+        Quat::<T>::new(v.x * s, v.y * s, v.z * s, angle.cos())
+    }
+
+    /// The shortest rotation turning the direction from into the direction to.
+    pub fn from_to(from: Vec3<T>, to: Vec3<T>) -> Quat<T> {
+        let from = from.get_normalized();
+        let to = to.get_normalized();
+        let cos = from * to;
+
+        if cos < -T::ONE + T::EPSILON {
+            // Opposite directions: turn half way around any perpendicular axis.
+            let axis = from.any_perpendicular();
+            return Quat::<T>::new(axis.x, axis.y, axis.z, T::ZERO);
+        }
+
+        // Half way between the identity and the doubled rotation.
+        let axis = from % to;
+        Quat::<T>::new(axis.x, axis.y, axis.z, T::ONE + cos).get_normalized()
+    }
+
+    /// Interpolates along the great arc from a to b at constant angular
+    /// velocity. Unlike slerp, b is not negated to take the shorter arc.
+    pub fn slerp_arc(a: Quat<T>, b: Quat<T>, blend: T) -> Quat<T> {
+        a * Quat::<T>::exp((a.conjugate() * b).log() * blend)
+    }
+
+    /// Splits a unit quaternion into a swing about an axis perpendicular to
+    /// the given unit axis followed by a twist about it, so that
+    /// self = swing * twist. A half turn about a perpendicular axis has no
+    /// twist and is returned whole as the swing.
+    pub fn swing_twist(&self, axis: Vec3<T>) -> (Quat<T>, Quat<T>) {
+        let projected = axis * (Vec3::<T>::new(self.x, self.y, self.z) * axis);
+        let twist = Quat::<T>::new(projected.x, projected.y, projected.z, self.w);
+
+        if twist.length_sq() <= T::EPSILON {
+            return (*self, Quat::<T>::IDENTITY);
+        }
+
+        let twist = twist.get_normalized();
+        (*self * twist.conjugate(), twist)
+    }
+
+    /// The signed angle of the twist about the unit axis, in [-PI, PI].
+    pub fn twist_angle(&self, axis: Vec3<T>) -> T {
+        let sin = Vec3::<T>::new(self.x, self.y, self.z) * axis;
+        let (sin, cos) = if self.w < T::ZERO { (-sin, -self.w) } else { (sin, self.w) };
+
+        T::atan2(sin, cos) * T::TWO
+    }
+
+    /// Keeps the twist about the unit axis between min and max radians,
+    /// leaving the swing unchanged.
+    pub fn clamp_twist(&self, axis: Vec3<T>, min: T, max: T) -> Quat<T> {
+        let (swing, _) = self.swing_twist(axis);
+        let angle = T::min(T::max(self.twist_angle(axis), min), max);
+
+        swing * Quat::<T>::from_axis_angle(axis, angle)
+    }
+
+    /// Limits the swing so the unit axis turns at most angle radians away
+    /// from where it started, leaving the twist unchanged.
+    pub fn clamp_swing_cone(&self, axis: Vec3<T>, angle: T) -> Quat<T> {
+        let (swing, twist) = self.swing_twist(axis);
+        let swing_angle = swing.log().length() * T::TWO;
+
+        if swing_angle <= angle {
+            return *self;
+        }
+
+        Quat::<T>::exp(swing.log() * (angle / swing_angle)) * twist
+    }
+
+    /// Limits the swing to an elliptical cone around the unit axis. The swing
+    /// may turn at most major_angle radians about the unit major axis, which
+    /// is perpendicular to the twist axis, and minor_angle radians about
+    /// axis % major_axis. Swings outside the cone are scaled back towards
+    /// the rest pose until they reach its edge.
+    pub fn clamp_swing_ellipse(&self, axis: Vec3<T>, major_axis: Vec3<T>, major_angle: T, minor_angle: T) -> Quat<T> {
+        let (swing, twist) = self.swing_twist(axis);
+        let rotation = swing.log() * T::TWO;
+
+        let a = rotation * major_axis;
+        let b = rotation * (axis % major_axis);
+
+        // Inside when (a / major)^2 + (b / minor)^2 <= 1, written without
+        // dividing so zero limits lock the swing about that axis.
+        let extent = (a * minor_angle) * (a * minor_angle) + (b * major_angle) * (b * major_angle);
+        let limit = major_angle * minor_angle;
+
+        if extent <= limit * limit {
+            return *self;
+        }
+
+        Quat::<T>::exp(rotation * (limit / extent.sqrt() * T::HALF)) * twist
+    }
+}
+
 impl<T: PartialOrd + Copy + Neg<Output = T>> Neg for Quat<T> {
     type Output = Quat<T>;
 
@@ -451,10 +560,7 @@ impl<T: PartialOrd + Copy + Mul<Output = T> +
     Add<Output = T> + Sub<Output = T>> MulAssign for Quat<T> 
 {
     fn mul_assign(&mut self, rhs: Self) {
-        self.x = self.x * rhs.w + self.w * rhs.x + self.y * rhs.z - self.z * rhs.y;
-        self.y = self.y * rhs.w + self.w * rhs.y + self.z * rhs.x - self.x * rhs.z;
-        self.z = self.z * rhs.w + self.w * rhs.z + self.x * rhs.y - self.y * rhs.x;
-        self.w = self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z;
+        *self = *self * rhs;
     }
 }
 
@@ -508,7 +614,7 @@ impl<T: PartialOrd + Copy> QuaternionSwizzle<T> for Quat<T> {
     }
 
     fn xyzw(&self) -> Quat<T> {
-        self.clone()
+        *self
     }
 
     fn yxzw(&self) -> Quat<T> {
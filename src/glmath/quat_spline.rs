@@ -0,0 +1,149 @@
+use std::ops::{Add, AddAssign, Sub, Neg};
+
+use crate::glmath::*;
+
+/// A C1 continuous rotation path through keyframed rotations, interpolated
+/// with squad. Inner control rotations are chosen from the neighboring keys
+/// so the angular velocity is continuous across keys, even when keys are
+/// unevenly spaced in time.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QuatSpline<T: PartialOrd + Copy> {
+    /// Key times in increasing order.
+    pub times: Vec<T>,
+    /// Key rotations, each negated as needed to lie in the same hemisphere
+    /// as the previous one.
+    pub rotations: Vec<Quat<T>>,
+    /// Control rotations shaping the segment arriving at each key.
+    incoming: Vec<Quat<T>>,
+    /// Control rotations shaping the segment leaving each key.
+    outgoing: Vec<Quat<T>>
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    QuatSpline<T>
+{
+    /// Creates a spline from (time, rotation) keys. Returns None if there
+    /// are no keys or the times are not strictly increasing.
+    pub fn new(keys: &[(T, Quat<T>)]) -> Option<QuatSpline<T>> {
+        if keys.is_empty() || keys.windows(2).any(|pair| pair[1].0 <= pair[0].0) {
+            return None;
+        }
+
+        let times: Vec<T> = keys.iter().map(|key| key.0).collect();
+        let mut rotations: Vec<Quat<T>> = Vec::with_capacity(keys.len());
+
+        for (_, rotation) in keys {
+            let rotation = rotation.get_normalized();
+
+            // q and -q are the same rotation, but interpolating between
+            // opposite hemispheres takes the long way around.
+            match rotations.last() {
+                Some(previous) if previous.dot(&rotation) < T::ZERO => rotations.push(-rotation),
+                _ => rotations.push(rotation)
+            }
+        }
+
+        let mut spline = QuatSpline::<T> {
+            times,
+            rotations,
+            incoming: Vec::new(),
+            outgoing: Vec::new()
+        };
+
+        spline.compute_tangents();
+        Some(spline)
+    }
+
+    pub fn len(&self) -> usize {
+        self.times.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.times.is_empty()
+    }
+
+    pub fn start_time(&self) -> T {
+        self.times[0]
+    }
+
+    pub fn end_time(&self) -> T {
+        self.times[self.times.len() - 1]
+    }
+
+    /// The incoming and outgoing squad control rotations of a key.
+    pub fn controls(&self, index: usize) -> (Quat<T>, Quat<T>) {
+        (self.incoming[index], self.outgoing[index])
+    }
+
+    /// The rotation at a time. Times outside of the keys are clamped.
+    pub fn sample(&self, time: T) -> Quat<T> {
+        if time <= self.start_time() {
+            return self.rotations[0];
+        }
+
+        if time >= self.end_time() {
+            return self.rotations[self.rotations.len() - 1];
+        }
+
+        let next = self.times.partition_point(|key| *key <= time);
+        let index = next - 1;
+        let t = (time - self.times[index]) / (self.times[next] - self.times[index]);
+
+        squad(self.rotations[index], self.outgoing[index], self.incoming[next], self.rotations[next], t)
+    }
+
+    /// Chooses control rotations from an estimate of each key's angular
+    /// velocity in log space.
+    fn compute_tangents(&mut self) {
+        let count = self.rotations.len();
+        self.incoming = self.rotations.clone();
+        self.outgoing = self.rotations.clone();
+
+        if count < 2 {
+            return;
+        }
+
+        for i in 0..count {
+            let q = self.rotations[i];
+
+            // Offsets to the neighboring keys in the tangent space at this key.
+            let to_previous = (i > 0).then(|| ((q.conjugate() * self.rotations[i - 1]).log(), self.times[i] - self.times[i - 1]));
+            let to_next = (i + 1 < count).then(|| ((q.conjugate() * self.rotations[i + 1]).log(), self.times[i + 1] - self.times[i]));
+
+            let velocity = match (to_previous, to_next) {
+                (Some((prev, dt0)), Some((next, dt1))) => {
+                    // Non-uniform finite difference, weighting each side's
+                    // slope by the other side's interval.
+                    (next * (dt0 / dt1) - prev * (dt1 / dt0)) * (T::ONE / (dt0 + dt1))
+                }
+                (None, Some((next, dt1))) => next * (T::ONE / dt1),
+                (Some((prev, dt0)), None) => -prev * (T::ONE / dt0),
+                (None, None) => Vec3::<T>::ZERO
+            };
+
+            // A squad segment leaves its start with velocity b + 2 log(q^-1 s)
+            // per unit parameter, where b is the offset to the next key.
+            if let Some((prev, dt0)) = to_previous {
+                self.incoming[i] = q * Quat::<T>::exp((velocity * dt0 + prev) * -T::HALF);
+            }
+
+            if let Some((next, dt1)) = to_next {
+                self.outgoing[i] = q * Quat::<T>::exp((velocity * dt1 - next) * T::HALF);
+            }
+        }
+    }
+}
+
+/// Spherical quadrangle interpolation between q0 and q1 with controls a and b.
+pub fn squad<T>(q0: Quat<T>, a: Quat<T>, b: Quat<T>, q1: Quat<T>, t: T) -> Quat<T>
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T> + Neg<Output = T>
+{
+    let outer = Quat::<T>::slerp_arc(q0, q1, t);
+    let inner = Quat::<T>::slerp_arc(a, b, t);
+
+    Quat::<T>::slerp_arc(outer, inner, T::TWO * t * (T::ONE - t))
+}
@@ -0,0 +1,409 @@
+use std::{ops::{Add, Sub, Neg}, fmt::Display};
+
+use crate::glmath::*;
+use crate::glmath::roots::quadratic_roots;
+
+/// A half line starting at origin and extending along dir.
+/// The direction does not need to be normalized, every hit distance is
+/// measured in multiples of dir. That keeps the distances of a ray
+/// transformed into another space comparable to the original ray.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct Ray3<T: PartialOrd + Copy> {
+    pub origin: Vec3<T>,
+    pub dir: Vec3<T>
+}
+
+/// The result of a successful ray query.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct RayHit<T: PartialOrd + Copy> {
+    /// Distance along the ray to the hit point.
+    pub distance: T,
+    /// Unit length surface normal at the hit point.
+    pub normal: Vec3<T>,
+    /// Weights of the three vertices when hitting a triangle, zero otherwise.
+    pub barycentric: Vec3<T>
+}
+
+impl<T: PartialOrd + Copy + Display> Display for Ray3<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{} -> {}]", self.origin, self.dir)
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + Sub<Output = T> +
+    Mul<Output = T> + Div<Output = T> + DivAssign<T> +
+    Neg<Output = T>>
+    Ray3<T>
+{
+    pub fn new(origin: Vec3<T>, dir: Vec3<T>) -> Ray3<T> {
+        Ray3::<T> { origin, dir }
+    }
+
+    /// Returns the point at the given distance along the ray.
+    pub fn at(&self, distance: T) -> Vec3<T> {
+        self.origin + self.dir * distance
+    }
+
+    /// Transforms the ray by a matrix. Passing the inverse of a model
+    /// matrix brings a world space ray into the model's local space.
+    pub fn transformed(&self, mat: &Mat44<T>) -> Ray3<T> {
+        let origin = *mat * Vec4::<T>::new(self.origin.x, self.origin.y, self.origin.z, T::ONE);
+        let dir = *mat * Vec4::<T>::new(self.dir.x, self.dir.y, self.dir.z, T::ZERO);
+
+        Ray3::<T> {
+            origin: origin.xyz(),
+            dir: dir.xyz()
+        }
+    }
+
+    /// Slab test against a box. A ray starting inside the box hits the
+    /// face it leaves through.
+    pub fn intersect_aabb(&self, aabb: &Aabb3<T>) -> Option<RayHit<T>> {
+        let mut t_enter = -T::ONE;
+        let mut t_exit = T::ZERO;
+        let mut enter_axis = 0;
+        let mut exit_axis = 0;
+        let mut enter_sign = T::ZERO;
+        let mut exit_sign = T::ZERO;
+        let mut has_enter = false;
+        let mut has_exit = false;
+
+        for axis in 0..3 {
+            let origin = self.origin[axis];
+            let dir = self.dir[axis];
+
+            // A ray parallel to the slab either always or never lies within it.
+            if dir == T::ZERO {
+                if origin < aabb.min[axis] || origin > aabb.max[axis] {
+                    return None;
+                }
+
+                continue;
+            }
+
+            let near;
+            let far;
+            let sign;
+
+            if dir > T::ZERO {
+                near = (aabb.min[axis] - origin) / dir;
+                far = (aabb.max[axis] - origin) / dir;
+                sign = -T::ONE;
+            }
+            else {
+                near = (aabb.max[axis] - origin) / dir;
+                far = (aabb.min[axis] - origin) / dir;
+                sign = T::ONE;
+            }
+
+            if !has_enter || near > t_enter {
+                t_enter = near;
+                enter_axis = axis;
+                enter_sign = sign;
+                has_enter = true;
+            }
+
+            if !has_exit || far < t_exit {
+                t_exit = far;
+                exit_axis = axis;
+                exit_sign = -sign;
+                has_exit = true;
+            }
+        }
+
+        // A zero direction never leaves the box.
+        if !has_enter || t_enter > t_exit || t_exit < T::ZERO {
+            return None;
+        }
+
+        let (distance, axis, sign) = if t_enter >= T::ZERO {
+            (t_enter, enter_axis, enter_sign)
+        }
+        else {
+            (t_exit, exit_axis, exit_sign)
+        };
+
+        let mut normal = Vec3::<T>::ZERO;
+        normal[axis] = sign;
+
+        Some(RayHit::<T> {
+            distance,
+            normal,
+            barycentric: Vec3::<T>::ZERO
+        })
+    }
+
+    /// Returns the distances at which the ray enters and leaves a box.
+    /// The entry distance is negative if the ray starts inside the box.
+    pub fn intersect_aabb_range(&self, aabb: &Aabb3<T>) -> Option<(T, T)> {
+        let mut t_enter: Option<T> = None;
+        let mut t_exit: Option<T> = None;
+
+        for axis in 0..3 {
+            let origin = self.origin[axis];
+            let dir = self.dir[axis];
+
+            if dir == T::ZERO {
+                if origin < aabb.min[axis] || origin > aabb.max[axis] {
+                    return None;
+                }
+
+                continue;
+            }
+
+            let t0 = (aabb.min[axis] - origin) / dir;
+            let t1 = (aabb.max[axis] - origin) / dir;
+            let (near, far) = if t0 < t1 { (t0, t1) } else { (t1, t0) };
+
+            t_enter = Some(t_enter.map_or(near, |t| T::max(t, near)));
+            t_exit = Some(t_exit.map_or(far, |t| T::min(t, far)));
+        }
+
+        let (t_enter, t_exit) = (t_enter?, t_exit?);
+
+        if t_enter > t_exit || t_exit < T::ZERO {
+            return None;
+        }
+
+        Some((t_enter, t_exit))
+    }
+
+    /// Intersects a sphere. A ray starting inside the sphere hits the far side.
+    pub fn intersect_sphere(&self, center: Vec3<T>, radius: T) -> Option<RayHit<T>> {
+        let oc = self.origin - center;
+        let [t0, t1] = quadratic_roots(self.dir * self.dir, oc * self.dir * T::TWO, oc * oc - radius * radius)[..] else {
+            return None;
+        };
+
+        let distance = if t0 >= T::ZERO { t0 } else { t1 };
+
+        if distance < T::ZERO {
+            return None;
+        }
+
+        Some(RayHit::<T> {
+            distance,
+            normal: (self.at(distance) - center).get_normalized(),
+            barycentric: Vec3::<T>::ZERO
+        })
+    }
+
+    /// Intersects the plane of all points p with normal * p + d = 0.
+    /// The returned normal is the plane's normal.
+    pub fn intersect_plane(&self, normal: Vec3<T>, d: T) -> Option<RayHit<T>> {
+        let denom = normal * self.dir;
+
+        // Relative to the lengths, so that the test does not depend on scale.
+        if denom.abs() <= T::EPSILON * normal.length() * self.dir.length() {
+            return None;
+        }
+
+        let distance = -(normal * self.origin + d) / denom;
+
+        if distance < T::ZERO {
+            return None;
+        }
+
+        Some(RayHit::<T> {
+            distance,
+            normal: normal.get_normalized(),
+            barycentric: Vec3::<T>::ZERO
+        })
+    }
+
+    /// Intersects a flat disc facing along normal.
+    pub fn intersect_disc(&self, center: Vec3<T>, normal: Vec3<T>, radius: T) -> Option<RayHit<T>> {
+        let hit = self.intersect_plane(normal, -(normal * center))?;
+        let offset = self.at(hit.distance) - center;
+
+        if offset.length_sq() > radius * radius {
+            return None;
+        }
+
+        Some(hit)
+    }
+
+    /// Möller–Trumbore intersection against the triangle abc.
+    /// Both sides of the triangle are hit. The normal follows the
+    /// counter clockwise winding of abc.
+    pub fn intersect_triangle(&self, a: Vec3<T>, b: Vec3<T>, c: Vec3<T>) -> Option<RayHit<T>> {
+        let edge1 = b - a;
+        let edge2 = c - a;
+        let p = self.dir % edge2;
+        let det = edge1 * p;
+
+        // Relative to the lengths, so that small triangles are still hit.
+        if det.abs() <= T::EPSILON * self.dir.length() * edge1.length() * edge2.length() {
+            return None;
+        }
+
+        let det_inv = T::ONE / det;
+        let s = self.origin - a;
+        let u = s * p * det_inv;
+
+        if u < T::ZERO || u > T::ONE {
+            return None;
+        }
+
+        let q = s % edge1;
+        let v = self.dir * q * det_inv;
+
+        if v < T::ZERO || u + v > T::ONE {
+            return None;
+        }
+
+        let distance = edge2 * q * det_inv;
+
+        if distance < T::ZERO {
+            return None;
+        }
+
+        Some(RayHit::<T> {
+            distance,
+            normal: (edge1 % edge2).get_normalized(),
+            barycentric: Vec3::<T>::new(T::ONE - u - v, u, v)
+        })
+    }
+
+    /// Watertight ray triangle intersection by Woop, Benthin and Wald.
+    /// Rays through a shared edge or vertex hit at least one of the
+    /// triangles sharing it, which Möller–Trumbore does not guarantee.
+    pub fn intersect_triangle_watertight(&self, a: Vec3<T>, b: Vec3<T>, c: Vec3<T>) -> Option<RayHit<T>> {
+        // Permute the axes so the largest component of the direction is z.
+        let dir_abs = Vec3::<T>::new(self.dir.x.abs(), self.dir.y.abs(), self.dir.z.abs());
+
+        let kz = if dir_abs.x > dir_abs.y {
+            if dir_abs.x > dir_abs.z { 0 } else { 2 }
+        }
+        else if dir_abs.y > dir_abs.z { 1 } else { 2 };
+
+        let mut kx = (kz + 1) % 3;
+        let mut ky = (kx + 1) % 3;
+
+        // Keep the winding direction of the triangle.
+        if self.dir[kz] < T::ZERO {
+            std::mem::swap(&mut kx, &mut ky);
+        }
+
+        if self.dir[kz] == T::ZERO {
+            return None;
+        }
+
+        // Shear so the ray points along +z.
+        let sz = T::ONE / self.dir[kz];
+        let sx = self.dir[kx] * sz;
+        let sy = self.dir[ky] * sz;
+
+        let pa = a - self.origin;
+        let pb = b - self.origin;
+        let pc = c - self.origin;
+
+        let ax = pa[kx] - sx * pa[kz];
+        let ay = pa[ky] - sy * pa[kz];
+        let bx = pb[kx] - sx * pb[kz];
+        let by = pb[ky] - sy * pb[kz];
+        let cx = pc[kx] - sx * pc[kz];
+        let cy = pc[ky] - sy * pc[kz];
+
+        // Scaled barycentric coordinates.
+        let u = cx * by - cy * bx;
+        let v = ax * cy - ay * cx;
+        let w = bx * ay - by * ax;
+
+        if (u < T::ZERO || v < T::ZERO || w < T::ZERO) &&
+            (u > T::ZERO || v > T::ZERO || w > T::ZERO) {
+            return None;
+        }
+
+        let det = u + v + w;
+
+        if det == T::ZERO {
+            return None;
+        }
+
+        let az = sz * pa[kz];
+        let bz = sz * pb[kz];
+        let cz = sz * pc[kz];
+        let scaled_distance = u * az + v * bz + w * cz;
+
+        // The distance must have the same sign as the determinant.
+        if (det < T::ZERO && scaled_distance > T::ZERO) ||
+            (det > T::ZERO && scaled_distance < T::ZERO) {
+            return None;
+        }
+
+        let det_inv = T::ONE / det;
+
+        Some(RayHit::<T> {
+            distance: scaled_distance * det_inv,
+            normal: ((b - a) % (c - a)).get_normalized(),
+            barycentric: Vec3::<T>::new(u * det_inv, v * det_inv, w * det_inv)
+        })
+    }
+
+    /// Intersects a capsule made of the segment ab swept by radius.
+    /// A ray starting inside the capsule hits the surface it leaves through.
+    pub fn intersect_capsule(&self, a: Vec3<T>, b: Vec3<T>, radius: T) -> Option<RayHit<T>> {
+        let ba = b - a;
+        let oa = self.origin - a;
+        let baba = ba * ba;
+        let bard = ba * self.dir;
+        let baoa = ba * oa;
+        let rdrd = self.dir * self.dir;
+
+        let mut best: Option<T> = None;
+        let mut consider = |t: T| {
+            if t >= T::ZERO && best.is_none_or(|b| t < b) {
+                best = Some(t);
+            }
+        };
+
+        // The cylinder around the segment, limited to the segment's span.
+        let qa = baba * rdrd - bard * bard;
+        let qb = (baba * (self.dir * oa) - baoa * bard) * T::TWO;
+        let qc = baba * (oa * oa) - baoa * baoa - radius * radius * baba;
+
+        if qa.abs() > T::EPSILON * baba * rdrd {
+            if let [t0, t1] = quadratic_roots(qa, qb, qc)[..] {
+                for t in [t0, t1] {
+                    let y = baoa + t * bard;
+
+                    if y > T::ZERO && y < baba {
+                        consider(t);
+                    }
+                }
+            }
+        }
+
+        // The two end caps.
+        for (center, is_start) in [(a, true), (b, false)] {
+            let oc = self.origin - center;
+
+            if let [t0, t1] = quadratic_roots(rdrd, oc * self.dir * T::TWO, oc * oc - radius * radius)[..] {
+                for t in [t0, t1] {
+                    let y = baoa + t * bard;
+
+                    if (is_start && y <= T::ZERO) || (!is_start && y >= baba) {
+                        consider(t);
+                    }
+                }
+            }
+        }
+
+        let distance = best?;
+        let pa = self.at(distance) - a;
+        let h = if baba > T::ZERO {
+            T::min(T::max((pa * ba) / baba, T::ZERO), T::ONE)
+        }
+        else {
+            T::ZERO
+        };
+
+        Some(RayHit::<T> {
+            distance,
+            normal: (pa - ba * h).get_normalized(),
+            barycentric: Vec3::<T>::ZERO
+        })
+    }
+}
@@ -0,0 +1,613 @@
+use std::ops::{Add, AddAssign, Sub, Neg};
+
+use crate::glmath::*;
+
+/// Depth of the bisection used to separate roots, enough to reach machine
+/// precision on any reasonable interval.
+const MAX_BISECTIONS: usize = 96;
+
+/// A polynomial with real coefficients, lowest degree first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polynomial<T: PartialOrd + Copy> {
+    pub coefficients: Vec<T>
+}
+
+/// Real roots of a * x^2 + b * x + c = 0 in ascending order, solving the
+/// linear equation when a is zero. A double root is returned twice.
+pub fn quadratic_roots<T>(a: T, b: T, c: T) -> Vec<T>
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T> + Neg<Output = T>
+{
+    if a == T::ZERO {
+        return if b == T::ZERO { vec![] } else { vec![-c / b] };
+    }
+
+    let four_ac = T::TWO * T::TWO * a * c;
+    let mut discriminant = b * b - four_ac;
+
+    // Rounding can push a double root's discriminant just below zero.
+    if discriminant < T::ZERO {
+        if discriminant < -T::EPSILON * T::TWO * (b * b + four_ac.abs()) {
+            return vec![];
+        }
+        discriminant = T::ZERO;
+    }
+
+    // Avoids cancellation between b and the root of the discriminant.
+    let root = discriminant.sqrt();
+    let q = if b < T::ZERO { (root - b) * T::HALF } else { -(b + root) * T::HALF };
+
+    if q == T::ZERO {
+        return vec![T::ZERO, T::ZERO];
+    }
+
+    sorted(vec![q / a, c / q])
+}
+
+/// Real roots of a * x^3 + b * x^2 + c * x + d = 0 in ascending order, by
+/// Cardano's formula for one root and the trigonometric form for three.
+/// Repeated roots are returned once per multiplicity.
+pub fn cubic_roots<T>(a: T, b: T, c: T, d: T) -> Vec<T>
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T> + Neg<Output = T>
+{
+    if a == T::ZERO {
+        return quadratic_roots(b, c, d);
+    }
+
+    let three = T::TWO + T::ONE;
+    let (b, c, d) = (b / a, c / a, d / a);
+
+    // x = y - b / 3 gives y^3 + p * y + q = 0.
+    let shift = b / three;
+    let p = c - b * shift;
+    let q = T::TWO * shift * shift * shift - c * shift + d;
+
+    let half_q = q * T::HALF;
+    let third_p = p / three;
+    let cube = third_p * third_p * third_p;
+    let discriminant = half_q * half_q + cube;
+
+    // Rounding in p and q scales with the terms they were summed from, and
+    // a discriminant within that noise of zero is treated as a double root.
+    let q_scale = T::max(T::TWO * (shift * shift * shift).abs(), T::max((c * shift).abs(), d.abs()));
+    let p_scale = T::max(c.abs(), (b * shift).abs());
+    let tolerance = T::EPSILON * T::TWO * T::TWO * T::TWO * (half_q.abs() * q_scale + third_p * third_p * p_scale);
+
+    let depressed = if discriminant > tolerance {
+        // One real root.
+        let magnitude = cube_root(half_q.abs() + discriminant.sqrt());
+        let u = if half_q > T::ZERO { -magnitude } else { magnitude };
+        vec![if u == T::ZERO { T::ZERO } else { u - third_p / u }]
+    }
+    else if p >= T::ZERO {
+        // A triple root.
+        vec![T::ZERO; 3]
+    }
+    else {
+        let radius = T::TWO * (-third_p).sqrt();
+        let cosine = T::max(-T::ONE, T::min(T::ONE, half_q * three / p * (-three / p).sqrt()));
+        let angle = cosine.acos() / three;
+        let step = T::TWO * T::PI / three;
+
+        vec![radius * angle.cos(), radius * (angle - step).cos(), radius * (angle - step - step).cos()]
+    };
+
+    let coefficients = [d, c, b, T::ONE];
+    sorted(depressed.into_iter().map(|y| polish(&coefficients, y - shift)).collect())
+}
+
+/// Real roots of a * x^4 + b * x^3 + c * x^2 + d * x + e = 0 in ascending
+/// order, by Ferrari's method. Repeated roots are returned once per
+/// multiplicity.
+pub fn quartic_roots<T>(a: T, b: T, c: T, d: T, e: T) -> Vec<T>
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T> + Neg<Output = T>
+{
+    if a == T::ZERO {
+        return cubic_roots(b, c, d, e);
+    }
+
+    let (b, c, d, e) = (b / a, c / a, d / a, e / a);
+
+    // x = y - b / 4 gives y^4 + p * y^2 + q * y + r = 0.
+    let shift = b * T::QUARTER;
+    let shift2 = shift * shift;
+    let p = c - (T::TWO + T::ONE) * T::TWO * shift2;
+    let q = d - T::TWO * c * shift + T::TWO * T::TWO * T::TWO * shift2 * shift;
+    let r = e - d * shift + c * shift2 - (T::TWO + T::ONE) * shift2 * shift2;
+
+    let scale = T::max(T::ONE, T::max(p.abs(), T::max(r.abs().sqrt(), shift2)));
+    let mut depressed = Vec::new();
+
+    if q.abs() <= T::EPSILON * scale * scale.sqrt() {
+        // Biquadratic, a quadratic in y^2.
+        for z in quadratic_roots(T::ONE, p, r) {
+            if z >= -T::EPSILON * scale {
+                let y = T::max(z, T::ZERO).sqrt();
+                depressed.push(-y);
+                depressed.push(y);
+            }
+        }
+    }
+    else {
+        // The largest root of the resolvent cubic is positive when q is not
+        // zero and splits the quartic into two quadratics.
+        let eight = T::TWO * T::TWO * T::TWO;
+        let resolvent = cubic_roots(eight, eight * p, T::TWO * p * p - eight * r, -q * q);
+        let m = resolvent.into_iter().fold(T::ZERO, T::max);
+
+        if m > T::ZERO {
+            let s = (T::TWO * m).sqrt();
+            let offset = q / (T::TWO * s);
+            depressed.extend(quadratic_roots(T::ONE, s, p * T::HALF + m - offset));
+            depressed.extend(quadratic_roots(T::ONE, -s, p * T::HALF + m + offset));
+        }
+    }
+
+    let coefficients = [e, d, c, b, T::ONE];
+    sorted(depressed.into_iter().map(|y| polish(&coefficients, y - shift)).collect())
+}
+
+/// Newton's method from x0, where f returns the value and slope. Returns
+/// None if the slope vanishes or the step is not below tolerance after
+/// max_iterations.
+pub fn newton<T, F>(mut f: F, x0: T, tolerance: T, max_iterations: usize) -> Option<T>
+    where T: PartialOrd + Copy + Vectorable<T> + Sub<Output = T> + Div<Output = T>,
+        F: FnMut(T) -> (T, T)
+{
+    let mut x = x0;
+
+    for _ in 0..max_iterations {
+        let (value, slope) = f(x);
+
+        if value == T::ZERO {
+            return Some(x);
+        }
+        if slope == T::ZERO {
+            return None;
+        }
+
+        let step = value / slope;
+        x = x - step;
+
+        if step.abs() <= tolerance {
+            return Some(x);
+        }
+    }
+
+    None
+}
+
+/// Bisection on [a, b], where f(a) and f(b) must differ in sign. Returns
+/// None if they do not or the bracket is not below tolerance after
+/// max_iterations.
+pub fn bisection<T, F>(mut f: F, a: T, b: T, tolerance: T, max_iterations: usize) -> Option<T>
+    where T: PartialOrd + Copy + Vectorable<T> + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+        F: FnMut(T) -> T
+{
+    let (mut a, mut b) = (a, b);
+    let (fa, fb) = (f(a), f(b));
+
+    if fa == T::ZERO {
+        return Some(a);
+    }
+    if fb == T::ZERO {
+        return Some(b);
+    }
+    if (fa > T::ZERO) == (fb > T::ZERO) {
+        return None;
+    }
+
+    let a_positive = fa > T::ZERO;
+
+    for _ in 0..max_iterations {
+        let middle = (a + b) * T::HALF;
+
+        if (b - a).abs() <= tolerance {
+            return Some(middle);
+        }
+
+        let value = f(middle);
+        if value == T::ZERO {
+            return Some(middle);
+        }
+
+        if (value > T::ZERO) == a_positive {
+            a = middle;
+        }
+        else {
+            b = middle;
+        }
+    }
+
+    None
+}
+
+/// Brent's method on [a, b], combining bisection with secant and inverse
+/// quadratic steps. f(a) and f(b) must differ in sign. Returns None if
+/// they do not or it has not converged to tolerance after max_iterations.
+pub fn brent<T, F>(mut f: F, a: T, b: T, tolerance: T, max_iterations: usize) -> Option<T>
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T> + Neg<Output = T>,
+        F: FnMut(T) -> T
+{
+    let (mut a, mut b) = (a, b);
+    let (mut fa, mut fb) = (f(a), f(b));
+
+    if fa == T::ZERO {
+        return Some(a);
+    }
+    if fb == T::ZERO {
+        return Some(b);
+    }
+    if (fa > T::ZERO) == (fb > T::ZERO) {
+        return None;
+    }
+
+    let three = T::TWO + T::ONE;
+    let (mut c, mut fc) = (b, fb);
+    let mut d = b - a;
+    let mut e = d;
+
+    for _ in 0..max_iterations {
+        // Keep the root between b and c, with b the best estimate.
+        if (fb > T::ZERO) == (fc > T::ZERO) {
+            (c, fc) = (a, fa);
+            d = b - a;
+            e = d;
+        }
+        if fc.abs() < fb.abs() {
+            (a, b, c) = (b, c, b);
+            (fa, fb, fc) = (fb, fc, fb);
+        }
+
+        let tolerance1 = T::TWO * T::EPSILON * b.abs() + tolerance * T::HALF;
+        let middle = (c - b) * T::HALF;
+
+        if middle.abs() <= tolerance1 || fb == T::ZERO {
+            return Some(b);
+        }
+
+        if e.abs() >= tolerance1 && fa.abs() > fb.abs() {
+            // Secant when there are two points, inverse quadratic otherwise.
+            let s = fb / fa;
+            let (mut p, mut q) = if a == c {
+                (T::TWO * middle * s, T::ONE - s)
+            }
+            else {
+                let (q, r) = (fa / fc, fb / fc);
+                (s * (T::TWO * middle * q * (q - r) - (b - a) * (r - T::ONE)), (q - T::ONE) * (r - T::ONE) * (s - T::ONE))
+            };
+
+            if p > T::ZERO {
+                q = -q;
+            }
+            p = p.abs();
+
+            let limit = T::min(three * middle * q - (tolerance1 * q).abs(), (e * q).abs());
+            if T::TWO * p < limit {
+                e = d;
+                d = p / q;
+            }
+            else {
+                d = middle;
+                e = d;
+            }
+        }
+        else {
+            d = middle;
+            e = d;
+        }
+
+        (a, fa) = (b, fb);
+        b += if d.abs() > tolerance1 { d } else if middle > T::ZERO { tolerance1 } else { -tolerance1 };
+        fb = f(b);
+    }
+
+    None
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    Polynomial<T>
+{
+    /// Constructs a polynomial from coefficients, lowest degree first.
+    /// Trailing zeros are dropped.
+    pub fn new(coefficients: Vec<T>) -> Polynomial<T> {
+        let mut result = Polynomial::<T> { coefficients };
+        while result.coefficients.last() == Some(&T::ZERO) {
+            result.coefficients.pop();
+        }
+        result
+    }
+
+    /// The monic polynomial with the given roots.
+    pub fn from_roots(roots: &[T]) -> Polynomial<T> {
+        roots.iter().fold(Polynomial::<T>::new(vec![T::ONE]), |product, root| &product * &Polynomial::<T>::new(vec![-*root, T::ONE]))
+    }
+
+    /// The degree, with zero for constants including the zero polynomial.
+    pub fn degree(&self) -> usize {
+        self.coefficients.len().saturating_sub(1)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.coefficients.is_empty()
+    }
+
+    /// Evaluates by Horner's rule.
+    pub fn evaluate(&self, x: T) -> T {
+        self.coefficients.iter().rev().fold(T::ZERO, |sum, c| sum * x + *c)
+    }
+
+    pub fn derivative(&self) -> Polynomial<T> {
+        let mut power = T::ZERO;
+        Polynomial::<T>::new(self.coefficients.iter().skip(1).map(|c| {
+            power += T::ONE;
+            *c * power
+        }).collect())
+    }
+
+    pub fn scaled(&self, factor: T) -> Polynomial<T> {
+        Polynomial::<T>::new(self.coefficients.iter().map(|c| *c * factor).collect())
+    }
+
+    /// Polynomial long division, returning the quotient and remainder.
+    /// Remainder coefficients that are rounding noise next to self are
+    /// dropped so that exact divisions come out exact.
+    pub fn div_rem(&self, divisor: &Polynomial<T>) -> (Polynomial<T>, Polynomial<T>) {
+        assert!(!divisor.is_zero(), "division by the zero polynomial");
+
+        if self.coefficients.len() < divisor.coefficients.len() {
+            return (Polynomial::<T>::new(vec![]), self.clone());
+        }
+
+        let lead = *divisor.coefficients.last().unwrap();
+        let shift = self.coefficients.len() - divisor.coefficients.len();
+        let mut remainder = self.coefficients.clone();
+        let mut quotient = vec![T::ZERO; shift + 1];
+
+        for k in (0..=shift).rev() {
+            let factor = remainder[k + divisor.degree()] / lead;
+            quotient[k] = factor;
+
+            for (i, c) in divisor.coefficients.iter().enumerate() {
+                remainder[k + i] = remainder[k + i] - factor * *c;
+            }
+        }
+
+        let scale = self.coefficients.iter().fold(T::ZERO, |max, c| T::max(max, c.abs()));
+        let tolerance = scale * T::EPSILON * T::from_f64(self.coefficients.len() as f64 * 8.0);
+        remainder.truncate(divisor.degree());
+        for c in remainder.iter_mut() {
+            if c.abs() <= tolerance {
+                *c = T::ZERO;
+            }
+        }
+
+        (Polynomial::<T>::new(quotient), Polynomial::<T>::new(remainder))
+    }
+
+    /// The Sturm sequence p, p', -rem(p, p'), ... used to count real roots.
+    pub fn sturm_sequence(&self) -> Vec<Polynomial<T>> {
+        let mut sequence = vec![self.clone()];
+        let mut next = self.derivative();
+
+        while !next.is_zero() {
+            let (_, remainder) = sequence.last().unwrap().div_rem(&next);
+            sequence.push(next);
+            next = remainder.scaled(-T::ONE);
+        }
+
+        sequence
+    }
+
+    /// The number of distinct real roots in (a, b].
+    pub fn count_roots(&self, a: T, b: T) -> usize {
+        let sequence = self.sturm_sequence();
+        sign_changes(&sequence, a).saturating_sub(sign_changes(&sequence, b))
+    }
+
+    /// Intervals (a, b] each holding exactly one distinct real root, found
+    /// by bisecting with Sturm's theorem. Roots closer together than
+    /// rounding allows to separate share an interval.
+    pub fn isolate_roots(&self, a: T, b: T) -> Vec<(T, T)> {
+        let sequence = self.sturm_sequence();
+        let mut intervals = Vec::new();
+
+        if self.degree() == 0 {
+            return intervals;
+        }
+
+        let mut stack = vec![(a, b, sign_changes(&sequence, a), sign_changes(&sequence, b), 0)];
+        while let Some((a, b, changes_a, changes_b, depth)) = stack.pop() {
+            let count = changes_a.saturating_sub(changes_b);
+
+            if count == 0 {
+                continue;
+            }
+
+            let middle = (a + b) * T::HALF;
+            if count == 1 || depth == MAX_BISECTIONS || middle <= a || middle >= b {
+                intervals.push((a, b));
+                continue;
+            }
+
+            let changes_middle = sign_changes(&sequence, middle);
+            stack.push((middle, b, changes_middle, changes_b, depth + 1));
+            stack.push((a, middle, changes_a, changes_middle, depth + 1));
+        }
+
+        intervals
+    }
+
+    /// The distinct real roots in (a, b] in ascending order. Roots where the
+    /// sign changes are refined by Brent's method, even multiplicity roots
+    /// by further Sturm bisection.
+    pub fn roots_between(&self, a: T, b: T) -> Vec<T> {
+        let sequence = self.sturm_sequence();
+
+        self.isolate_roots(a, b).into_iter().map(|(mut low, mut high)| {
+            if self.evaluate(high) == T::ZERO {
+                return high;
+            }
+
+            let tolerance = T::EPSILON * T::max(T::ONE, T::max(low.abs(), high.abs()));
+            let (f_low, f_high) = (self.evaluate(low), self.evaluate(high));
+
+            if (f_low > T::ZERO) != (f_high > T::ZERO) {
+                if let Some(root) = brent(|x| self.evaluate(x), low, high, tolerance, MAX_BISECTIONS) {
+                    return root;
+                }
+            }
+
+            for _ in 0..MAX_BISECTIONS {
+                let middle = (low + high) * T::HALF;
+                if high - low <= tolerance || middle <= low || middle >= high {
+                    break;
+                }
+
+                if sign_changes(&sequence, low) > sign_changes(&sequence, middle) {
+                    high = middle;
+                }
+                else {
+                    low = middle;
+                }
+            }
+
+            (low + high) * T::HALF
+        }).collect()
+    }
+
+    /// All distinct real roots in ascending order, searched within Cauchy's
+    /// bound on their magnitude.
+    pub fn roots(&self) -> Vec<T> {
+        let Some(lead) = self.coefficients.last() else { return vec![] };
+
+        let bound = T::ONE + self.coefficients.iter().rev().skip(1).fold(T::ZERO, |max, c| T::max(max, (*c / *lead).abs()));
+        self.roots_between(-bound, bound)
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> + Add<Output = T>> Add for &Polynomial<T> {
+    type Output = Polynomial<T>;
+
+    fn add(self, rhs: &Polynomial<T>) -> Self::Output {
+        let length = usize::max(self.coefficients.len(), rhs.coefficients.len());
+        let get = |p: &Polynomial<T>, i: usize| p.coefficients.get(i).copied().unwrap_or(T::ZERO);
+
+        let mut coefficients: Vec<T> = (0..length).map(|i| get(self, i) + get(rhs, i)).collect();
+        while coefficients.last() == Some(&T::ZERO) {
+            coefficients.pop();
+        }
+        Polynomial::<T> { coefficients }
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> + Sub<Output = T>> Sub for &Polynomial<T> {
+    type Output = Polynomial<T>;
+
+    fn sub(self, rhs: &Polynomial<T>) -> Self::Output {
+        let length = usize::max(self.coefficients.len(), rhs.coefficients.len());
+        let get = |p: &Polynomial<T>, i: usize| p.coefficients.get(i).copied().unwrap_or(T::ZERO);
+
+        let mut coefficients: Vec<T> = (0..length).map(|i| get(self, i) - get(rhs, i)).collect();
+        while coefficients.last() == Some(&T::ZERO) {
+            coefficients.pop();
+        }
+        Polynomial::<T> { coefficients }
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> + Add<Output = T> + Mul<Output = T>> Mul for &Polynomial<T> {
+    type Output = Polynomial<T>;
+
+    fn mul(self, rhs: &Polynomial<T>) -> Self::Output {
+        if self.coefficients.is_empty() || rhs.coefficients.is_empty() {
+            return Polynomial::<T> { coefficients: vec![] };
+        }
+
+        let mut coefficients = vec![T::ZERO; self.coefficients.len() + rhs.coefficients.len() - 1];
+        for (i, a) in self.coefficients.iter().enumerate() {
+            for (j, b) in rhs.coefficients.iter().enumerate() {
+                coefficients[i + j] = coefficients[i + j] + *a * *b;
+            }
+        }
+        Polynomial::<T> { coefficients }
+    }
+}
+
+/// Sign changes along a Sturm sequence at x, skipping zeros.
+fn sign_changes<T>(sequence: &[Polynomial<T>], x: T) -> usize
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T> + Neg<Output = T>
+{
+    let mut changes = 0;
+    let mut last = None;
+
+    for p in sequence {
+        let value = p.evaluate(x);
+        if value == T::ZERO {
+            continue;
+        }
+
+        let positive = value > T::ZERO;
+        if last.is_some_and(|last| last != positive) {
+            changes += 1;
+        }
+        last = Some(positive);
+    }
+
+    changes
+}
+
+/// The real cube root, seeded in f64 and refined by one Newton step so
+/// that it is exact to the precision of T.
+fn cube_root<T>(x: T) -> T
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>
+{
+    if x == T::ZERO {
+        return x;
+    }
+
+    let y = T::from_f64(x.to_f64().cbrt());
+    y - (y * y * y - x) / ((T::TWO + T::ONE) * y * y)
+}
+
+/// A couple of Newton steps on a polynomial given lowest degree first,
+/// each kept only if it reduces the residual.
+fn polish<T>(coefficients: &[T], mut x: T) -> T
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>
+{
+    for _ in 0..2 {
+        let (value, slope) = coefficients.iter().rev().fold((T::ZERO, T::ZERO), |(value, slope), c| (value * x + *c, slope * x + value));
+
+        if slope == T::ZERO {
+            break;
+        }
+
+        let next = x - value / slope;
+        let next_value = coefficients.iter().rev().fold(T::ZERO, |sum, c| sum * next + *c);
+
+        if next_value.abs() >= value.abs() {
+            break;
+        }
+        x = next;
+    }
+
+    x
+}
+
+fn sorted<T: PartialOrd + Copy>(mut values: Vec<T>) -> Vec<T> {
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    values
+}
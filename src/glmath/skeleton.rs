@@ -0,0 +1,159 @@
+use std::ops::{Add, AddAssign, Sub, SubAssign, Neg};
+
+use crate::glmath::*;
+use crate::glmath::animation::Animatable;
+use crate::glmath::transform::Transform;
+
+/// A joint hierarchy with its bind pose. Joints are ordered so every
+/// parent comes before its children.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Skeleton<T: PartialOrd + Copy> {
+    /// The parent of each joint, None for roots.
+    pub parents: Vec<Option<usize>>,
+    /// The model space matrix of each joint in the bind pose.
+    pub bind_poses: Vec<Mat44<T>>
+}
+
+/// The local transform of every joint of a skeleton relative to its parent.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Pose<T: PartialOrd + Copy> {
+    pub joints: Vec<Transform<T>>
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + SubAssign<T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    Skeleton<T>
+{
+    /// Creates a skeleton. Returns None if the lists differ in length or a
+    /// joint's parent does not come before it.
+    pub fn new(parents: &[Option<usize>], bind_poses: &[Mat44<T>]) -> Option<Skeleton<T>> {
+        if parents.len() != bind_poses.len() {
+            return None;
+        }
+
+        if parents.iter().enumerate().any(|(joint, parent)| parent.is_some_and(|p| p >= joint)) {
+            return None;
+        }
+
+        Some(Skeleton::<T> { parents: parents.to_vec(), bind_poses: bind_poses.to_vec() })
+    }
+
+    pub fn joint_count(&self) -> usize {
+        self.parents.len()
+    }
+
+    /// Matrices taking model space into each joint's bind space.
+    pub fn inverse_bind_matrices(&self) -> Vec<Mat44<T>> {
+        self.bind_poses.iter().map(|bind| bind.get_inverted()).collect()
+    }
+
+    /// The bind pose as local transforms.
+    pub fn bind_pose(&self) -> Pose<T> {
+        let joints = (0..self.joint_count()).map(|joint| {
+            let local = match self.parents[joint] {
+                Some(parent) => self.bind_poses[parent].get_inverted() * self.bind_poses[joint],
+                None => self.bind_poses[joint]
+            };
+
+            Transform::<T>::from_matrix(&local)
+        }).collect();
+
+        Pose::<T> { joints }
+    }
+
+    /// The model space matrix of every joint in the pose. Panics if the
+    /// pose does not have one transform per joint.
+    pub fn model_matrices(&self, pose: &Pose<T>) -> Vec<Mat44<T>> {
+        assert_eq!(pose.joints.len(), self.joint_count());
+        let mut model: Vec<Mat44<T>> = Vec::with_capacity(self.joint_count());
+
+        for joint in 0..self.joint_count() {
+            let local = pose.joints[joint].to_matrix();
+
+            model.push(match self.parents[joint] {
+                Some(parent) => model[parent] * local,
+                None => local
+            });
+        }
+
+        model
+    }
+
+    /// The skinning matrices of the pose, taking bind pose vertices to the
+    /// posed model space. Panics if the pose does not have one transform
+    /// per joint.
+    pub fn skinning_palette(&self, pose: &Pose<T>) -> Vec<Mat44<T>> {
+        self.model_matrices(pose).iter()
+            .zip(self.inverse_bind_matrices())
+            .map(|(model, inverse_bind)| *model * inverse_bind)
+            .collect()
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    Pose<T>
+{
+    pub fn new(joints: Vec<Transform<T>>) -> Pose<T> {
+        Pose::<T> { joints }
+    }
+
+    pub fn identity(joint_count: usize) -> Pose<T> {
+        Pose::<T> { joints: vec![Transform::<T>::identity(); joint_count] }
+    }
+
+    /// Blends towards another pose, replacing it by weight. The mask scales
+    /// the weight of each joint, so unmasked joints keep this pose.
+    pub fn blend(&self, other: &Pose<T>, weight: T, mask: Option<&[T]>) -> Pose<T> {
+        let joints = self.joints.iter().zip(&other.joints).enumerate().map(|(joint, (a, b))| {
+            Transform::<T>::lerp(a, b, joint_weight(weight, mask, joint))
+        }).collect();
+
+        Pose::<T> { joints }
+    }
+
+    /// The difference taking a reference pose to this one, for use with add.
+    pub fn difference(&self, reference: &Pose<T>) -> Pose<T> {
+        let joints = self.joints.iter().zip(&reference.joints).map(|(target, base)| {
+            Transform::<T>::new(
+                target.translation - base.translation,
+                target.rotation * base.rotation.conjugate(),
+                Vec3::<T>::new(
+                    ratio(target.scale.x, base.scale.x),
+                    ratio(target.scale.y, base.scale.y),
+                    ratio(target.scale.z, base.scale.z)))
+        }).collect();
+
+        Pose::<T> { joints }
+    }
+
+    /// Layers an additive pose made by difference on top of this pose.
+    /// The mask scales the weight of each joint.
+    pub fn add(&self, additive: &Pose<T>, weight: T, mask: Option<&[T]>) -> Pose<T> {
+        let joints = self.joints.iter().zip(&additive.joints).enumerate().map(|(joint, (base, delta))| {
+            let weight = joint_weight(weight, mask, joint);
+            let rotation = Quat::<T>::lerp(&Quat::<T>::IDENTITY, &delta.rotation, weight);
+            let scale = Vec3::<T>::lerp(&Vec3::<T>::new(T::ONE, T::ONE, T::ONE), &delta.scale, weight);
+
+            Transform::<T>::new(
+                base.translation + delta.translation * weight,
+                (rotation * base.rotation).get_normalized(),
+                Vec3::<T>::new(base.scale.x * scale.x, base.scale.y * scale.y, base.scale.z * scale.z))
+        }).collect();
+
+        Pose::<T> { joints }
+    }
+}
+
+fn joint_weight<T: Copy + Mul<Output = T>>(weight: T, mask: Option<&[T]>, joint: usize) -> T {
+    match mask {
+        Some(mask) => weight * mask[joint],
+        None => weight
+    }
+}
+
+fn ratio<T: PartialOrd + Copy + Vectorable<T> + Div<Output = T>>(a: T, b: T) -> T {
+    if b == T::ZERO { T::ONE } else { a / b }
+}
@@ -0,0 +1,786 @@
+use std::ops::{Add, AddAssign, Sub, Neg};
+
+use crate::glmath::*;
+use crate::glmath::collision::Contact;
+use crate::glmath::closest::segment_segment;
+use crate::glmath::physics::{RigidBody, integrate_orientation};
+
+/// The collision shape of a body, centered on its position and turned by its
+/// orientation.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Shape<T: PartialOrd + Copy> {
+    Sphere { radius: T },
+    Cuboid { half_extents: Vec3<T> }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Collider<T: PartialOrd + Copy> {
+    pub shape: Shape<T>,
+    pub friction: T,
+    pub restitution: T
+}
+
+/// A point of contact between two bodies.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct ContactPoint<T: PartialOrd + Copy> {
+    pub position: Vec3<T>,
+    pub depth: T,
+    /// Accumulated impulse along the normal.
+    pub normal_impulse: T,
+    /// Accumulated friction impulse along each tangent.
+    pub tangent_impulses: [T; 2],
+    /// The position in the first body's frame, to match points between steps.
+    local_a: Vec3<T>,
+    r_a: Vec3<T>,
+    r_b: Vec3<T>,
+    normal_mass: T,
+    tangent_masses: [T; 2],
+    target_velocity: T
+}
+
+/// The contact points between two bodies, sharing one normal.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Manifold<T: PartialOrd + Copy> {
+    pub a: usize,
+    pub b: usize,
+    /// Unit direction pointing from body a to body b.
+    pub normal: Vec3<T>,
+    pub tangents: [Vec3<T>; 2],
+    pub points: Vec<ContactPoint<T>>,
+    pub friction: T,
+    pub restitution: T
+}
+
+/// What a joint constrains. Axes are given in each body's local frame.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum JointKind<T: PartialOrd + Copy> {
+    /// The anchors are held together.
+    Ball,
+    /// The anchors are held length apart.
+    Distance { length: T },
+    /// The anchors are held together and the axes kept aligned, leaving
+    /// rotation about them free.
+    Hinge { axis_a: Vec3<T>, axis_b: Vec3<T> }
+}
+
+/// A constraint between two bodies. Anchors are in each body's local frame.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Joint<T: PartialOrd + Copy> {
+    pub a: usize,
+    pub b: usize,
+    pub anchor_a: Vec3<T>,
+    pub anchor_b: Vec3<T>,
+    pub kind: JointKind<T>,
+    /// Accumulated impulses, kept for warm starting.
+    linear_impulse: Vec3<T>,
+    axial_impulse: T,
+    angular_impulses: [T; 2],
+    r_a: Vec3<T>,
+    r_b: Vec3<T>,
+    linear_mass: Mat33<T>,
+    linear_bias: Vec3<T>,
+    direction: Vec3<T>,
+    axial_mass: T,
+    axial_bias: T,
+    angular_axes: [Vec3<T>; 2],
+    angular_masses: [T; 2],
+    angular_biases: [T; 2]
+}
+
+/// A set of bodies stepped by a sequential impulse solver. Contacts and
+/// joints are solved as velocity constraints with Baumgarte position
+/// correction, in a fixed order so every run gives the same result.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct World<T: PartialOrd + Copy> {
+    pub bodies: Vec<RigidBody<T>>,
+    pub colliders: Vec<Option<Collider<T>>>,
+    pub joints: Vec<Joint<T>>,
+    pub gravity: Vec3<T>,
+    /// Solver iterations per step.
+    pub iterations: usize,
+    /// Fraction of the position error corrected each step.
+    pub baumgarte: T,
+    /// Penetration allowed without correction, which keeps resting contacts
+    /// touching.
+    pub slop: T,
+    /// Approach speeds below this do not bounce.
+    pub restitution_threshold: T,
+    /// Contact points closer than this to one from the previous step start
+    /// from its impulses.
+    pub match_distance: T,
+    pub warm_starting: bool,
+    manifolds: Vec<Manifold<T>>
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T>> Collider<T> {
+    pub fn sphere(radius: T) -> Collider<T> {
+        Collider::<T> { shape: Shape::Sphere { radius }, friction: T::HALF, restitution: T::ZERO }
+    }
+
+    pub fn cuboid(half_extents: Vec3<T>) -> Collider<T> {
+        Collider::<T> { shape: Shape::Cuboid { half_extents }, friction: T::HALF, restitution: T::ZERO }
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    Joint<T>
+{
+    pub fn new(a: usize, b: usize, anchor_a: Vec3<T>, anchor_b: Vec3<T>, kind: JointKind<T>) -> Joint<T> {
+        Joint::<T> {
+            a,
+            b,
+            anchor_a,
+            anchor_b,
+            kind,
+            linear_impulse: Vec3::<T>::ZERO,
+            axial_impulse: T::ZERO,
+            angular_impulses: [T::ZERO; 2],
+            r_a: Vec3::<T>::ZERO,
+            r_b: Vec3::<T>::ZERO,
+            linear_mass: Mat33::<T>::ZERO,
+            linear_bias: Vec3::<T>::ZERO,
+            direction: Vec3::<T>::ZERO,
+            axial_mass: T::ZERO,
+            axial_bias: T::ZERO,
+            angular_axes: [Vec3::<T>::ZERO; 2],
+            angular_masses: [T::ZERO; 2],
+            angular_biases: [T::ZERO; 2]
+        }
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    World<T>
+{
+    pub fn new(gravity: Vec3<T>) -> World<T> {
+        World::<T> {
+            bodies: Vec::new(),
+            colliders: Vec::new(),
+            joints: Vec::new(),
+            gravity,
+            iterations: 10,
+            baumgarte: T::from_f64(0.2),
+            slop: T::from_f64(0.005),
+            restitution_threshold: T::ONE,
+            match_distance: T::from_f64(0.05),
+            warm_starting: true,
+            manifolds: Vec::new()
+        }
+    }
+
+    /// Adds a body, returning its index.
+    pub fn add_body(&mut self, body: RigidBody<T>, collider: Option<Collider<T>>) -> usize {
+        self.bodies.push(body);
+        self.colliders.push(collider);
+        self.bodies.len() - 1
+    }
+
+    /// Joins two bodies at a world space point.
+    pub fn add_ball_joint(&mut self, a: usize, b: usize, anchor: Vec3<T>) -> usize {
+        let (anchor_a, anchor_b) = (self.local_point(a, anchor), self.local_point(b, anchor));
+        self.add_joint(Joint::<T>::new(a, b, anchor_a, anchor_b, JointKind::Ball))
+    }
+
+    /// Keeps two world space points on the bodies at their current distance.
+    pub fn add_distance_joint(&mut self, a: usize, b: usize, anchor_a: Vec3<T>, anchor_b: Vec3<T>) -> usize {
+        let length = (anchor_b - anchor_a).length();
+        let (local_a, local_b) = (self.local_point(a, anchor_a), self.local_point(b, anchor_b));
+        self.add_joint(Joint::<T>::new(a, b, local_a, local_b, JointKind::Distance { length }))
+    }
+
+    /// Joins two bodies at a world space point, letting them turn only about
+    /// the world space axis.
+    pub fn add_hinge_joint(&mut self, a: usize, b: usize, anchor: Vec3<T>, axis: Vec3<T>) -> usize {
+        let axis = axis.get_normalized();
+        let (anchor_a, anchor_b) = (self.local_point(a, anchor), self.local_point(b, anchor));
+        let axis_a = self.bodies[a].orientation.conjugate() * axis;
+        let axis_b = self.bodies[b].orientation.conjugate() * axis;
+
+        self.add_joint(Joint::<T>::new(a, b, anchor_a, anchor_b, JointKind::Hinge { axis_a, axis_b }))
+    }
+
+    pub fn add_joint(&mut self, joint: Joint<T>) -> usize {
+        self.joints.push(joint);
+        self.joints.len() - 1
+    }
+
+    /// The contacts found by the last step.
+    pub fn manifolds(&self) -> &[Manifold<T>] {
+        &self.manifolds
+    }
+
+    /// Advances the world by dt seconds.
+    pub fn step(&mut self, dt: T) {
+        for body in self.bodies.iter_mut().filter(|body| !body.is_static()) {
+            body.linear_velocity += self.gravity * dt;
+        }
+
+        self.collide();
+
+        let inverse_inertias: Vec<Mat33<T>> = self.bodies.iter().map(|body| body.world_inverse_inertia()).collect();
+        let bias_rate = self.baumgarte / dt;
+
+        for joint in &mut self.joints {
+            prepare_joint(joint, &mut self.bodies, &inverse_inertias, bias_rate, self.warm_starting);
+        }
+
+        for manifold in &mut self.manifolds {
+            prepare_manifold(manifold, &mut self.bodies, &inverse_inertias, bias_rate, self.slop,
+                self.restitution_threshold, self.warm_starting);
+        }
+
+        for _ in 0..self.iterations {
+            for joint in &mut self.joints {
+                solve_joint(joint, &mut self.bodies, &inverse_inertias);
+            }
+
+            for manifold in &mut self.manifolds {
+                solve_manifold(manifold, &mut self.bodies, &inverse_inertias);
+            }
+        }
+
+        for body in self.bodies.iter_mut().filter(|body| !body.is_static()) {
+            body.position += body.linear_velocity * dt;
+            body.orientation = integrate_orientation(body.orientation, body.angular_velocity, dt);
+        }
+    }
+
+    fn local_point(&self, body: usize, point: Vec3<T>) -> Vec3<T> {
+        self.bodies[body].orientation.conjugate() * (point - self.bodies[body].position)
+    }
+
+    /// Finds the contacts between every pair of colliders, carrying
+    /// impulses over from matching points of the last step. Bodies that
+    /// share a joint do not collide.
+    fn collide(&mut self) {
+        let previous = std::mem::take(&mut self.manifolds);
+
+        for a in 0..self.bodies.len() {
+            for b in a + 1..self.bodies.len() {
+                let (Some(collider_a), Some(collider_b)) = (self.colliders[a], self.colliders[b]) else { continue };
+                let (body_a, body_b) = (&self.bodies[a], &self.bodies[b]);
+
+                if body_a.is_static() && body_b.is_static() {
+                    continue;
+                }
+
+                if self.joints.iter().any(|joint| (joint.a, joint.b) == (a, b) || (joint.a, joint.b) == (b, a)) {
+                    continue;
+                }
+
+                let reach = bounding_radius(&collider_a.shape) + bounding_radius(&collider_b.shape);
+                if (body_b.position - body_a.position).length_sq() > reach * reach {
+                    continue;
+                }
+
+                let Some((normal, contacts)) = collide_shapes(body_a, &collider_a.shape, body_b, &collider_b.shape) else { continue };
+
+                let old = previous.binary_search_by_key(&(a, b), |manifold| (manifold.a, manifold.b)).ok().map(|i| &previous[i]);
+                let points = contacts.iter().map(|&(position, depth)| {
+                    let local_a = body_a.orientation.conjugate() * (position - body_a.position);
+                    let mut point = ContactPoint::<T> {
+                        position,
+                        depth,
+                        normal_impulse: T::ZERO,
+                        tangent_impulses: [T::ZERO; 2],
+                        local_a,
+                        r_a: Vec3::<T>::ZERO,
+                        r_b: Vec3::<T>::ZERO,
+                        normal_mass: T::ZERO,
+                        tangent_masses: [T::ZERO; 2],
+                        target_velocity: T::ZERO
+                    };
+
+                    let matched = old.and_then(|old| old.points.iter()
+                        .find(|p| (p.local_a - local_a).length_sq() <= self.match_distance * self.match_distance));
+
+                    if let Some(matched) = matched {
+                        point.normal_impulse = matched.normal_impulse;
+                        point.tangent_impulses = matched.tangent_impulses;
+                    }
+
+                    point
+                }).collect();
+
+                self.manifolds.push(Manifold::<T> {
+                    a,
+                    b,
+                    normal,
+                    tangents: tangents(normal),
+                    points,
+                    friction: (collider_a.friction * collider_b.friction).sqrt(),
+                    restitution: T::max(collider_a.restitution, collider_b.restitution)
+                });
+            }
+        }
+    }
+}
+
+/// Contact normal from a to b and the points with their depths.
+type Contacts<T> = (Vec3<T>, Vec<(Vec3<T>, T)>);
+
+/// Overlap along a separating axis candidate, the axis pointing from a to b
+/// and the indices of the box axes it came from.
+type Separation<T> = (T, Vec3<T>, usize, usize);
+
+fn collide_shapes<T>(body_a: &RigidBody<T>, shape_a: &Shape<T>, body_b: &RigidBody<T>, shape_b: &Shape<T>) -> Option<Contacts<T>>
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T> + Neg<Output = T>
+{
+    let single = |contact: Contact<T>| (contact.normal, vec![(contact.point, contact.depth)]);
+
+    match (*shape_a, *shape_b) {
+        (Shape::Sphere { radius: radius_a }, Shape::Sphere { radius: radius_b }) => {
+            let sphere = Sphere::<T>::new(body_a.position, radius_a);
+            sphere.collide_sphere(&Sphere::<T>::new(body_b.position, radius_b)).map(single)
+        }
+        (Shape::Sphere { radius }, Shape::Cuboid { half_extents }) => {
+            let sphere = Sphere::<T>::new(body_a.position, radius);
+            sphere.collide_obb(&Obb::<T>::from_quat(body_b.position, half_extents, body_b.orientation)).map(single)
+        }
+        (Shape::Cuboid { .. }, Shape::Sphere { .. }) => {
+            let (normal, points) = collide_shapes(body_b, shape_b, body_a, shape_a)?;
+            Some((-normal, points))
+        }
+        (Shape::Cuboid { half_extents: extents_a }, Shape::Cuboid { half_extents: extents_b }) => collide_boxes(
+            &Obb::<T>::from_quat(body_a.position, extents_a, body_a.orientation),
+            &Obb::<T>::from_quat(body_b.position, extents_b, body_b.orientation))
+    }
+}
+
+/// Box against box by separating axes. Face contacts clip the incident face
+/// against the sides of the reference face for up to four points; edge
+/// contacts give the closest points of the two edges.
+fn collide_boxes<T>(a: &Obb<T>, b: &Obb<T>) -> Option<Contacts<T>>
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T> + Neg<Output = T>
+{
+    let offset = b.center - a.center;
+
+    // The axis of least overlap among the faces of a, the faces of b and the
+    // edge pairs.
+    let mut best: [Option<Separation<T>>; 3] = [None; 3];
+
+    for (kind, best) in best.iter_mut().enumerate() {
+        for first in 0..3 {
+            for second in 0..3 {
+                let axis = match kind {
+                    0 if second == 0 => a.axis(first),
+                    1 if second == 0 => b.axis(first),
+                    2 => a.axis(first) % b.axis(second),
+                    _ => continue
+                };
+
+                // Parallel edges are already covered by the face axes.
+                let len_sq = axis.length_sq();
+                if len_sq <= T::EPSILON {
+                    continue;
+                }
+
+                let axis = axis / len_sq.sqrt();
+                let dist = offset * axis;
+                let overlap = a.projected_radius(axis) + b.projected_radius(axis) - dist.abs();
+
+                if overlap < T::ZERO {
+                    return None;
+                }
+
+                if best.is_none_or(|(depth, ..)| overlap < depth) {
+                    *best = Some((overlap, if dist < T::ZERO { -axis } else { axis }, first, second));
+                }
+            }
+        }
+    }
+
+    // Prefer faces, and the faces of a, unless another axis is clearly
+    // better, so resting contacts do not flip between features.
+    let tolerance = T::from_f64(0.95);
+    let (face_a, face_b) = (best[0]?, best[1]?);
+    let face = if face_b.0 < face_a.0 * tolerance { (face_b, true) } else { (face_a, false) };
+
+    if let Some((depth, normal, edge_a, edge_b)) = best[2] {
+        if depth < face.0.0 * tolerance {
+            return Some((normal, vec![edge_contact(a, b, normal, edge_a, edge_b, depth)]));
+        }
+    }
+
+    let ((_, normal, axis, _), flipped) = face;
+    let points = if flipped { clip_faces(b, a, -normal, axis) } else { clip_faces(a, b, normal, axis) };
+
+    if points.is_empty() {
+        let contact = a.collide_obb(b)?;
+        return Some((contact.normal, vec![(contact.point, contact.depth)]));
+    }
+
+    Some((normal, reduce_points(points, normal)))
+}
+
+/// Clips the face of the incident box facing the reference box against the
+/// reference face with the given outward normal.
+fn clip_faces<T>(reference: &Obb<T>, incident: &Obb<T>, normal: Vec3<T>, axis: usize) -> Vec<(Vec3<T>, T)>
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T> + Neg<Output = T>
+{
+    // The incident face is the one most opposed to the normal.
+    let mut facing = 0;
+    for i in 1..3 {
+        if (incident.axis(i) * normal).abs() > (incident.axis(facing) * normal).abs() {
+            facing = i;
+        }
+    }
+
+    let sign = if incident.axis(facing) * normal > T::ZERO { -T::ONE } else { T::ONE };
+    let center = incident.center + incident.axis(facing) * (incident.half_extents[facing] * sign);
+    let u = incident.axis((facing + 1) % 3) * incident.half_extents[(facing + 1) % 3];
+    let v = incident.axis((facing + 2) % 3) * incident.half_extents[(facing + 2) % 3];
+
+    let mut polygon = vec![center + u + v, center - u + v, center - u - v, center + u - v];
+
+    // Clip against the four side planes of the reference face.
+    for side in [(axis + 1) % 3, (axis + 2) % 3] {
+        let side_axis = reference.axis(side);
+        let extent = reference.half_extents[side];
+        let middle = side_axis * reference.center;
+
+        polygon = clip_polygon(&polygon, side_axis, middle + extent);
+        polygon = clip_polygon(&polygon, -side_axis, extent - middle);
+    }
+
+    // Keep the points below the reference face, placed midway between the
+    // incident point and the face.
+    let face = normal * reference.center + reference.half_extents[axis];
+
+    polygon.iter().filter_map(|&p| {
+        let separation = normal * p - face;
+        (separation <= T::ZERO).then(|| (p - normal * (separation * T::HALF), -separation))
+    }).collect()
+}
+
+/// Sutherland-Hodgman clipping of a polygon to the half space n * p <= d.
+fn clip_polygon<T>(polygon: &[Vec3<T>], n: Vec3<T>, d: T) -> Vec<Vec3<T>>
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>
+{
+    let mut result = Vec::with_capacity(polygon.len() + 1);
+
+    for (i, &start) in polygon.iter().enumerate() {
+        let end = polygon[(i + 1) % polygon.len()];
+        let (start_dist, end_dist) = (n * start - d, n * end - d);
+
+        if start_dist <= T::ZERO {
+            result.push(start);
+        }
+
+        if (start_dist <= T::ZERO) != (end_dist <= T::ZERO) {
+            result.push(start + (end - start) * (start_dist / (start_dist - end_dist)));
+        }
+    }
+
+    result
+}
+
+/// Keeps at most four points: the deepest, the one furthest from it, the one
+/// furthest from the line through both, then the one furthest outside that
+/// triangle.
+fn reduce_points<T>(points: Vec<(Vec3<T>, T)>, normal: Vec3<T>) -> Vec<(Vec3<T>, T)>
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T> + Neg<Output = T>
+{
+    if points.len() <= 4 {
+        return points;
+    }
+
+    let best_by = |score: &dyn Fn(Vec3<T>, T) -> T| {
+        let mut best = 0;
+        for (i, &(p, depth)) in points.iter().enumerate() {
+            if score(p, depth) > score(points[best].0, points[best].1) {
+                best = i;
+            }
+        }
+        best
+    };
+
+    let first = best_by(&|_, depth| depth);
+    let p1 = points[first].0;
+    let second = best_by(&|p, _| (p - p1).length_sq());
+    let p2 = points[second].0;
+    let third = best_by(&|p, _| (((p2 - p1) % (p - p1)) * normal).abs());
+    let p3 = points[third].0;
+
+    // Signed areas are negative outside an edge of the triangle.
+    let winding = if ((p2 - p1) % (p3 - p1)) * normal < T::ZERO { -T::ONE } else { T::ONE };
+    let outside = |p: Vec3<T>, _: T| {
+        let areas = [((p2 - p1) % (p - p1)) * normal, ((p3 - p2) % (p - p2)) * normal, ((p1 - p3) % (p - p3)) * normal];
+        -T::min(T::min(areas[0], areas[1]), areas[2]) * winding
+    };
+    let fourth = best_by(&outside);
+
+    vec![points[first], points[second], points[third], points[fourth]]
+}
+
+/// The closest points of the edges of a and b along the given axes that lie
+/// furthest towards each other.
+fn edge_contact<T>(a: &Obb<T>, b: &Obb<T>, normal: Vec3<T>, edge_a: usize, edge_b: usize, depth: T) -> (Vec3<T>, T)
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T> + Neg<Output = T>
+{
+    let edge = |obb: &Obb<T>, along: usize, towards: Vec3<T>| {
+        let mut middle = obb.center;
+        for i in (0..3).filter(|&i| i != along) {
+            let sign = if obb.axis(i) * towards < T::ZERO { -T::ONE } else { T::ONE };
+            middle += obb.axis(i) * (obb.half_extents[i] * sign);
+        }
+
+        let half = obb.axis(along) * obb.half_extents[along];
+        (middle - half, middle + half)
+    };
+
+    let (p1, q1) = edge(a, edge_a, normal);
+    let (p2, q2) = edge(b, edge_b, -normal);
+    let closest = segment_segment(p1, q1, p2, q2);
+
+    ((closest.on_a + closest.on_b) * T::HALF, depth)
+}
+
+fn bounding_radius<T>(shape: &Shape<T>) -> T
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + Mul<Output = T> + Div<Output = T> + DivAssign<T>
+{
+    match *shape {
+        Shape::Sphere { radius } => radius,
+        Shape::Cuboid { half_extents } => half_extents.length()
+    }
+}
+
+/// Two unit vectors perpendicular to the normal and each other.
+fn tangents<T>(normal: Vec3<T>) -> [Vec3<T>; 2]
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T> + Neg<Output = T>
+{
+    let first = normal.any_perpendicular();
+    [first, normal % first]
+}
+
+/// The inverse of the mass felt by an impulse along the direction applied
+/// at r_a on body a and r_b on body b.
+fn inverse_mass_along<T>(bodies: &[RigidBody<T>], inverse_inertias: &[Mat33<T>],
+    (a, r_a): (usize, Vec3<T>), (b, r_b): (usize, Vec3<T>), direction: Vec3<T>) -> T
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + Sub<Output = T> + Mul<Output = T>
+{
+    let angular_a = (inverse_inertias[a] * (r_a % direction)) % r_a;
+    let angular_b = (inverse_inertias[b] * (r_b % direction)) % r_b;
+
+    bodies[a].inverse_mass + bodies[b].inverse_mass + direction * (angular_a + angular_b)
+}
+
+fn inverse_or_zero<T: PartialOrd + Copy + Vectorable<T> + Div<Output = T>>(value: T) -> T {
+    if value > T::ZERO { T::ONE / value } else { T::ZERO }
+}
+
+/// The velocity of the point on b relative to the point on a.
+fn relative_velocity<T>(bodies: &[RigidBody<T>], (a, r_a): (usize, Vec3<T>), (b, r_b): (usize, Vec3<T>)) -> Vec3<T>
+    where T: PartialOrd + Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T>
+{
+    let velocity_a = bodies[a].linear_velocity + bodies[a].angular_velocity % r_a;
+    let velocity_b = bodies[b].linear_velocity + bodies[b].angular_velocity % r_b;
+    velocity_b - velocity_a
+}
+
+/// Applies the impulse to b and its opposite to a.
+fn apply_impulse<T>(bodies: &mut [RigidBody<T>], inverse_inertias: &[Mat33<T>],
+    (a, r_a): (usize, Vec3<T>), (b, r_b): (usize, Vec3<T>), impulse: Vec3<T>)
+    where T: PartialOrd + Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T>
+{
+    bodies[a].linear_velocity = bodies[a].linear_velocity - impulse * bodies[a].inverse_mass;
+    bodies[a].angular_velocity = bodies[a].angular_velocity - inverse_inertias[a] * (r_a % impulse);
+    bodies[b].linear_velocity = bodies[b].linear_velocity + impulse * bodies[b].inverse_mass;
+    bodies[b].angular_velocity = bodies[b].angular_velocity + inverse_inertias[b] * (r_b % impulse);
+}
+
+/// Applies the angular impulse to b and its opposite to a.
+fn apply_angular_impulse<T>(bodies: &mut [RigidBody<T>], inverse_inertias: &[Mat33<T>], a: usize, b: usize, impulse: Vec3<T>)
+    where T: PartialOrd + Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T>
+{
+    bodies[a].angular_velocity = bodies[a].angular_velocity - inverse_inertias[a] * impulse;
+    bodies[b].angular_velocity = bodies[b].angular_velocity + inverse_inertias[b] * impulse;
+}
+
+fn prepare_manifold<T>(manifold: &mut Manifold<T>, bodies: &mut [RigidBody<T>], inverse_inertias: &[Mat33<T>],
+    bias_rate: T, slop: T, restitution_threshold: T, warm_starting: bool)
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T> + Neg<Output = T>
+{
+    let (a, b, normal) = (manifold.a, manifold.b, manifold.normal);
+
+    for point in &mut manifold.points {
+        point.r_a = point.position - bodies[a].position;
+        point.r_b = point.position - bodies[b].position;
+        let (at_a, at_b) = ((a, point.r_a), (b, point.r_b));
+
+        point.normal_mass = inverse_or_zero(inverse_mass_along(bodies, inverse_inertias, at_a, at_b, normal));
+        for (mass, tangent) in point.tangent_masses.iter_mut().zip(manifold.tangents) {
+            *mass = inverse_or_zero(inverse_mass_along(bodies, inverse_inertias, at_a, at_b, tangent));
+        }
+
+        // Separate at a rate set by the penetration, or bounce back from a
+        // fast approach, whichever is faster.
+        let approach = relative_velocity(bodies, at_a, at_b) * normal;
+        let push = bias_rate * T::max(point.depth - slop, T::ZERO);
+        let bounce = if approach < -restitution_threshold { -approach * manifold.restitution } else { T::ZERO };
+        point.target_velocity = T::max(push, bounce);
+
+        if warm_starting {
+            let impulse = normal * point.normal_impulse
+                + manifold.tangents[0] * point.tangent_impulses[0]
+                + manifold.tangents[1] * point.tangent_impulses[1];
+            apply_impulse(bodies, inverse_inertias, at_a, at_b, impulse);
+        }
+        else {
+            point.normal_impulse = T::ZERO;
+            point.tangent_impulses = [T::ZERO; 2];
+        }
+    }
+}
+
+fn solve_manifold<T>(manifold: &mut Manifold<T>, bodies: &mut [RigidBody<T>], inverse_inertias: &[Mat33<T>])
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T> + Neg<Output = T>
+{
+    let (a, b, normal) = (manifold.a, manifold.b, manifold.normal);
+
+    for point in &mut manifold.points {
+        let (at_a, at_b) = ((a, point.r_a), (b, point.r_b));
+
+        // Friction first, bounded by the friction cone of the current
+        // normal impulse.
+        let limit = manifold.friction * point.normal_impulse;
+        for i in 0..2 {
+            let tangent = manifold.tangents[i];
+            let speed = relative_velocity(bodies, at_a, at_b) * tangent;
+            let accumulated = T::min(T::max(point.tangent_impulses[i] - speed * point.tangent_masses[i], -limit), limit);
+            let change = accumulated - point.tangent_impulses[i];
+            point.tangent_impulses[i] = accumulated;
+
+            apply_impulse(bodies, inverse_inertias, at_a, at_b, tangent * change);
+        }
+
+        // The accumulated normal impulse may only push.
+        let speed = relative_velocity(bodies, at_a, at_b) * normal;
+        let accumulated = T::max(point.normal_impulse + (point.target_velocity - speed) * point.normal_mass, T::ZERO);
+        let change = accumulated - point.normal_impulse;
+        point.normal_impulse = accumulated;
+
+        apply_impulse(bodies, inverse_inertias, at_a, at_b, normal * change);
+    }
+}
+
+fn prepare_joint<T>(joint: &mut Joint<T>, bodies: &mut [RigidBody<T>], inverse_inertias: &[Mat33<T>], bias_rate: T, warm_starting: bool)
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T> + Neg<Output = T>
+{
+    let (a, b) = (joint.a, joint.b);
+    joint.r_a = bodies[a].orientation * joint.anchor_a;
+    joint.r_b = bodies[b].orientation * joint.anchor_b;
+    let (at_a, at_b) = ((a, joint.r_a), (b, joint.r_b));
+    let error = (bodies[b].position + joint.r_b) - (bodies[a].position + joint.r_a);
+
+    match joint.kind {
+        JointKind::Ball | JointKind::Hinge { .. } => {
+            // Build the 3x3 mass matrix one axis at a time.
+            let mut mass = Mat33::<T>::ZERO;
+            for (column, axis) in [Vec3::<T>::X, Vec3::<T>::Y, Vec3::<T>::Z].into_iter().enumerate() {
+                let angular_a = (inverse_inertias[a] * (joint.r_a % axis)) % joint.r_a;
+                let angular_b = (inverse_inertias[b] * (joint.r_b % axis)) % joint.r_b;
+                let image = axis * (bodies[a].inverse_mass + bodies[b].inverse_mass) + angular_a + angular_b;
+                mass.data[column] = [image.x, image.y, image.z];
+            }
+
+            joint.linear_mass = mass.get_inverted();
+            joint.linear_bias = error * bias_rate;
+        }
+        JointKind::Distance { length } => {
+            let distance = error.length();
+            joint.direction = if distance > T::EPSILON { error / distance } else { Vec3::<T>::Y };
+            joint.axial_mass = inverse_or_zero(inverse_mass_along(bodies, inverse_inertias, at_a, at_b, joint.direction));
+            joint.axial_bias = (distance - length) * bias_rate;
+        }
+    }
+
+    if let JointKind::Hinge { axis_a, axis_b } = joint.kind {
+        // Rotation about the two axes perpendicular to the hinge is locked.
+        let axis_a = bodies[a].orientation * axis_a;
+        let axis_b = bodies[b].orientation * axis_b;
+        let misalignment = axis_a % axis_b;
+        joint.angular_axes = tangents(axis_a);
+
+        for i in 0..2 {
+            let axis = joint.angular_axes[i];
+            joint.angular_masses[i] = inverse_or_zero(axis * (inverse_inertias[a] * axis + inverse_inertias[b] * axis));
+            joint.angular_biases[i] = misalignment * axis * bias_rate;
+        }
+    }
+
+    if warm_starting {
+        let linear = joint.linear_impulse + joint.direction * joint.axial_impulse;
+        apply_impulse(bodies, inverse_inertias, at_a, at_b, linear);
+
+        let angular = joint.angular_axes[0] * joint.angular_impulses[0] + joint.angular_axes[1] * joint.angular_impulses[1];
+        apply_angular_impulse(bodies, inverse_inertias, a, b, angular);
+    }
+    else {
+        joint.linear_impulse = Vec3::<T>::ZERO;
+        joint.axial_impulse = T::ZERO;
+        joint.angular_impulses = [T::ZERO; 2];
+    }
+}
+
+fn solve_joint<T>(joint: &mut Joint<T>, bodies: &mut [RigidBody<T>], inverse_inertias: &[Mat33<T>])
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T> + Neg<Output = T>
+{
+    let (a, b) = (joint.a, joint.b);
+    let (at_a, at_b) = ((a, joint.r_a), (b, joint.r_b));
+
+    if let JointKind::Hinge { .. } = joint.kind {
+        for i in 0..2 {
+            let axis = joint.angular_axes[i];
+            let speed = (bodies[b].angular_velocity - bodies[a].angular_velocity) * axis;
+            let change = -(speed + joint.angular_biases[i]) * joint.angular_masses[i];
+            joint.angular_impulses[i] += change;
+
+            apply_angular_impulse(bodies, inverse_inertias, a, b, axis * change);
+        }
+    }
+
+    match joint.kind {
+        JointKind::Ball | JointKind::Hinge { .. } => {
+            let velocity = relative_velocity(bodies, at_a, at_b);
+            let change = -(joint.linear_mass * (velocity + joint.linear_bias));
+            joint.linear_impulse += change;
+
+            apply_impulse(bodies, inverse_inertias, at_a, at_b, change);
+        }
+        JointKind::Distance { .. } => {
+            let speed = relative_velocity(bodies, at_a, at_b) * joint.direction;
+            let change = -(speed + joint.axial_bias) * joint.axial_mass;
+            joint.axial_impulse += change;
+
+            apply_impulse(bodies, inverse_inertias, at_a, at_b, joint.direction * change);
+        }
+    }
+}
@@ -0,0 +1,325 @@
+use std::ops::{Add, AddAssign, Sub, Neg};
+
+use crate::glmath::*;
+use crate::glmath::dynamic::DVector;
+
+/// Collects (row, column, value) entries in any order before compressing
+/// them into a CsrMatrix. Repeated entries are summed, which is what
+/// assembling per element contributions needs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TripletBuilder<T: PartialOrd + Copy> {
+    pub rows: usize,
+    pub columns: usize,
+    pub entries: Vec<(usize, usize, T)>
+}
+
+/// A sparse matrix in compressed sparse row form. The entries of row i are
+/// at row_offsets[i]..row_offsets[i + 1], sorted by column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsrMatrix<T: PartialOrd + Copy> {
+    pub rows: usize,
+    pub columns: usize,
+    pub row_offsets: Vec<usize>,
+    pub column_indices: Vec<usize>,
+    pub values: Vec<T>
+}
+
+/// Preconditioners for CsrMatrix::conjugate_gradient.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Preconditioner {
+    None,
+    /// Divides by the diagonal.
+    Jacobi,
+    /// Incomplete Cholesky with the sparsity of the matrix itself. Falls
+    /// back to Jacobi if the factorization breaks down.
+    IncompleteCholesky
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    TripletBuilder<T>
+{
+    pub fn new(rows: usize, columns: usize) -> TripletBuilder<T> {
+        TripletBuilder::<T> { rows, columns, entries: Vec::new() }
+    }
+
+    /// Adds value to the entry at (row, column).
+    pub fn add(&mut self, row: usize, column: usize, value: T) {
+        assert!(row < self.rows && column < self.columns);
+        self.entries.push((row, column, value));
+    }
+
+    pub fn build(&self) -> CsrMatrix<T> {
+        let mut entries = self.entries.clone();
+        entries.sort_by_key(|(row, column, _)| (*row, *column));
+
+        let mut row_offsets = vec![0; self.rows + 1];
+        let mut column_indices: Vec<usize> = Vec::with_capacity(entries.len());
+        let mut values: Vec<T> = Vec::with_capacity(entries.len());
+        let mut last = None;
+
+        for (row, column, value) in entries {
+            if last == Some((row, column)) {
+                *values.last_mut().unwrap() += value;
+                continue;
+            }
+
+            last = Some((row, column));
+            column_indices.push(column);
+            values.push(value);
+            row_offsets[row + 1] += 1;
+        }
+
+        for row in 0..self.rows {
+            row_offsets[row + 1] += row_offsets[row];
+        }
+
+        CsrMatrix::<T> { rows: self.rows, columns: self.columns, row_offsets, column_indices, values }
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    CsrMatrix<T>
+{
+    pub fn identity(size: usize) -> CsrMatrix<T> {
+        CsrMatrix::<T> {
+            rows: size,
+            columns: size,
+            row_offsets: (0..=size).collect(),
+            column_indices: (0..size).collect(),
+            values: vec![T::ONE; size]
+        }
+    }
+
+    /// The number of stored entries.
+    pub fn non_zeros(&self) -> usize {
+        self.values.len()
+    }
+
+    /// The (column, value) pairs stored in a row.
+    pub fn row(&self, row: usize) -> impl Iterator<Item = (usize, T)> + '_ {
+        let range = self.row_offsets[row]..self.row_offsets[row + 1];
+        self.column_indices[range.clone()].iter().copied().zip(self.values[range].iter().copied())
+    }
+
+    /// The entry at (row, column), zero if it is not stored.
+    pub fn get(&self, row: usize, column: usize) -> T {
+        let range = self.row_offsets[row]..self.row_offsets[row + 1];
+        match self.column_indices[range.clone()].binary_search(&column) {
+            Ok(i) => self.values[range.start + i],
+            Err(_) => T::ZERO
+        }
+    }
+
+    pub fn diagonal(&self) -> Vec<T> {
+        (0..usize::min(self.rows, self.columns)).map(|i| self.get(i, i)).collect()
+    }
+
+    /// self * alpha + other * beta, with the union of both sparsity patterns.
+    pub fn add_scaled(&self, alpha: T, other: &CsrMatrix<T>, beta: T) -> CsrMatrix<T> {
+        assert_eq!((self.rows, self.columns), (other.rows, other.columns));
+
+        let mut builder = TripletBuilder::<T>::new(self.rows, self.columns);
+        for row in 0..self.rows {
+            for (column, value) in self.row(row) {
+                builder.add(row, column, alpha * value);
+            }
+            for (column, value) in other.row(row) {
+                builder.add(row, column, beta * value);
+            }
+        }
+        builder.build()
+    }
+
+    /// Sparse matrix-vector product.
+    pub fn mul_vector(&self, x: &DVector<T>) -> DVector<T> {
+        assert_eq!(self.columns, x.len());
+        DVector::<T>::from_vec((0..self.rows).map(|row| self.row(row).fold(T::ZERO, |sum, (column, value)| sum + value * x[column])).collect())
+    }
+
+    /// Multiplies each coordinate of a block of points by the matrix, as if
+    /// x were a columns x 3 matrix.
+    pub fn mul_vec3(&self, x: &[Vec3<T>]) -> Vec<Vec3<T>> {
+        assert_eq!(self.columns, x.len());
+        (0..self.rows).map(|row| self.row(row).fold(Vec3::<T>::ZERO, |sum, (column, value)| sum + x[column] * value)).collect()
+    }
+
+    /// Solves self * x = b for a symmetric positive definite matrix and a
+    /// block of right hand sides, one per coordinate, by preconditioned
+    /// conjugate gradients starting from initial. Returns None if the
+    /// residual is not below tolerance * |b| after max_iterations.
+    pub fn conjugate_gradient(&self, b: &[Vec3<T>], initial: &[Vec3<T>], preconditioner: Preconditioner, tolerance: T, max_iterations: usize) -> Option<Vec<Vec3<T>>> {
+        assert_eq!(self.rows, self.columns);
+        assert!(b.len() == self.rows && initial.len() == self.rows);
+
+        let dot = |a: &[Vec3<T>], b: &[Vec3<T>]| a.iter().zip(b).fold(T::ZERO, |sum, (a, b)| sum + *a * *b);
+
+        let factor = match preconditioner {
+            Preconditioner::IncompleteCholesky => self.incomplete_cholesky(),
+            _ => None
+        };
+        let inverse_diagonal: Vec<T> = self.diagonal().iter().map(|d| if *d != T::ZERO { T::ONE / *d } else { T::ONE }).collect();
+
+        let precondition = |r: &[Vec3<T>]| -> Vec<Vec3<T>> {
+            match (preconditioner, &factor) {
+                (Preconditioner::None, _) => r.to_vec(),
+                (_, Some(l)) => l.cholesky_solve(r),
+                _ => r.iter().zip(&inverse_diagonal).map(|(r, d)| *r * *d).collect()
+            }
+        };
+
+        let mut x = initial.to_vec();
+        let mut residual: Vec<Vec3<T>> = b.iter().zip(self.mul_vec3(&x)).map(|(b, ax)| *b - ax).collect();
+        let threshold = tolerance * tolerance * dot(b, b);
+
+        let mut z = precondition(&residual);
+        let mut direction = z.clone();
+        let mut rz = dot(&residual, &z);
+
+        for _ in 0..max_iterations {
+            if dot(&residual, &residual) <= threshold {
+                return Some(x);
+            }
+
+            let product = self.mul_vec3(&direction);
+            let curvature = dot(&direction, &product);
+            if curvature <= T::ZERO {
+                return None;
+            }
+
+            let step = rz / curvature;
+            for (x, d) in x.iter_mut().zip(&direction) {
+                *x += *d * step;
+            }
+            for (r, p) in residual.iter_mut().zip(&product) {
+                *r = *r - *p * step;
+            }
+
+            z = precondition(&residual);
+            let next_rz = dot(&residual, &z);
+            let beta = next_rz / rz;
+            for (d, z) in direction.iter_mut().zip(&z) {
+                *d = *z + *d * beta;
+            }
+            rz = next_rz;
+        }
+
+        if dot(&residual, &residual) <= threshold { Some(x) } else { None }
+    }
+
+    /// The lower triangular L with the sparsity of the lower triangle of
+    /// self such that L * L^T approximates self. Returns None on a non
+    /// positive pivot.
+    fn incomplete_cholesky(&self) -> Option<CsrMatrix<T>> {
+        let mut builder = TripletBuilder::<T>::new(self.rows, self.columns);
+        for row in 0..self.rows {
+            for (column, value) in self.row(row).filter(|(column, _)| *column <= row) {
+                builder.add(row, column, value);
+            }
+        }
+
+        let mut l = builder.build();
+        if (0..l.rows).any(|row| l.row_offsets[row] == l.row_offsets[row + 1] || l.column_indices[l.row_offsets[row + 1] - 1] != row) {
+            return None;
+        }
+
+        for row in 0..l.rows {
+            let (start, end) = (l.row_offsets[row], l.row_offsets[row + 1]);
+
+            for entry in start..end {
+                let column = l.column_indices[entry];
+
+                // The dot product of the rows of L before this column.
+                let mut sum = l.values[entry];
+                let (mut i, mut j) = (start, l.row_offsets[column]);
+                while i < entry && l.column_indices[j] < column {
+                    match l.column_indices[i].cmp(&l.column_indices[j]) {
+                        std::cmp::Ordering::Less => i += 1,
+                        std::cmp::Ordering::Greater => j += 1,
+                        std::cmp::Ordering::Equal => {
+                            sum = sum - l.values[i] * l.values[j];
+                            i += 1;
+                            j += 1;
+                        }
+                    }
+                }
+
+                if column == row {
+                    if sum <= T::ZERO {
+                        return None;
+                    }
+                    l.values[entry] = sum.sqrt();
+                }
+                else {
+                    l.values[entry] = sum / l.values[l.row_offsets[column + 1] - 1];
+                }
+            }
+        }
+
+        Some(l)
+    }
+
+    /// Solves L * L^T * x = b where self is lower triangular with its
+    /// diagonal stored last in each row.
+    fn cholesky_solve(&self, b: &[Vec3<T>]) -> Vec<Vec3<T>> {
+        let mut x = b.to_vec();
+
+        for row in 0..self.rows {
+            let diagonal_entry = self.row_offsets[row + 1] - 1;
+            let mut sum = x[row];
+            for entry in self.row_offsets[row]..diagonal_entry {
+                sum = sum - x[self.column_indices[entry]] * self.values[entry];
+            }
+            x[row] = sum / self.values[diagonal_entry];
+        }
+
+        // L^T by scattering each solved value up its row of L.
+        for row in (0..self.rows).rev() {
+            let diagonal_entry = self.row_offsets[row + 1] - 1;
+            x[row] /= self.values[diagonal_entry];
+
+            let solved = x[row];
+            for entry in self.row_offsets[row]..diagonal_entry {
+                let column = self.column_indices[entry];
+                x[column] = x[column] - solved * self.values[entry];
+            }
+        }
+
+        x
+    }
+}
+
+/// The cotangent Laplacian of a triangle mesh, with weights
+/// (cot a + cot b) / 2 for the angles opposite each edge. The sign makes
+/// it positive semi-definite, with rows summing to zero.
+pub fn cotangent_laplacian<T>(vertices: &[Vec3<T>], triangles: &[[usize; 3]]) -> CsrMatrix<T>
+    where T: PartialOrd + Copy + Vectorable<T> +
+        Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+        Div<Output = T> + DivAssign<T> + Neg<Output = T>
+{
+    let mut builder = TripletBuilder::<T>::new(vertices.len(), vertices.len());
+
+    for triangle in triangles {
+        for corner in 0..3 {
+            // The angle at corner is opposite the edge (i, j).
+            let (i, j) = (triangle[(corner + 1) % 3], triangle[(corner + 2) % 3]);
+            let (u, v) = (vertices[i] - vertices[triangle[corner]], vertices[j] - vertices[triangle[corner]]);
+
+            let sine = (u % v).length();
+            if sine == T::ZERO {
+                continue;
+            }
+
+            let weight = (u * v) / sine * T::HALF;
+            builder.add(i, j, -weight);
+            builder.add(j, i, -weight);
+            builder.add(i, i, weight);
+            builder.add(j, j, weight);
+        }
+    }
+
+    builder.build()
+}
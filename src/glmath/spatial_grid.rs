@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+use std::ops::{Add, Sub};
+
+use crate::glmath::*;
+
+/// Integer coordinates of a grid cell.
+pub type Cell = [i64; 3];
+
+/// A uniform grid stored in a hash map so only occupied cells use memory.
+/// Items are identified by caller chosen ids and stored in every cell
+/// their bounds touch. Suited to broadphase and particle neighbor queries
+/// where items are of similar size to the cells.
+#[derive(Debug, Clone)]
+pub struct SpatialHashGrid<T: PartialOrd + Copy> {
+    cell_size: T,
+    cells: HashMap<Cell, Vec<usize>>,
+    items: HashMap<usize, Aabb3<T>>
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>>
+    SpatialHashGrid<T>
+{
+    pub fn new(cell_size: T) -> SpatialHashGrid<T> {
+        SpatialHashGrid::<T> {
+            cell_size,
+            cells: HashMap::new(),
+            items: HashMap::new()
+        }
+    }
+
+    pub fn cell_size(&self) -> T {
+        self.cell_size
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Number of cells holding at least one item.
+    pub fn occupied_cells(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// The cell containing the point.
+    pub fn cell_of(&self, point: Vec3<T>) -> Cell {
+        let to_cell = |value: T| (value / self.cell_size).to_f64().floor() as i64;
+        [to_cell(point.x), to_cell(point.y), to_cell(point.z)]
+    }
+
+    /// The bounds an item was inserted with.
+    pub fn bounds(&self, id: usize) -> Option<Aabb3<T>> {
+        self.items.get(&id).copied()
+    }
+
+    /// Inserts an item covering the given bounds, replacing any item
+    /// already using the id.
+    pub fn insert(&mut self, id: usize, bounds: Aabb3<T>) {
+        self.remove(id);
+
+        let (min, max) = (self.cell_of(bounds.min), self.cell_of(bounds.max));
+        for_each_cell(min, max, |cell| self.cells.entry(cell).or_default().push(id));
+
+        self.items.insert(id, bounds);
+    }
+
+    pub fn insert_point(&mut self, id: usize, point: Vec3<T>) {
+        self.insert(id, Aabb3::<T>::new(point, point));
+    }
+
+    /// Removes an item. Returns false if there was no item with the id.
+    pub fn remove(&mut self, id: usize) -> bool {
+        let bounds = match self.items.remove(&id) {
+            Some(bounds) => bounds,
+            None => return false
+        };
+
+        let (min, max) = (self.cell_of(bounds.min), self.cell_of(bounds.max));
+        for_each_cell(min, max, |cell| {
+            if let Some(ids) = self.cells.get_mut(&cell) {
+                ids.retain(|other| *other != id);
+
+                if ids.is_empty() {
+                    self.cells.remove(&cell);
+                }
+            }
+        });
+
+        true
+    }
+
+    /// Moves an item to new bounds. Only touches the cell lists when the
+    /// item moves into different cells. Returns false if there was no
+    /// item with the id.
+    pub fn move_item(&mut self, id: usize, bounds: Aabb3<T>) -> bool {
+        let old = match self.items.get(&id) {
+            Some(old) => *old,
+            None => return false
+        };
+
+        if self.cell_of(old.min) == self.cell_of(bounds.min) && self.cell_of(old.max) == self.cell_of(bounds.max) {
+            self.items.insert(id, bounds);
+        }
+        else {
+            self.insert(id, bounds);
+        }
+
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.items.clear();
+    }
+
+    /// Calls back once with every item whose bounds overlap the box.
+    pub fn query_aabb<F>(&self, aabb: &Aabb3<T>, mut callback: F)
+        where F: FnMut(usize)
+    {
+        self.query_cells(aabb, |id, bounds| {
+            if bounds.overlaps(aabb) {
+                callback(id);
+            }
+        });
+    }
+
+    /// Calls back once with every item whose bounds are within radius of
+    /// the center.
+    pub fn query_radius<F>(&self, center: Vec3<T>, radius: T, mut callback: F)
+        where F: FnMut(usize)
+    {
+        let extent = Vec3::<T>::new(radius, radius, radius);
+        let aabb = Aabb3::<T>::new(center - extent, center + extent);
+        let radius_sq = radius * radius;
+
+        self.query_cells(&aabb, |id, bounds| {
+            if bounds.distance_sq(center) <= radius_sq {
+                callback(id);
+            }
+        });
+    }
+
+    /// Calls back once with every pair of items whose bounds overlap,
+    /// with the smaller id first.
+    pub fn overlapping_pairs<F>(&self, mut callback: F)
+        where F: FnMut(usize, usize)
+    {
+        for (cell, ids) in &self.cells {
+            for (i, a) in ids.iter().enumerate() {
+                let bounds_a = &self.items[a];
+                let min_a = self.cell_of(bounds_a.min);
+
+                for b in &ids[i + 1..] {
+                    let bounds_b = &self.items[b];
+                    let min_b = self.cell_of(bounds_b.min);
+
+                    // A pair sharing several cells is only reported from the
+                    // first cell they share.
+                    let first_shared = [min_a[0].max(min_b[0]), min_a[1].max(min_b[1]), min_a[2].max(min_b[2])];
+
+                    if first_shared == *cell && bounds_a.overlaps(bounds_b) {
+                        callback(usize::min(*a, *b), usize::max(*a, *b));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Calls back with the id and bounds of every item in the cells
+    /// overlapping the box, each item once.
+    fn query_cells<F>(&self, aabb: &Aabb3<T>, mut callback: F)
+        where F: FnMut(usize, &Aabb3<T>)
+    {
+        let (min, max) = (self.cell_of(aabb.min), self.cell_of(aabb.max));
+
+        for_each_cell(min, max, |cell| {
+            if let Some(ids) = self.cells.get(&cell) {
+                for id in ids {
+                    let bounds = &self.items[id];
+                    let item_min = self.cell_of(bounds.min);
+
+                    // Items spanning several of the queried cells are only
+                    // reported from the first of them.
+                    let first = [item_min[0].max(min[0]), item_min[1].max(min[1]), item_min[2].max(min[2])];
+
+                    if first == cell {
+                        callback(*id, bounds);
+                    }
+                }
+            }
+        });
+    }
+}
+
+fn for_each_cell<F>(min: Cell, max: Cell, mut callback: F)
+    where F: FnMut(Cell)
+{
+    for x in min[0]..=max[0] {
+        for y in min[1]..=max[1] {
+            for z in min[2]..=max[2] {
+                callback([x, y, z]);
+            }
+        }
+    }
+}
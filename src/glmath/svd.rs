@@ -0,0 +1,75 @@
+use std::ops::{Add, AddAssign, Sub, Neg};
+
+use crate::glmath::*;
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    Mat33<T>
+{
+    /// Singular value decomposition self = U * diag(sigma) * V^T, following
+    /// McAdams et al: V diagonalizes self^T * self by Jacobi rotations and
+    /// Givens QR of self * V gives U. U and V are always rotations, so a
+    /// reflection shows up as a negative last singular value. The values are
+    /// sorted by magnitude, largest first.
+    pub fn svd(&self) -> (Mat33<T>, Vec3<T>, Mat33<T>) {
+        let (_, v) = (self.get_transposed() * *self).symmetric_eigen();
+        let product = *self * v;
+
+        // b[row][column] is reduced to upper triangular by rotations that
+        // leave the first two pivots positive.
+        let mut b = [0, 1, 2].map(|row| [0, 1, 2].map(|column| product.data[column][row]));
+        let mut u = Mat33::<T>::IDENTITY;
+
+        for (i, j, column) in [(0, 1, 0), (0, 2, 0), (1, 2, 1)] {
+            let (x, y) = (b[i][column], b[j][column]);
+            let r = (x * x + y * y).sqrt();
+
+            if r == T::ZERO {
+                continue;
+            }
+
+            let (c, s) = (x / r, y / r);
+
+            let (row_i, row_j) = (b[i], b[j]);
+            b[i] = [0, 1, 2].map(|k| c * row_i[k] + s * row_j[k]);
+            b[j] = [0, 1, 2].map(|k| c * row_j[k] - s * row_i[k]);
+
+            // U collects the transposes of the rotations.
+            for row in 0..3 {
+                let (u_i, u_j) = (u.data[i][row], u.data[j][row]);
+                u.data[i][row] = c * u_i + s * u_j;
+                u.data[j][row] = c * u_j - s * u_i;
+            }
+        }
+
+        (u, Vec3::<T>::new(b[0][0], b[1][1], b[2][2]), v)
+    }
+
+    /// Polar decomposition self = R * S into a rotation R and a symmetric
+    /// stretch S. A reflection is kept out of R, leaving S with a negative
+    /// eigenvalue, so R can always be turned into a Quat.
+    pub fn polar_decompose(&self) -> (Mat33<T>, Mat33<T>) {
+        let (u, sigma, v) = self.svd();
+        let scaled = Mat33::<T>::from_axes(v.column(0) * sigma.x, v.column(1) * sigma.y, v.column(2) * sigma.z);
+
+        (u * v.get_transposed(), scaled * v.get_transposed())
+    }
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    Mat44<T>
+{
+    /// Splits an affine matrix into translation, rotation and a symmetric
+    /// stretch holding scale and shear, so that it equals translating after
+    /// rotating after stretching. The rotation is the closest one to the
+    /// upper 3x3 part, so shear does not leak into it.
+    pub fn decompose(&self) -> (Vec3<T>, Quat<T>, Mat33<T>) {
+        let (rotation, stretch) = Mat33::<T>::from_mat44(self).polar_decompose();
+        let translation = Vec3::<T>::new(self.data[3][0], self.data[3][1], self.data[3][2]);
+
+        (translation, Quat::<T>::from_matrix(&rotation.to_mat44()).get_normalized(), stretch)
+    }
+}
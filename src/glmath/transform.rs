@@ -0,0 +1,81 @@
+use std::ops::{Add, AddAssign, Sub, Neg};
+
+use crate::glmath::*;
+
+/// A transform stored as separate translation, rotation and scale. Applied
+/// to a point it scales, then rotates, then translates.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct Transform<T: PartialOrd + Copy> {
+    pub translation: Vec3<T>,
+    pub rotation: Quat<T>,
+    pub scale: Vec3<T>
+}
+
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + AddAssign<T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T> + Neg<Output = T>>
+    Transform<T>
+{
+    pub fn new(translation: Vec3<T>, rotation: Quat<T>, scale: Vec3<T>) -> Transform<T> {
+        Transform::<T> { translation, rotation, scale }
+    }
+
+    pub fn identity() -> Transform<T> {
+        Transform::<T>::new(Vec3::<T>::ZERO, Quat::<T>::IDENTITY, Vec3::<T>::new(T::ONE, T::ONE, T::ONE))
+    }
+
+    /// Decomposes an affine matrix. Shear, which a transform cannot hold, is
+    /// dropped by taking the closest rotation. A mirroring matrix is given a
+    /// negative x scale.
+    pub fn from_matrix(matrix: &Mat44<T>) -> Transform<T> {
+        let mut linear = Mat33::<T>::from_mat44(matrix);
+
+        // A negative determinant means the axes are mirrored. Flip x so the
+        // rest is a rotation and a stretch.
+        let mirrored = linear.det() < T::ZERO;
+        if mirrored {
+            linear.data[0] = linear.data[0].map(|v| -v);
+        }
+
+        let (rotation, stretch) = linear.polar_decompose();
+        let mut scale = Vec3::<T>::new(stretch.data[0][0], stretch.data[1][1], stretch.data[2][2]);
+
+        if mirrored {
+            scale.x = -scale.x;
+        }
+
+        Transform::<T> {
+            translation: Vec3::<T>::new(matrix.data[3][0], matrix.data[3][1], matrix.data[3][2]),
+            rotation: Quat::<T>::from_matrix(&rotation.to_mat44()).get_normalized(),
+            scale
+        }
+    }
+
+    pub fn to_matrix(&self) -> Mat44<T> {
+        let mut matrix = self.rotation.to_matrix();
+
+        for i in 0..3 {
+            for row in 0..3 {
+                matrix.data[i][row] = matrix.data[i][row] * self.scale[i];
+            }
+        }
+
+        matrix.data[3][0] = self.translation.x;
+        matrix.data[3][1] = self.translation.y;
+        matrix.data[3][2] = self.translation.z;
+        matrix
+    }
+
+    pub fn transform_point(&self, point: Vec3<T>) -> Vec3<T> {
+        self.rotation * scale_vec(point, self.scale) + self.translation
+    }
+
+    pub fn transform_vector(&self, vector: Vec3<T>) -> Vec3<T> {
+        self.rotation * scale_vec(vector, self.scale)
+    }
+}
+
+/// Component wise product of two vectors.
+fn scale_vec<T: PartialOrd + Copy + Mul<Output = T>>(a: Vec3<T>, b: Vec3<T>) -> Vec3<T> {
+    Vec3::<T> { x: a.x * b.x, y: a.y * b.y, z: a.z * b.z }
+}
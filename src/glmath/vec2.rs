@@ -1,4 +1,4 @@
-use std::{ops::{Add, AddAssign, Sub, SubAssign, Mul, MulAssign, Neg}, fmt::Display};
+use std::{ops::{Add, AddAssign, Sub, SubAssign, Mul, MulAssign, Neg, Index, IndexMut}, fmt::Display};
 use crate::glmath::*;
 
 #[derive(Debug, Clone, Copy, Default, PartialEq)]
@@ -143,6 +143,29 @@ impl<T: PartialOrd + Copy + DivAssign> DivAssign<T> for Vec2<T> {
     }
 }
 
+// Component access by axis index.
+impl<T: PartialOrd + Copy> Index<usize> for Vec2<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        match index {
+            0 => &self.x,
+            1 => &self.y,
+            _ => panic!("Vec2 index out of range: {}", index)
+        }
+    }
+}
+
+impl<T: PartialOrd + Copy> IndexMut<usize> for Vec2<T> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        match index {
+            0 => &mut self.x,
+            1 => &mut self.y,
+            _ => panic!("Vec2 index out of range: {}", index)
+        }
+    }
+}
+
 impl<T: PartialOrd + Copy +
     std::ops::Div<Output = T>> TwoDimSwizzle<T> for Vec2<T> {
     fn x(&self) -> &T {
@@ -154,7 +177,7 @@ impl<T: PartialOrd + Copy +
     }
 
     fn xy(&self) -> Vec2<T> {
-        self.clone()
+        *self
     }
 
     fn yx(&self) -> Vec2<T> {
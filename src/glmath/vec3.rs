@@ -1,4 +1,4 @@
-use std::{ops::{Add, AddAssign, Neg, Sub, SubAssign, Mul, MulAssign, Rem}, fmt::Display};
+use std::{ops::{Add, AddAssign, Neg, Sub, SubAssign, Mul, MulAssign, Rem, Index, IndexMut}, fmt::Display};
 
 use crate::glmath::*;
 
@@ -59,6 +59,27 @@ impl<T: Vectorable<T> + PartialOrd + Copy> Vec3<T> {
     };
 }
 
+impl<T: PartialOrd + Copy + Vectorable<T> +
+    Add<Output = T> + Sub<Output = T> + Mul<Output = T> +
+    Div<Output = T> + DivAssign<T>>
+    Vec3<T>
+{
+    /// Returns some unit vector perpendicular to the vector, crossing it
+    /// with the axis it has the smallest component along. The zero vector
+    /// gives Y.
+    pub fn any_perpendicular(&self) -> Vec3<T> {
+        let (x, y, z) = (self.x.abs(), self.y.abs(), self.z.abs());
+        let other = if x <= y && x <= z { Vec3::<T>::X } else if y <= z { Vec3::<T>::Y } else { Vec3::<T>::Z };
+        let perpendicular = *self % other;
+
+        if perpendicular.length_sq() == T::ZERO {
+            return Vec3::<T>::Y;
+        }
+
+        perpendicular.get_normalized()
+    }
+}
+
 impl<T: PartialOrd + Copy + std::ops::Neg<Output = T>> Neg for Vec3<T> {
     type Output = Vec3<T>;
 
@@ -178,6 +199,31 @@ impl<T: PartialOrd + Copy + Mul<Output = T> + Sub<Output = T>> Rem<Vec3<T>> for
     }
 }
 
+// Component access by axis index.
+impl<T: PartialOrd + Copy> Index<usize> for Vec3<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        match index {
+            0 => &self.x,
+            1 => &self.y,
+            2 => &self.z,
+            _ => panic!("Vec3 index out of range: {}", index)
+        }
+    }
+}
+
+impl<T: PartialOrd + Copy> IndexMut<usize> for Vec3<T> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        match index {
+            0 => &mut self.x,
+            1 => &mut self.y,
+            2 => &mut self.z,
+            _ => panic!("Vec3 index out of range: {}", index)
+        }
+    }
+}
+
 impl<T: PartialOrd + Copy> TwoDimSwizzle<T> for Vec3<T> {
     fn x(&self) -> &T {
         &self.x
@@ -208,7 +254,7 @@ impl<T: PartialOrd + Copy> ThreeDimSwizzle<T> for Vec3<T> {
     }
 
     fn xyz(&self) -> Vec3<T> {
-        self.clone()
+        *self
     }
 
     fn yxz(&self) -> Vec3<T> {
@@ -1,4 +1,4 @@
-use std::{ops::{Add, MulAssign, AddAssign, SubAssign, Sub, Neg}, fmt::Display};
+use std::{ops::{Add, MulAssign, AddAssign, SubAssign, Sub, Neg, Index, IndexMut}, fmt::Display};
 use crate::glmath::*;
 
 #[derive(Debug, Copy, Clone, Default, PartialEq)]
@@ -188,6 +188,33 @@ impl<T: PartialOrd + Copy +
     }
 }
 
+// Component access by axis index.
+impl<T: PartialOrd + Copy> Index<usize> for Vec4<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        match index {
+            0 => &self.x,
+            1 => &self.y,
+            2 => &self.z,
+            3 => &self.w,
+            _ => panic!("Vec4 index out of range: {}", index)
+        }
+    }
+}
+
+impl<T: PartialOrd + Copy> IndexMut<usize> for Vec4<T> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        match index {
+            0 => &mut self.x,
+            1 => &mut self.y,
+            2 => &mut self.z,
+            3 => &mut self.w,
+            _ => panic!("Vec4 index out of range: {}", index)
+        }
+    }
+}
+
 impl<T: Vectorable<T> + PartialOrd + Copy> TwoDimSwizzle<T> for Vec4<T> {
     fn x(&self) -> &T {
         &self.x
@@ -238,11 +265,11 @@ impl<T: Vectorable<T> + PartialOrd + Copy> ThreeDimSwizzle<T> for Vec4<T> {
 
 impl<T: Vectorable<T> + PartialOrd + Copy> FourDimSwizzle<T> for Vec4<T> {
     fn w(&self) -> &T {
-        return &self.w;
+        &self.w
     }
 
     fn xyzw(&self) -> Vec4<T> {
-        self.clone()
+        *self
     }
 
     fn yxzw(&self) -> Vec4<T> {
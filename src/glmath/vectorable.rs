@@ -6,8 +6,13 @@ pub trait Vectorable<T> {
 
     fn atan2(a: T, b: T) -> T;
     fn asin(&self) -> T;
+    fn exp(&self) -> T;
 
     fn max(a: Self, b: Self) -> T;
+    fn min(a: Self, b: Self) -> T;
+    fn abs(&self) -> T;
+    fn from_f64(value: f64) -> T;
+    fn to_f64(&self) -> f64;
 
     const ZERO: T;
     const ONE: T;
@@ -15,6 +20,9 @@ pub trait Vectorable<T> {
     const HALF: T;
     const QUARTER: T;
     const PI: T;
+    const EPSILON: T;
+    /// The smallest positive subnormal value.
+    const TINY: T;
 }
 
 impl Vectorable<f32> for f32 {
@@ -42,16 +50,38 @@ impl Vectorable<f32> for f32 {
         f32::asin(*self)
     }
 
+    fn exp(&self) -> f32 {
+        f32::exp(*self)
+    }
+
     fn max(a: Self, b: Self) -> f32 {
         if a > b { a } else { b }
     }
 
+    fn min(a: Self, b: Self) -> f32 {
+        if a < b { a } else { b }
+    }
+
+    fn abs(&self) -> f32 {
+        f32::abs(*self)
+    }
+
+    fn from_f64(value: f64) -> f32 {
+        value as f32
+    }
+
+    fn to_f64(&self) -> f64 {
+        *self as f64
+    }
+
     const ZERO: f32 = 0.0;
     const ONE: f32 = 1.0;
     const TWO: f32 = 2.0;
     const HALF: f32 = 0.5;
     const QUARTER: f32 = 0.25;
-    const PI: f32 = 3.1415926;
+    const PI: f32 = std::f32::consts::PI;
+    const EPSILON: f32 = f32::EPSILON;
+    const TINY: f32 = f32::from_bits(1);
 }
 
 impl Vectorable<f64> for f64 {
@@ -79,14 +109,36 @@ impl Vectorable<f64> for f64 {
         f64::asin(*self)
     }
 
+    fn exp(&self) -> f64 {
+        f64::exp(*self)
+    }
+
     fn max(a: Self, b: Self) -> f64 {
         if a > b { a } else { b }
     }
 
+    fn min(a: Self, b: Self) -> f64 {
+        if a < b { a } else { b }
+    }
+
+    fn abs(&self) -> f64 {
+        f64::abs(*self)
+    }
+
+    fn from_f64(value: f64) -> f64 {
+        value
+    }
+
+    fn to_f64(&self) -> f64 {
+        *self
+    }
+
     const ZERO: f64 = 0.0;
     const ONE: f64 = 1.0;
     const TWO: f64 = 2.0;
     const HALF: f64 = 0.5;
     const QUARTER: f64 = 0.25;
-    const PI: f64 = 3.1415926;
+    const PI: f64 = std::f64::consts::PI;
+    const EPSILON: f64 = f64::EPSILON;
+    const TINY: f64 = f64::from_bits(1);
 }
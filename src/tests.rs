@@ -1,4 +1,5 @@
-#[cfg(test)] 
+#[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests {
     macro_rules! assert_delta {
         ($x:expr, $y:expr, $d:expr) => {
@@ -6,10 +7,40 @@ mod tests {
         }
     }
 
+    macro_rules! assert_near {
+        ($x:expr, $y:expr, $d:expr) => {
+            assert!(($x - $y).abs() <= $d, "{} is not within {} of {}", $x, $d, $y);
+        }
+    }
+
     const DELTA: f64 = 0.00001;
+    const DELTA_F32: f32 = 0.0001;
 
     use std::{f32::consts::PI};
-    use glmath::glmath::{*, quat::Quat, vec3::Vec3};
+    use glmath::glmath::{*, quat::Quat, vec2::Vec2, vec3::Vec3, vec4::Vec4, mat44::Mat44, frustum::{Frustum, Containment}, collision::*, bvh::Bvh,
+        spatial_grid::SpatialHashGrid, octree::Octree, kdtree::KdTree,
+        curves::*, quat_spline::QuatSpline,
+        animation::*, skeleton::{Skeleton, Pose}, ik::*, physics::*, solver::*, eigen::covariance, linear::Factorize,
+        sparse::*, roots::*, dual::*, interval::Interval, aabb3::Aabb3,
+        predicates::*};
+
+    /// Deterministic pseudo random numbers for tests comparing against brute force.
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn next(&mut self) -> f32 {
+            self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (self.0 >> 40) as f32 / (1u64 << 24) as f32
+        }
+
+        fn range(&mut self, min: f32, max: f32) -> f32 {
+            min + (max - min) * self.next()
+        }
+
+        fn vec3(&mut self, min: f32, max: f32) -> Vec3f {
+            Vec3f::new(self.range(min, max), self.range(min, max), self.range(min, max))
+        }
+    }
 
     #[test]
     fn test_vec2() {
@@ -62,6 +93,16 @@ mod tests {
         assert_eq!(Vec3f::new(-272.2, -404.0, 309.2), cross);
 
         assert_eq!(Vec3f::ZERO % Vec3f::ZERO, Vec3f::ZERO);
+
+        // Perpendicular unit vectors, and angles between parallel vectors.
+        for v in [Vec3f::X, Vec3f::Y, Vec3f::Z, Vec3f::new(0.3, -2.0, 0.01), Vec3f::new(-5.0, 5.0, 5.0)] {
+            let perpendicular = v.any_perpendicular();
+            assert_near!(perpendicular * v, 0.0, DELTA_F32);
+            assert_near!(perpendicular.length(), 1.0, DELTA_F32);
+        }
+        let v = Vec3f::new(0.1, 0.7, 0.3);
+        assert_near!(v.angle_between(&(v * 3.0)), 0.0, 0.001);
+        assert_near!(v.angle_between(&-v), PI, 0.001);
     }
 
     #[test]
@@ -125,6 +166,20 @@ mod tests {
         assert_delta!(q.y, q2.y, DELTA);
         assert_delta!(q.z, q2.z, DELTA);
         assert_delta!(q.w, q2.w, DELTA);
+
+        // Rotating a vector agrees with the rotation matrix of a Mat44.
+        let q = Quatf::from_axis_angle(Vec3f::Z, PI / 2.0);
+        assert_near!((q * Vec3f::X - Vec3f::Y).length(), 0.0, DELTA_F32);
+
+        let mat = Mat44f::IDENTITY.get_rotated(Vec3f::Z, PI / 2.0);
+        let q2 = Quatf::from_matrix(&mat);
+        assert_near!((q2 - q).length(), 0.0, DELTA_F32);
+
+        // Compound assignment matches the product.
+        let r = Quatf::from_axis_angle(Vec3f::new(1.0, 2.0, -0.5).get_normalized(), 0.7);
+        let mut compound = q;
+        compound *= r;
+        assert_near!((compound - q * r).length(), 0.0, DELTA_F32);
     }
 
     #[test]
@@ -205,4 +260,2296 @@ mod tests {
         // Idt * scale matrix = scale matrix.
         assert_eq!(Mat44f::IDENTITY * scale_mat, scale_mat);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_ray3() {
+        let ray = Ray3f::new(Vec3f::new(0.0, 0.0, -5.0), Vec3f::Z);
+        assert_eq!(ray.at(2.0), Vec3f::new(0.0, 0.0, -3.0));
+
+        // Slab test hits the front face, and the back face from the inside.
+        let aabb = Aabb3f::new(Vec3f::new(-1.0, -1.0, -1.0), Vec3f::new(1.0, 1.0, 1.0));
+        let hit = ray.intersect_aabb(&aabb).unwrap();
+        assert_eq!(hit.distance, 4.0);
+        assert_eq!(hit.normal, -Vec3f::Z);
+
+        let inside = Ray3f::new(Vec3f::ZERO, Vec3f::X);
+        let hit = inside.intersect_aabb(&aabb).unwrap();
+        assert_eq!(hit.distance, 1.0);
+        assert_eq!(hit.normal, Vec3f::X);
+
+        // Axis parallel rays outside of a slab miss.
+        let parallel = Ray3f::new(Vec3f::new(2.0, 0.0, -5.0), Vec3f::Z);
+        assert!(parallel.intersect_aabb(&aabb).is_none());
+        assert!(Ray3f::new(ray.origin, -ray.dir).intersect_aabb(&aabb).is_none());
+
+        // Spheres.
+        let hit = ray.intersect_sphere(Vec3f::ZERO, 2.0).unwrap();
+        assert_near!(hit.distance, 3.0, DELTA_F32);
+        assert_eq!(hit.normal, -Vec3f::Z);
+        assert!(parallel.intersect_sphere(Vec3f::ZERO, 1.0).is_none());
+
+        // Planes and discs.
+        let hit = ray.intersect_plane(Vec3f::Z, -1.0).unwrap();
+        assert_eq!(hit.distance, 6.0);
+        assert!(ray.intersect_disc(Vec3f::new(3.0, 0.0, 0.0), Vec3f::Z, 2.0).is_none());
+        assert_eq!(ray.intersect_disc(Vec3f::new(1.0, 0.0, 0.0), Vec3f::Z, 2.0).unwrap().distance, 5.0);
+
+        // Both triangle tests agree on distance and barycentrics.
+        let a = Vec3f::new(-1.0, -1.0, 0.0);
+        let b = Vec3f::new(1.0, -1.0, 0.0);
+        let c = Vec3f::new(0.0, 1.0, 0.0);
+        let mt = ray.intersect_triangle(a, b, c).unwrap();
+        let wt = ray.intersect_triangle_watertight(a, b, c).unwrap();
+        assert_near!(mt.distance, 5.0, DELTA_F32);
+        assert_near!(wt.distance, 5.0, DELTA_F32);
+        assert_near!((mt.barycentric - wt.barycentric).length(), 0.0, DELTA_F32);
+        assert_near!(mt.barycentric.x + mt.barycentric.y + mt.barycentric.z, 1.0, DELTA_F32);
+        assert_eq!(mt.normal, Vec3f::Z);
+        assert!(parallel.intersect_triangle(a, b, c).is_none());
+        assert!(parallel.intersect_triangle_watertight(a, b, c).is_none());
+
+        // Tiny triangles are hit by both tests.
+        let scale = 0.0001;
+        let hit = ray.intersect_triangle(a * scale, b * scale, c * scale).unwrap();
+        assert_near!(hit.distance, 5.0, DELTA_F32);
+        assert!(ray.intersect_triangle_watertight(a * scale, b * scale, c * scale).is_some());
+        assert_near!(ray.intersect_plane(Vec3f::Z * (scale * scale), -scale * scale).unwrap().distance, 6.0, DELTA_F32);
+
+        // A ray through a shared edge hits at least one of the triangles.
+        let d = Vec3f::new(0.0, -3.0, 0.0);
+        let edge_ray = Ray3f::new(Vec3f::new(0.0, -1.0, -5.0), Vec3f::Z);
+        assert!(edge_ray.intersect_triangle_watertight(a, b, c).is_some() ||
+            edge_ray.intersect_triangle_watertight(b, a, d).is_some());
+
+        // Capsules are hit on the body and on the caps.
+        let hit = ray.intersect_capsule(Vec3f::new(-2.0, 0.0, 0.0), Vec3f::new(2.0, 0.0, 0.0), 1.0).unwrap();
+        assert_near!(hit.distance, 4.0, DELTA_F32);
+        assert_near!((hit.normal + Vec3f::Z).length(), 0.0, DELTA_F32);
+
+        let along = Ray3f::new(Vec3f::new(-5.0, 0.0, 0.0), Vec3f::X);
+        let hit = along.intersect_capsule(Vec3f::new(-2.0, 0.0, 0.0), Vec3f::new(2.0, 0.0, 0.0), 1.0).unwrap();
+        assert_near!(hit.distance, 2.0, DELTA_F32);
+        assert_near!((hit.normal + Vec3f::X).length(), 0.0, DELTA_F32);
+        assert!(parallel.intersect_capsule(Vec3f::new(-0.5, 0.0, 0.0), Vec3f::new(0.5, 0.0, 0.0), 1.0).is_none());
+
+        // The body of a tiny capsule is still hit.
+        let scale = 5e-5;
+        let tiny_ray = Ray3f::new(Vec3f::new(0.0, 0.0, -5.0 * scale), Vec3f::Z);
+        let hit = tiny_ray.intersect_capsule(Vec3f::new(-2.0 * scale, 0.0, 0.0), Vec3f::new(2.0 * scale, 0.0, 0.0), scale).unwrap();
+        assert_near!(hit.distance / scale, 4.0, 0.001);
+
+        // Transforming into local space keeps the distances.
+        let model = Mat44f::IDENTITY.get_translated(Vec3f::new(10.0, 0.0, 0.0));
+        let world_ray = Ray3f::new(Vec3f::new(10.0, 0.0, -5.0), Vec3f::Z);
+        let local_ray = world_ray.transformed(&model.get_inverted());
+        assert_eq!(local_ray, ray);
+        assert_eq!(local_ray.intersect_aabb(&aabb).unwrap().distance, 4.0);
+    }
+
+    #[test]
+    fn test_plane() {
+        let plane = Planef::from_point_normal(Vec3f::new(0.0, 2.0, 0.0), Vec3f::new(0.0, 2.0, 0.0));
+        let plane = plane.get_normalized();
+        assert_eq!(plane, Planef::new(Vec3f::Y, -2.0));
+
+        assert_eq!(plane.signed_distance(Vec3f::new(1.0, 5.0, 1.0)), 3.0);
+        assert_eq!(plane.signed_distance(Vec3f::ZERO), -2.0);
+        assert_eq!(plane.project_point(Vec3f::new(1.0, 5.0, 1.0)), Vec3f::new(1.0, 2.0, 1.0));
+
+        assert_eq!(plane.intersect_line(Vec3f::ZERO, Vec3f::new(1.0, 1.0, 0.0)), Some(Vec3f::new(2.0, 2.0, 0.0)));
+        assert_eq!(plane.intersect_line(Vec3f::ZERO, Vec3f::X), None);
+        assert_eq!(plane.intersect_segment(Vec3f::ZERO, Vec3f::Y), None);
+        assert_eq!(plane.intersect_segment(Vec3f::ZERO, Vec3f::Y * 4.0), Some(Vec3f::Y * 2.0));
+
+        let from_points = Planef::from_points(Vec3f::ZERO, Vec3f::X, Vec3f::Y);
+        assert_eq!(from_points.normal, Vec3f::Z);
+
+        let corner = Planef::intersect_planes(&plane, &Planef::new(Vec3f::X, -1.0), &Planef::new(Vec3f::Z, 3.0));
+        assert_eq!(corner, Some(Vec3f::new(1.0, 2.0, -3.0)));
+
+        // Parallel checks do not depend on the length of the normals.
+        let tiny = Planef::new(Vec3f::Y * 1e-8, -2e-8);
+        let hit = tiny.intersect_line(Vec3f::ZERO, Vec3f::new(1.0, 1.0, 0.0)).unwrap();
+        assert!((hit - Vec3f::new(2.0, 2.0, 0.0)).length() < DELTA_F32);
+        let scaled = |p: Planef, s: f32| Planef::new(p.normal * s, p.d * s);
+        let corner = Planef::intersect_planes(&scaled(plane, 1e-3), &Planef::new(Vec3f::X * 1e-3, -1e-3), &Planef::new(Vec3f::Z * 1e-3, 3e-3));
+        assert!((corner.unwrap() - Vec3f::new(1.0, 2.0, -3.0)).length() < DELTA_F32);
+        let huge = Planef::new(Vec3f::Y * 1e4, -2e4);
+        assert_eq!(huge.intersect_line(Vec3f::ZERO, Vec3f::new(1.0, 1e-12, 0.0)), None);
+    }
+
+    #[test]
+    fn test_frustum() {
+        // OpenGL style perspective with a 90 degree field of view looking down -z.
+        let (near, far) = (1.0, 100.0);
+        let mut proj = Mat44f::ZERO;
+        proj.data[0][0] = 1.0;
+        proj.data[1][1] = 1.0;
+        proj.data[2][2] = (far + near) / (near - far);
+        proj.data[2][3] = -1.0;
+        proj.data[3][2] = 2.0 * far * near / (near - far);
+
+        let frustum = Frustumf::from_view_projection(&proj);
+        assert_near!(frustum.planes[Frustumf::NEAR].signed_distance(Vec3f::new(0.0, 0.0, -2.0)), 1.0, DELTA_F32);
+        assert_near!((frustum.corners.unwrap()[7] - Vec3f::new(100.0, 100.0, -100.0)).length(), 0.0, 0.01);
+
+        assert_eq!(frustum.test_point(Vec3f::new(0.0, 0.0, -10.0)), Containment::Inside);
+        assert_eq!(frustum.test_point(Vec3f::new(0.0, 0.0, 10.0)), Containment::Outside);
+        assert_eq!(frustum.test_point(Vec3f::new(0.0, 0.0, -0.5)), Containment::Outside);
+        assert_eq!(frustum.test_point(Vec3f::new(20.0, 0.0, -10.0)), Containment::Outside);
+
+        assert_eq!(frustum.test_sphere(Vec3f::new(0.0, 0.0, -10.0), 1.0), Containment::Inside);
+        assert_eq!(frustum.test_sphere(Vec3f::new(10.5, 0.0, -10.0), 1.0), Containment::Intersect);
+        assert_eq!(frustum.test_sphere(Vec3f::new(0.0, 0.0, 10.0), 1.0), Containment::Outside);
+
+        let inside = Aabb3f::new(Vec3f::new(-1.0, -1.0, -11.0), Vec3f::new(1.0, 1.0, -9.0));
+        let straddling = Aabb3f::new(Vec3f::new(5.0, -1.0, -11.0), Vec3f::new(15.0, 1.0, -9.0));
+        let behind = Aabb3f::new(Vec3f::new(-1.0, -1.0, 1.0), Vec3f::new(1.0, 1.0, 2.0));
+        assert_eq!(frustum.test_aabb(&inside), Containment::Inside);
+        assert_eq!(frustum.test_aabb(&straddling), Containment::Intersect);
+        assert_eq!(frustum.test_aabb(&behind), Containment::Outside);
+        assert_eq!(frustum.test_aabb_exact(&straddling), Containment::Intersect);
+
+        // A box past the far corner straddles the far, right and top planes
+        // without touching the frustum. Only the exact test rejects it.
+        let corner = Aabb3f::new(Vec3f::new(101.0, 101.0, -120.0), Vec3f::new(120.0, 120.0, -95.0));
+        assert_eq!(frustum.test_aabb(&corner), Containment::Intersect);
+        assert_eq!(frustum.test_aabb_exact(&corner), Containment::Outside);
+
+        // The exact tests agree with GJK against the frustum's corners for a
+        // rotated frustum, skipping shapes that are within rounding of touching.
+        let to_f64 = |m: &Mat44f| Mat44::<f64> { data: m.data.map(|column| column.map(|x| x as f64)) };
+        let view = Mat44f::IDENTITY.get_rotated(Vec3f::new(0.3, 1.0, 0.2).get_normalized(), 0.7);
+        let frustum = Frustum::<f64>::from_view_projection(&(to_f64(&proj) * to_f64(&view)));
+        let hull = ConvexHull::<f64>::new(frustum.corners.unwrap().to_vec());
+
+        let mut rng = Lcg(5);
+        let (mut culled_boxes, mut culled_spheres) = (0, 0);
+        for _ in 0..2000 {
+            let center = rng.vec3(-120.0, 120.0);
+            let center = Vec3::<f64>::new(center.x as f64, center.y as f64, center.z as f64);
+            let size = rng.range(1.0, 30.0) as f64;
+
+            let grown = |scale: f64| {
+                let half = Vec3::<f64>::new(size, size * 0.5, size * 2.0) * scale;
+                Aabb3::<f64>::new(center - half, center + half)
+            };
+            let touching = gjk_intersect(&hull, &grown(1.001));
+            if touching == gjk_intersect(&hull, &grown(0.999)) {
+                let exact = frustum.test_aabb_exact(&grown(1.0));
+                assert_eq!(exact != Containment::Outside, touching);
+                if frustum.test_aabb(&grown(1.0)) == Containment::Intersect && !touching {
+                    culled_boxes += 1;
+                }
+            }
+
+            let touching = gjk_intersect(&hull, &Sphere::<f64>::new(center, size * 1.001));
+            if touching == gjk_intersect(&hull, &Sphere::<f64>::new(center, size * 0.999)) {
+                assert_eq!(frustum.test_sphere_exact(center, size) != Containment::Outside, touching);
+                if frustum.test_sphere(center, size) == Containment::Intersect && !touching {
+                    culled_spheres += 1;
+                }
+            }
+        }
+        assert!(culled_boxes > 0 && culled_spheres > 0);
+
+        // Without a far plane there are no corners, and the exact tests fall
+        // back to the planes instead of culling distant objects.
+        let mut infinite = proj;
+        infinite.data[2][2] = -1.0;
+        infinite.data[3][2] = -2.0 * near;
+        let frustum = Frustumf::from_view_projection(&infinite);
+        assert!(frustum.corners.is_none());
+        assert_eq!(frustum.test_point(Vec3f::new(0.0, 0.0, -1e6)), Containment::Inside);
+
+        let distant = Aabb3f::new(Vec3f::new(9990.0, -1.0, -10001.0), Vec3f::new(10010.0, 1.0, -9999.0));
+        assert_eq!(frustum.test_aabb_exact(&distant), Containment::Intersect);
+        assert_eq!(frustum.test_sphere_exact(Vec3f::new(10005.0, 0.0, -10000.0), 10.0), Containment::Intersect);
+    }
+
+    #[test]
+    fn test_collision() {
+        // Spheres.
+        let a = Spheref::new(Vec3f::ZERO, 1.0);
+        let b = Spheref::new(Vec3f::new(1.5, 0.0, 0.0), 1.0);
+        let contact = a.collide_sphere(&b).unwrap();
+        assert_eq!(contact.normal, Vec3f::X);
+        assert_eq!(contact.depth, 0.5);
+        assert_eq!(contact.point, Vec3f::new(0.75, 0.0, 0.0));
+        assert!(a.collide_sphere(&Spheref::new(Vec3f::new(3.0, 0.0, 0.0), 1.0)).is_none());
+
+        // Sphere against a box, outside and inside the box.
+        let aabb = Aabb3f::new(Vec3f::new(0.5, -1.0, -1.0), Vec3f::new(2.5, 1.0, 1.0));
+        let contact = a.collide_aabb(&aabb).unwrap();
+        assert_eq!(contact.normal, Vec3f::X);
+        assert_eq!(contact.depth, 0.5);
+        let contact = b.collide_aabb(&aabb).unwrap();
+        assert_eq!(contact.normal, -Vec3f::X);
+        assert_eq!(contact.depth, 2.0);
+        assert!(Spheref::new(Vec3f::new(-1.0, 0.0, 0.0), 1.0).collide_aabb(&aabb).is_none());
+
+        // Boxes, one rotated 45 degrees so its edge points at the other.
+        let box_a = Obbf::new(Vec3f::ZERO, Vec3f::new(1.0, 1.0, 1.0), Mat33f::IDENTITY);
+        let rotation = Quatf::from_axis_angle(Vec3f::Z, PI / 4.0);
+        let box_b = Obbf::from_quat(Vec3f::new(2.3, 0.0, 0.0), Vec3f::new(1.0, 1.0, 1.0), rotation);
+        let contact = box_a.collide_obb(&box_b).unwrap();
+        assert_near!((contact.normal - Vec3f::X).length(), 0.0, DELTA_F32);
+        assert_near!(contact.depth, 2.0f32.sqrt() - 1.3, DELTA_F32);
+        let box_c = Obbf::from_quat(Vec3f::new(2.5, 0.0, 0.0), Vec3f::new(1.0, 1.0, 1.0), rotation);
+        assert!(box_a.collide_obb(&box_c).is_none());
+
+        // Capsules lying across each other.
+        let cap_a = Capsulef::new(Vec3f::new(-1.0, 0.0, 0.0), Vec3f::new(1.0, 0.0, 0.0), 0.5);
+        let cap_b = Capsulef::new(Vec3f::new(0.0, -1.0, 0.8), Vec3f::new(0.0, 1.0, 0.8), 0.5);
+        let contact = cap_a.collide_capsule(&cap_b).unwrap();
+        assert_near!((contact.normal - Vec3f::Z).length(), 0.0, DELTA_F32);
+        assert_near!(contact.depth, 0.2, DELTA_F32);
+        let cap_c = Capsulef::new(Vec3f::new(3.0, -1.0, 0.0), Vec3f::new(3.0, 1.0, 0.0), 0.5);
+        assert!(cap_a.collide_capsule(&cap_c).is_none());
+
+        // GJK and EPA agree with the dedicated tests.
+        assert!(gjk_intersect(&a, &b));
+        assert!(!gjk_intersect(&a, &Spheref::new(Vec3f::new(3.0, 0.0, 0.0), 1.0)));
+        assert!(gjk_intersect(&box_a, &box_b));
+        assert!(!gjk_intersect(&box_a, &box_c));
+
+        let contact = collide_convex(&box_a, &box_b).unwrap();
+        assert_near!((contact.normal - Vec3f::X).length(), 0.0, 0.001);
+        assert_near!(contact.depth, 2.0f32.sqrt() - 1.3, 0.001);
+
+        // EPA approximates curved shapes with a polytope, so the normal is rougher.
+        let contact = collide_convex(&a, &b).unwrap();
+        assert_near!((contact.normal - Vec3f::X).length(), 0.0, 0.05);
+        assert_near!(contact.depth, 0.5, 0.01);
+
+        // Arbitrary point clouds.
+        let hull = ConvexHull::new(vec![
+            Vec3f::new(0.0, 0.0, 0.0),
+            Vec3f::new(-1.0, 1.0, 0.0),
+            Vec3f::new(-1.0, -1.0, 1.0),
+            Vec3f::new(-1.0, -1.0, -1.0)
+        ]);
+        assert!(collide_convex(&hull, &aabb).is_none());
+        let shifted = Aabb3f::new(Vec3f::new(-0.25, -1.0, -1.0), Vec3f::new(2.0, 1.0, 1.0));
+        let contact = collide_convex(&hull, &shifted).unwrap();
+        assert_near!((contact.normal - Vec3f::X).length(), 0.0, 0.001);
+        assert_near!(contact.depth, 0.25, 0.001);
+        assert!(std::panic::catch_unwind(|| ConvexHull::<f32>::new(vec![])).is_err());
+        assert_eq!(hull.points().len(), 4);
+    }
+
+    #[test]
+    fn test_closest() {
+        // Point and segment, interior and clamped to an end.
+        let result = closest::point_segment(Vec3f::new(1.0, 2.0, 0.0), Vec3f::ZERO, Vec3f::new(4.0, 0.0, 0.0));
+        assert_eq!(result.on_b, Vec3f::new(1.0, 0.0, 0.0));
+        assert_eq!(result.distance_sq, 4.0);
+        let result = closest::point_segment(Vec3f::new(-3.0, 0.0, 0.0), Vec3f::ZERO, Vec3f::new(4.0, 0.0, 0.0));
+        assert_eq!(result.on_b, Vec3f::ZERO);
+
+        // Skew segments, parallel segments and degenerate segments.
+        let result = closest::segment_segment(
+            Vec3f::new(-1.0, 0.0, 0.0), Vec3f::new(1.0, 0.0, 0.0),
+            Vec3f::new(0.5, -1.0, 2.0), Vec3f::new(0.5, 1.0, 2.0));
+        assert_eq!(result.on_a, Vec3f::new(0.5, 0.0, 0.0));
+        assert_eq!(result.on_b, Vec3f::new(0.5, 0.0, 2.0));
+        assert_eq!(result.distance_sq, 4.0);
+        let result = closest::segment_segment(
+            Vec3f::ZERO, Vec3f::new(1.0, 0.0, 0.0),
+            Vec3f::new(3.0, 1.0, 0.0), Vec3f::new(5.0, 1.0, 0.0));
+        assert_eq!(result.on_a, Vec3f::new(1.0, 0.0, 0.0));
+        assert_eq!(result.on_b, Vec3f::new(3.0, 1.0, 0.0));
+        let result = closest::segment_segment(Vec3f::ZERO, Vec3f::ZERO, Vec3f::Y, Vec3f::Y);
+        assert_eq!(result.distance_sq, 1.0);
+
+        // Point and triangle in the face, edge and vertex regions.
+        let (a, b, c) = (Vec3f::ZERO, Vec3f::new(2.0, 0.0, 0.0), Vec3f::new(0.0, 2.0, 0.0));
+        assert_eq!(closest::point_triangle(Vec3f::new(0.5, 0.5, 3.0), a, b, c).on_b, Vec3f::new(0.5, 0.5, 0.0));
+        assert_eq!(closest::point_triangle(Vec3f::new(1.0, -1.0, 0.0), a, b, c).on_b, Vec3f::new(1.0, 0.0, 0.0));
+        assert_eq!(closest::point_triangle(Vec3f::new(2.0, 2.0, 0.0), a, b, c).on_b, Vec3f::new(1.0, 1.0, 0.0));
+        assert_eq!(closest::point_triangle(Vec3f::new(-1.0, -1.0, 1.0), a, b, c).on_b, a);
+        assert_eq!(closest::point_triangle(Vec3f::new(3.0, -1.0, 0.0), a, b, c).on_b, b);
+        assert_eq!(closest::point_triangle(Vec3f::new(-1.0, 3.0, 0.0), a, b, c).on_b, c);
+
+        // Point and rotated box.
+        let obb = Obbf::from_quat(Vec3f::ZERO, Vec3f::new(1.0, 1.0, 1.0), Quatf::from_axis_angle(Vec3f::Z, PI / 4.0));
+        let result = closest::point_obb(Vec3f::new(3.0, 0.0, 0.0), &obb);
+        assert_near!(result.on_b.x, 2.0f32.sqrt(), DELTA_F32);
+        assert_near!(result.on_b.y, 0.0, DELTA_F32);
+        assert_eq!(closest::point_obb(Vec3f::new(0.5, 0.0, 0.0), &obb).distance_sq, 0.0);
+
+        // Separated, edge to edge and intersecting triangles.
+        let tri = [a, b, c];
+        let above = [Vec3f::new(0.0, 0.0, 1.0), Vec3f::new(2.0, 0.0, 1.0), Vec3f::new(0.0, 2.0, 1.0)];
+        assert_eq!(closest::triangle_triangle(&tri, &above).distance_sq, 1.0);
+
+        let crossing = [Vec3f::new(3.0, -1.0, 1.0), Vec3f::new(3.0, 1.0, 1.0), Vec3f::new(3.0, 0.0, 3.0)];
+        let result = closest::triangle_triangle(&tri, &crossing);
+        assert_near!(result.distance_sq, 2.0, DELTA_F32);
+        assert_eq!(result.on_a, b);
+
+        let piercing = [Vec3f::new(0.5, 0.5, -1.0), Vec3f::new(0.5, 0.5, 1.0), Vec3f::new(5.0, 5.0, 0.0)];
+        let result = closest::triangle_triangle(&tri, &piercing);
+        assert_eq!(result.distance_sq, 0.0);
+    }
+
+    #[test]
+    fn test_bvh() {
+        let mut rng = Lcg(7);
+        let spheres: Vec<Spheref> = (0..200).map(|_| Spheref::new(rng.vec3(-20.0, 20.0), rng.range(0.2, 1.5))).collect();
+        let bounds_of = |spheres: &[Spheref]| -> Vec<Aabb3f> {
+            spheres.iter().map(|s| {
+                let r = Vec3f::new(s.radius, s.radius, s.radius);
+                Aabb3f::new(s.center - r, s.center + r)
+            }).collect()
+        };
+
+        let bounds = bounds_of(&spheres);
+        let trees = [Bvh::build_sah(&bounds), Bvh::build_binned(&bounds, 12)];
+
+        for bvh in &trees {
+            // Every primitive appears in exactly one leaf.
+            let mut seen = vec![0; spheres.len()];
+            for node in bvh.nodes.iter().filter(|n| n.is_leaf()) {
+                for index in &bvh.indices[node.offset..node.offset + node.count] {
+                    seen[*index] += 1;
+                    assert!(node.bounds.overlaps(&bounds[*index]));
+                }
+            }
+            assert!(seen.iter().all(|count| *count == 1));
+
+            // Closest and any hit queries agree with brute force.
+            for _ in 0..50 {
+                let ray = Ray3f::new(rng.vec3(-30.0, 30.0), rng.vec3(-1.0, 1.0));
+                let hit = |index: usize| ray.intersect_sphere(spheres[index].center, spheres[index].radius).map(|h| h.distance);
+
+                let expected = (0..spheres.len())
+                    .filter_map(|i| hit(i).map(|d| (i, d)))
+                    .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+
+                assert_eq!(bvh.closest_hit(&ray, hit), expected);
+                assert_eq!(bvh.any_hit(&ray, f32::MAX, hit).is_some(), expected.is_some());
+
+                if let Some((_, dist)) = expected {
+                    assert!(bvh.any_hit(&ray, dist * 0.5, hit).is_none_or(|i| hit(i).unwrap() <= dist * 0.5));
+                }
+            }
+
+            // Box queries report a superset of the overlapping primitives.
+            let query = Aabb3f::new(Vec3f::new(-5.0, -5.0, -5.0), Vec3f::new(5.0, 8.0, 5.0));
+            let mut found = Vec::new();
+            bvh.query_aabb(&query, |index| found.push(index));
+            let mut found: Vec<usize> = found.into_iter().filter(|i| bounds[*i].overlaps(&query)).collect();
+            found.sort();
+            let expected: Vec<usize> = (0..bounds.len()).filter(|i| bounds[*i].overlaps(&query)).collect();
+            assert_eq!(found, expected);
+
+            // A frustum that contains everything reports everything.
+            let frustum = Frustumf::from_planes([
+                Planef::new(Vec3f::X, 100.0), Planef::new(-Vec3f::X, 100.0),
+                Planef::new(Vec3f::Y, 100.0), Planef::new(-Vec3f::Y, 100.0),
+                Planef::new(Vec3f::Z, 100.0), Planef::new(-Vec3f::Z, 100.0)
+            ]);
+            let mut count = 0;
+            bvh.query_frustum(&frustum, |_| count += 1);
+            assert_eq!(count, spheres.len());
+
+            // A frustum holding only the positive x half rejects most of the rest.
+            let half = Frustumf::from_planes([
+                Planef::new(Vec3f::X, 0.0), Planef::new(-Vec3f::X, 100.0),
+                Planef::new(Vec3f::Y, 100.0), Planef::new(-Vec3f::Y, 100.0),
+                Planef::new(Vec3f::Z, 100.0), Planef::new(-Vec3f::Z, 100.0)
+            ]);
+            let mut found = Vec::new();
+            bvh.query_frustum(&half, |index| found.push(index));
+            assert!(found.len() < spheres.len());
+            assert!((0..bounds.len()).filter(|i| bounds[*i].max.x >= 0.0).all(|i| found.contains(&i)));
+        }
+
+        // Refitting after moving the primitives keeps queries exact.
+        let mut bvh = trees[0].clone();
+        let moved: Vec<Spheref> = spheres.iter().map(|s| Spheref::new(s.center + Vec3f::new(3.0, -2.0, 1.0), s.radius)).collect();
+        let moved_bounds = bounds_of(&moved);
+        bvh.refit(&moved_bounds);
+        assert!(bvh.nodes[0].bounds.min.x >= bounds.iter().map(|b| b.min.x).fold(f32::MAX, f32::min) + 2.999);
+
+        for _ in 0..20 {
+            let ray = Ray3f::new(rng.vec3(-30.0, 30.0), rng.vec3(-1.0, 1.0));
+            let hit = |index: usize| ray.intersect_sphere(moved[index].center, moved[index].radius).map(|h| h.distance);
+            let expected = (0..moved.len())
+                .filter_map(|i| hit(i).map(|d| (i, d)))
+                .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+            assert_eq!(bvh.closest_hit(&ray, hit), expected);
+        }
+    }
+
+    #[test]
+    fn test_spatial() {
+        let mut rng = Lcg(31);
+        let world = Aabb3f::new(Vec3f::new(-50.0, -50.0, -50.0), Vec3f::new(50.0, 50.0, 50.0));
+
+        let mut bounds: Vec<Aabb3f> = (0..300).map(|i| {
+            let center = rng.vec3(-50.0, 50.0);
+            // Mix points with boxes of varying size.
+            let extent = if i % 3 == 0 { Vec3f::ZERO } else { rng.vec3(0.0, 4.0) };
+            Aabb3f::new(center - extent, center + extent)
+        }).collect();
+
+        let mut grid = SpatialHashGrid::<f32>::new(5.0);
+        let mut tree = Octree::<f32>::new(&world, 6);
+
+        for (id, b) in bounds.iter().enumerate() {
+            grid.insert(id, *b);
+            tree.insert(id, *b);
+        }
+
+        assert_eq!(grid.cell_of(Vec3f::new(-0.1, 4.9, 5.0)), [-1, 0, 1]);
+        assert_eq!(grid.len(), 300);
+        assert_eq!(tree.len(), 300);
+
+        let check = |grid: &SpatialHashGrid<f32>, tree: &Octree<f32>, bounds: &[Aabb3f], rng: &mut Lcg| {
+            let live: Vec<usize> = (0..bounds.len()).filter(|id| grid.bounds(*id).is_some()).collect();
+
+            for _ in 0..30 {
+                let center = rng.vec3(-60.0, 60.0);
+                let radius = rng.range(0.0, 20.0);
+
+                let expected: Vec<usize> = live.iter().copied()
+                    .filter(|id| bounds[*id].distance_sq(center) <= radius * radius)
+                    .collect();
+
+                let mut found = Vec::new();
+                grid.query_radius(center, radius, |id| found.push(id));
+                found.sort();
+                assert_eq!(found, expected);
+
+                let mut found = Vec::new();
+                tree.query_radius(center, radius, |id| found.push(id));
+                found.sort();
+                assert_eq!(found, expected);
+
+                let query = Aabb3f::new(center - Vec3f::new(radius, 3.0, 7.0), center + Vec3f::new(2.0, radius, 1.0));
+                let mut found = Vec::new();
+                grid.query_aabb(&query, |id| found.push(id));
+                found.sort();
+                let expected: Vec<usize> = live.iter().copied().filter(|id| bounds[*id].overlaps(&query)).collect();
+                assert_eq!(found, expected);
+
+                // Ties in distance may be ordered differently, so compare distances.
+                let mut expected: Vec<f32> = live.iter().map(|id| bounds[*id].distance_sq(center)).collect();
+                expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                expected.truncate(7);
+                let nearest = tree.k_nearest(center, 7);
+                assert_eq!(nearest.iter().map(|n| n.1).collect::<Vec<f32>>(), expected);
+                assert!(nearest.iter().all(|(id, dist)| bounds[*id].distance_sq(center) == *dist));
+            }
+
+            let mut pairs = Vec::new();
+            grid.overlapping_pairs(|a, b| pairs.push((a, b)));
+            pairs.sort();
+            let mut expected = Vec::new();
+            for (i, a) in live.iter().enumerate() {
+                for b in &live[i + 1..] {
+                    if bounds[*a].overlaps(&bounds[*b]) {
+                        expected.push((*a, *b));
+                    }
+                }
+            }
+            assert_eq!(pairs, expected);
+
+            let frustum = Frustumf::from_planes([
+                Planef::new(Vec3f::new(1.0, 0.2, 0.0).get_normalized(), 10.0), Planef::new(-Vec3f::X, 30.0),
+                Planef::new(Vec3f::Y, 25.0), Planef::new(-Vec3f::Y, 25.0),
+                Planef::new(Vec3f::new(0.0, 0.3, 1.0).get_normalized(), 5.0), Planef::new(-Vec3f::Z, 40.0)
+            ]);
+            let mut found = Vec::new();
+            tree.query_frustum(&frustum, |id| found.push(id));
+            found.sort();
+            let expected: Vec<usize> = live.iter().copied()
+                .filter(|id| frustum.test_aabb(&bounds[*id]) != Containment::Outside)
+                .collect();
+            assert_eq!(found, expected);
+        };
+
+        check(&grid, &tree, &bounds, &mut rng);
+
+        // Move a third of the items, some outside of the octree's bounds, and remove another third.
+        for (id, b) in bounds.iter_mut().enumerate() {
+            if id % 3 == 1 {
+                let offset = rng.vec3(-30.0, 30.0);
+                *b = Aabb3f::new(b.min + offset, b.max + offset);
+                assert!(grid.move_item(id, *b));
+                assert!(tree.move_item(id, *b));
+            }
+            else if id % 3 == 2 {
+                assert!(grid.remove(id));
+                assert!(tree.remove(id));
+            }
+        }
+
+        assert!(!grid.remove(2));
+        assert!(!tree.move_item(2, world));
+        assert_eq!(grid.len(), 200);
+        assert_eq!(tree.len(), 200);
+
+        check(&grid, &tree, &bounds, &mut rng);
+
+        // Items centered outside of a small root, or larger than it, are
+        // kept in the root and still found by every query.
+        let mut tree = Octree::<f32>::new(&Aabb3f::new(Vec3f::new(-1.0, -1.0, -1.0), Vec3f::new(1.0, 1.0, 1.0)), 4);
+        let bounds: Vec<Aabb3f> = (0..60).map(|i| {
+            let center = rng.vec3(-60.0, 60.0);
+            let extent = match i % 3 {
+                0 => Vec3f::ZERO,
+                1 => rng.vec3(0.0, 0.5),
+                _ => rng.vec3(0.0, 50.0)
+            };
+            let center = if i % 4 == 0 { center * 0.01 } else { center };
+            Aabb3f::new(center - extent, center + extent)
+        }).collect();
+        for (id, b) in bounds.iter().enumerate() {
+            tree.insert(id, *b);
+        }
+        tree.insert_point(60, Vec3f::new(10.0, 0.0, 0.0));
+        tree.insert(61, Aabb3f::new(Vec3f::new(-50.0, -0.5, -0.5), Vec3f::new(50.0, 0.5, 0.5)));
+
+        let mut found = Vec::new();
+        tree.query_radius(Vec3f::new(10.0, 0.0, 0.0), 0.5, |id| found.push(id));
+        assert!(found.contains(&60) && found.contains(&61));
+        found.clear();
+        tree.query_radius(Vec3f::new(40.0, 0.0, 0.0), 0.5, |id| found.push(id));
+        assert!(found.contains(&61));
+
+        let mut bounds = bounds;
+        bounds.push(Aabb3f::new(Vec3f::new(10.0, 0.0, 0.0), Vec3f::new(10.0, 0.0, 0.0)));
+        bounds.push(tree.bounds(61).unwrap());
+        let frustum = Frustumf::from_planes([
+            Planef::new(Vec3f::X, -5.0), Planef::new(-Vec3f::X, 45.0),
+            Planef::new(Vec3f::Y, 2.0), Planef::new(-Vec3f::Y, 2.0),
+            Planef::new(Vec3f::Z, 2.0), Planef::new(-Vec3f::Z, 2.0)
+        ]);
+
+        for _ in 0..30 {
+            let center = rng.vec3(-60.0, 60.0);
+            let radius = rng.range(0.0, 20.0);
+
+            let mut found = Vec::new();
+            tree.query_radius(center, radius, |id| found.push(id));
+            found.sort();
+            let expected: Vec<usize> = (0..bounds.len()).filter(|id| bounds[*id].distance_sq(center) <= radius * radius).collect();
+            assert_eq!(found, expected);
+
+            let mut expected: Vec<f32> = bounds.iter().map(|b| b.distance_sq(center)).collect();
+            expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            expected.truncate(5);
+            assert_eq!(tree.k_nearest(center, 5).iter().map(|n| n.1).collect::<Vec<f32>>(), expected);
+        }
+
+        let mut found = Vec::new();
+        tree.query_frustum(&frustum, |id| found.push(id));
+        found.sort();
+        let expected: Vec<usize> = (0..bounds.len()).filter(|id| frustum.test_aabb(&bounds[*id]) != Containment::Outside).collect();
+        assert!(expected.contains(&60) && expected.contains(&61));
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn test_kdtree() {
+        let mut rng = Lcg(32);
+        let points: Vec<Vec3f> = (0..500).map(|_| rng.vec3(-10.0, 10.0)).collect();
+        let tree = KdTree::build(&points);
+        assert_eq!(tree.len(), 500);
+
+        let sorted_distances = |query: Vec3f, metric: fn(Vec3f) -> f32| {
+            let mut dists: Vec<f32> = points.iter().map(|p| metric(*p - query)).collect();
+            dists.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            dists
+        };
+
+        for _ in 0..50 {
+            let query = rng.vec3(-12.0, 12.0);
+            let expected = sorted_distances(query, |d| d.length_sq());
+
+            let (index, dist) = tree.nearest(query).unwrap();
+            assert_eq!(dist, expected[0]);
+            assert_eq!((points[index] - query).length_sq(), dist);
+
+            let nearest = tree.k_nearest(query, 10);
+            assert_eq!(nearest.iter().map(|n| n.1).collect::<Vec<f32>>(), expected[..10].to_vec());
+
+            // Approximate results are within the error bound of the exact ones.
+            let approx = tree.k_nearest_approx(query, 10, 0.5);
+            assert_eq!(approx.len(), 10);
+            for (found, exact) in approx.iter().zip(&expected) {
+                assert!(found.1 <= exact * 2.25 + DELTA_F32);
+            }
+
+            let mut found = Vec::new();
+            tree.within_radius(query, 3.0, |index, dist| {
+                assert_eq!((points[index] - query).length_sq(), dist);
+                found.push(index);
+            });
+            found.sort();
+            let expected: Vec<usize> = (0..points.len()).filter(|i| (points[*i] - query).length_sq() <= 9.0).collect();
+            assert_eq!(found, expected);
+        }
+
+        // A weighted metric that treats distances along y as larger.
+        let weighted: fn(Vec3f) -> f32 = |d| d.x * d.x + 16.0 * d.y * d.y + d.z * d.z;
+        let tree = KdTree::with_metric(&points, weighted);
+        for _ in 0..20 {
+            let query = rng.vec3(-10.0, 10.0);
+            let nearest = tree.k_nearest(query, 5);
+            assert_eq!(nearest.iter().map(|n| n.1).collect::<Vec<f32>>(), sorted_distances(query, weighted)[..5].to_vec());
+        }
+
+        // Other dimensions.
+        let points2: Vec<Vec2f> = (0..200).map(|_| Vec2f::new(rng.range(0.0, 1.0), rng.range(0.0, 1.0))).collect();
+        let tree2 = KdTree::build(&points2);
+        let query2 = Vec2f::new(0.5, 0.5);
+        let best2 = points2.iter().map(|p| (*p - query2).length_sq()).fold(f32::MAX, f32::min);
+        assert_eq!(tree2.nearest(query2).unwrap().1, best2);
+
+        let points4: Vec<Vec4f> = (0..200).map(|_| {
+            let v = rng.vec3(-1.0, 1.0);
+            Vec4f::new(v.x, v.y, v.z, rng.range(-1.0, 1.0))
+        }).collect();
+        let tree4 = KdTree::build(&points4);
+        let query4 = Vec4f::new(0.1, -0.2, 0.3, 0.0);
+        let best4 = points4.iter().map(|p| (*p - query4).length_sq()).fold(f32::MAX, f32::min);
+        assert_eq!(tree4.nearest(query4).unwrap().1, best4);
+
+        let empty = KdTree::<f32, Vec3f>::build(&[]);
+        assert!(empty.nearest(Vec3f::ZERO).is_none());
+    }
+
+    #[test]
+    fn test_curves() {
+        let h = 0.001;
+        let bezier = CubicBezier::new(Vec3f::new(0.0, 0.0, 0.0), Vec3f::new(1.0, 3.0, -1.0),
+            Vec3f::new(3.0, -2.0, 2.0), Vec3f::new(4.0, 1.0, 0.0));
+
+        assert_eq!(bezier.evaluate(0.0), bezier.points[0]);
+        assert_eq!(bezier.evaluate(1.0), bezier.points[3]);
+
+        for t in [0.1, 0.35, 0.5, 0.8] {
+            let numeric = (bezier.evaluate(t + h) - bezier.evaluate(t - h)) * (0.5 / h);
+            assert!((bezier.derivative(t) - numeric).length() < 0.01);
+
+            let numeric = (bezier.derivative(t + h) - bezier.derivative(t - h)) * (0.5 / h);
+            assert!((bezier.second_derivative(t) - numeric).length() < 0.01);
+
+            // The halves of a split trace the original curve.
+            let (first, second) = bezier.split(t);
+            for s in [0.0, 0.3, 0.9] {
+                assert!((first.evaluate(s) - bezier.evaluate(s * t)).length() < DELTA_F32);
+                assert!((second.evaluate(s) - bezier.evaluate(t + s * (1.0 - t))).length() < DELTA_F32);
+            }
+        }
+
+        // Bounds contain every sample and are touched by the samples.
+        let (min, max) = bezier.bounds();
+        let samples: Vec<Vec3f> = (0..=1000).map(|i| bezier.evaluate(i as f32 / 1000.0)).collect();
+        for axis in 0..3 {
+            let lo = samples.iter().map(|p| p[axis]).fold(f32::MAX, f32::min);
+            let hi = samples.iter().map(|p| p[axis]).fold(f32::MIN, f32::max);
+            assert!(min[axis] <= lo && lo - min[axis] < 0.001);
+            assert!(max[axis] >= hi && max[axis] - hi < 0.001);
+        }
+
+        // Hermite and Bézier forms describe the same curve.
+        let hermite = bezier.to_hermite();
+        assert_eq!(hermite.to_bezier().points.iter().zip(&bezier.points).filter(|(a, b)| (**a - **b).length() > DELTA_F32).count(), 0);
+        let (left, right) = hermite.split(0.25);
+        for t in [0.0, 0.4, 1.0] {
+            assert!((hermite.evaluate(t) - bezier.evaluate(t)).length() < DELTA_F32);
+            assert!((hermite.derivative(t) - bezier.derivative(t)).length() < 0.001);
+            assert!((left.evaluate(t) - bezier.evaluate(t * 0.25)).length() < DELTA_F32);
+            assert!((right.evaluate(t) - bezier.evaluate(0.25 + t * 0.75)).length() < DELTA_F32);
+        }
+        assert_eq!(hermite.bounds(), bezier.bounds());
+
+        // Centripetal Catmull-Rom interpolates its inner points with a
+        // continuous tangent direction, even with uneven spacing.
+        let points = [Vec2f::new(0.0, 0.0), Vec2f::new(1.0, 1.0), Vec2f::new(1.1, 1.0),
+            Vec2f::new(4.0, -1.0), Vec2f::new(5.0, 3.0), Vec2f::new(6.0, 3.0)];
+        let spline = CatmullRom::new(&points).unwrap();
+        assert!(CatmullRom::new(&points[..3]).is_none());
+        assert_eq!(spline.domain(), (0.0, 3.0));
+
+        for i in 0..=3 {
+            assert!((spline.evaluate(i as f32) - points[i + 1]).length() < DELTA_F32);
+        }
+
+        for i in 1..3 {
+            let before = spline.segment(i - 1).derivative(1.0).get_normalized();
+            let after = spline.segment(i).derivative(0.0).get_normalized();
+            assert!((before - after).length() < DELTA_F32);
+        }
+
+        // Splitting keeps whole segments and cuts the one holding t.
+        let (first, second) = spline.split(1.5);
+        assert_eq!((first.len(), second.len()), (2, 2));
+        for s in [0.0, 0.3, 1.0] {
+            assert!((first[0].evaluate(s) - spline.evaluate(s)).length() < DELTA_F32);
+            assert!((first[1].evaluate(s) - spline.evaluate(1.0 + s * 0.5)).length() < DELTA_F32);
+            assert!((second[0].evaluate(s) - spline.evaluate(1.5 + s * 0.5)).length() < DELTA_F32);
+            assert!((second[1].evaluate(s) - spline.evaluate(2.0 + s)).length() < DELTA_F32);
+        }
+        let (first, second) = spline.split(2.0);
+        assert_eq!((first.len(), second.len()), (2, 1));
+
+        let (min, max) = spline.bounds();
+        for i in 0..=300 {
+            let p = spline.evaluate(i as f32 / 100.0);
+            assert!(p.x >= min.x - DELTA_F32 && p.x <= max.x + DELTA_F32 && p.y >= min.y - DELTA_F32 && p.y <= max.y + DELTA_F32);
+        }
+
+        // A uniform cubic B-spline reaches (a + 4b + c) / 6 at its knots.
+        let controls = [Vec3f::new(0.0, 0.0, 0.0), Vec3f::new(1.0, 2.0, 0.0), Vec3f::new(2.0, -1.0, 1.0),
+            Vec3f::new(3.0, 0.0, 3.0), Vec3f::new(5.0, 1.0, 0.0), Vec3f::new(6.0, 2.0, -2.0)];
+        let uniform = BSpline::uniform(3, &controls).unwrap();
+        assert_eq!(uniform.domain(), (3.0, 6.0));
+        let expected = (controls[1] + controls[2] * 4.0 + controls[3]) * (1.0 / 6.0);
+        assert!((uniform.evaluate(4.0) - expected).length() < DELTA_F32);
+
+        // A clamped spline starts and ends at its end points.
+        let clamped = BSpline::clamped(3, &controls).unwrap();
+        assert_eq!(clamped.knots, vec![0.0, 0.0, 0.0, 0.0, 1.0, 2.0, 3.0, 3.0, 3.0, 3.0]);
+        assert!((clamped.evaluate(0.0) - controls[0]).length() < DELTA_F32);
+        assert!((clamped.evaluate(3.0) - controls[5]).length() < DELTA_F32);
+        assert!(BSpline::new(3, &controls, &[0.0, 1.0]).is_none());
+
+        let non_uniform = BSpline::new(2, &controls, &[0.0, 0.0, 0.0, 0.5, 2.0, 2.5, 4.0, 4.0, 4.0]).unwrap();
+        let refined = non_uniform.insert_knot(1.3).insert_knot(3.0);
+        assert_eq!(refined.points.len(), controls.len() + 2);
+
+        for spline in [&uniform, &clamped, &non_uniform] {
+            let (start, end) = spline.domain();
+            for i in 1..20 {
+                let t = start + (end - start) * i as f32 / 20.0;
+                let numeric = (spline.evaluate(t + h) - spline.evaluate(t - h)) * (0.5 / h);
+                assert!((spline.derivative(t) - numeric).length() < 0.02);
+            }
+        }
+
+        for i in 0..=40 {
+            let t = i as f32 / 10.0;
+            assert!((refined.evaluate(t) - non_uniform.evaluate(t)).length() < DELTA_F32);
+        }
+
+        // The halves of a split trace the original curve, whether or not the
+        // split is at an existing knot.
+        for (spline, u) in [(&uniform, 4.5), (&uniform, 5.0), (&clamped, 1.0), (&clamped, 2.7), (&non_uniform, 2.0), (&non_uniform, 0.2)] {
+            let (start, end) = spline.domain();
+            let (first, second) = spline.split(u).unwrap();
+            assert_eq!((first.domain(), second.domain()), ((start, u), (u, end)));
+            assert!((first.evaluate(u) - spline.evaluate(u)).length() < DELTA_F32);
+
+            for i in 0..=10 {
+                let t = start + (u - start) * i as f32 / 10.0;
+                assert!((first.evaluate(t) - spline.evaluate(t)).length() < DELTA_F32);
+                let t = u + (end - u) * i as f32 / 10.0;
+                assert!((second.evaluate(t) - spline.evaluate(t)).length() < DELTA_F32);
+            }
+        }
+        assert!(clamped.split(0.0).is_none() && clamped.split(3.0).is_none());
+
+        // A quadratic NURBS traces an exact quarter circle.
+        let quarter = Nurbs::new(2, &[Vec2f::new(1.0, 0.0), Vec2f::new(1.0, 1.0), Vec2f::new(0.0, 1.0)],
+            &[1.0, 0.5f32.sqrt(), 1.0], &[0.0, 0.0, 0.0, 1.0, 1.0, 1.0]).unwrap();
+        assert!(Nurbs::new(2, &[Vec2f::ZERO; 3], &[1.0, 0.0, 1.0], &[0.0, 0.0, 0.0, 1.0, 1.0, 1.0]).is_none());
+
+        for i in 0..=10 {
+            let t = i as f32 / 10.0;
+            let p = quarter.evaluate(t);
+            assert_near!(p.length(), 1.0, DELTA_F32);
+            // The tangent of a circle is perpendicular to the radius.
+            assert_near!(quarter.derivative(t) * p, 0.0, 0.001);
+
+            if i > 0 && i < 10 {
+                let numeric = (quarter.evaluate(t + h) - quarter.evaluate(t - h)) * (0.5 / h);
+                assert!((quarter.derivative(t) - numeric).length() < 0.01);
+            }
+        }
+
+        // Splitting in homogeneous coordinates keeps both halves on the circle.
+        let (first, second) = quarter.split(0.3).unwrap();
+        assert_eq!(first.knots(), &[0.0, 0.0, 0.0, 0.3, 0.3, 0.3]);
+        for i in 0..=10 {
+            let t = i as f32 / 10.0;
+            assert!((first.evaluate(t * 0.3) - quarter.evaluate(t * 0.3)).length() < DELTA_F32);
+            assert!((second.evaluate(0.3 + t * 0.7) - quarter.evaluate(0.3 + t * 0.7)).length() < DELTA_F32);
+        }
+        let refined = quarter.insert_knot(0.5);
+        assert_eq!(refined.weights.len(), 4);
+        assert!((refined.evaluate(0.8) - quarter.evaluate(0.8)).length() < DELTA_F32);
+        assert!(quarter.split(1.0).is_none());
+
+        let table = ArcLengthTable::new(&quarter, 32);
+        assert_near!(table.length(), PI / 2.0, 0.0001);
+        // Equal distances along the arc are equal angles.
+        let p = quarter.evaluate(table.parameter_at(PI / 6.0));
+        assert_near!(p.y.atan2(p.x), PI / 6.0, 0.001);
+        assert_eq!(table.parameter_at(-1.0), 0.0);
+        assert_eq!(table.parameter_at(10.0), 1.0);
+
+        // A straight line with bunched control points is not traversed at
+        // constant speed by its parameter, but is by arc length.
+        let line = CubicBezier::new(Vec3f::ZERO, Vec3f::new(0.1, 0.0, 0.0), Vec3f::new(0.2, 0.0, 0.0), Vec3f::new(4.0, 0.0, 0.0));
+        let table = ArcLengthTable::new(&line, 64);
+        assert_near!(table.length(), 4.0, 0.001);
+        assert_near!(line.evaluate(table.parameter_at_fraction(0.25)).x, 1.0, 0.01);
+        assert_near!(line.evaluate(table.parameter_at(3.0)).x, 3.0, 0.01);
+    }
+
+    #[test]
+    fn test_quat_spline() {
+        let axis = |x: f64, y: f64, z: f64| Vec3::<f64>::new(x, y, z).get_normalized();
+        let angle_between = |a: Quat<f64>, b: Quat<f64>| (a.conjugate() * b).log().length() * 2.0;
+
+        // log and exp are inverses, and slerp_arc turns at a constant rate.
+        let q = Quat::<f64>::from_axis_angle(axis(1.0, 2.0, -1.0), 2.0);
+        let back = Quat::<f64>::exp(q.log());
+        assert!((back - q).length() < 1e-9);
+        assert_near!(q.log().length(), 1.0, 1e-9);
+
+        let a = Quat::<f64>::from_axis_angle(axis(0.0, 1.0, 0.0), 0.3);
+        let b = Quat::<f64>::from_axis_angle(axis(1.0, 0.0, 1.0), 1.7);
+        let total = angle_between(a, b);
+        for t in [0.25, 0.5, 0.8] {
+            let q = Quat::<f64>::slerp_arc(a, b, t);
+            assert_near!(angle_between(a, q), total * t, 1e-9);
+            assert_near!(angle_between(q, b), total * (1.0 - t), 1e-9);
+        }
+
+        let keys = [
+            (0.0, Quat::<f64>::IDENTITY),
+            (0.5, Quat::<f64>::from_axis_angle(axis(0.0, 1.0, 0.0), 0.8)),
+            (2.0, Quat::<f64>::from_axis_angle(axis(1.0, 1.0, 0.0), 2.0)),
+            (2.3, Quat::<f64>::from_axis_angle(axis(0.0, 0.0, 1.0), -1.0)),
+            (4.0, Quat::<f64>::from_axis_angle(axis(1.0, -1.0, 1.0), 2.5))
+        ];
+        let spline = QuatSpline::new(&keys).unwrap();
+        assert!(QuatSpline::new(&[keys[1], keys[0]]).is_none());
+        assert!(QuatSpline::<f64>::new(&[]).is_none());
+
+        // The spline passes through its keys.
+        for (time, rotation) in &keys {
+            assert!(angle_between(spline.sample(*time), *rotation) < 1e-9);
+        }
+        assert_eq!(spline.sample(-1.0), spline.rotations[0]);
+        assert_eq!(spline.sample(9.0), spline.rotations[4]);
+
+        // Angular velocity is continuous across the inner keys.
+        let h = 1e-5;
+        let velocity = |from: f64, to: f64| (spline.sample(from).conjugate() * spline.sample(to)).log() * (2.0 / (to - from));
+        for (time, _) in &keys[1..4] {
+            let before = velocity(time - h, *time);
+            let after = velocity(*time, time + h);
+            assert!((before - after).length() < 1e-3, "{} vs {}", before, after);
+            assert!(before.length() > 0.1);
+        }
+
+        // Keys given in the opposite hemisphere describe the same path.
+        let mut flipped = keys;
+        flipped[2].1 = -flipped[2].1;
+        flipped[3].1 = -flipped[3].1;
+        let flipped = QuatSpline::new(&flipped).unwrap();
+        for i in 0..=40 {
+            let time = i as f64 * 0.1;
+            assert!(angle_between(flipped.sample(time), spline.sample(time)) < 1e-9);
+        }
+
+        // With two keys the path is a constant rate slerp.
+        let two = QuatSpline::new(&[keys[0], keys[2]]).unwrap();
+        let total = angle_between(keys[0].1, keys[2].1);
+        assert_near!(angle_between(keys[0].1, two.sample(0.5)), total * 0.25, 1e-9);
+        assert_near!(angle_between(keys[0].1, two.sample(1.5)), total * 0.75, 1e-9);
+    }
+
+    #[test]
+    fn test_animation() {
+        let easings = [Easing::Linear, Easing::QuadIn, Easing::QuadOut, Easing::QuadInOut,
+            Easing::CubicIn, Easing::CubicOut, Easing::CubicInOut, Easing::ElasticIn, Easing::ElasticOut,
+            Easing::ElasticInOut, Easing::BackIn, Easing::BackOut, Easing::BackInOut,
+            Easing::BounceIn, Easing::BounceOut, Easing::BounceInOut];
+
+        for easing in easings {
+            assert_near!(easing.apply(0.0f32), 0.0, DELTA_F32);
+            assert_near!(easing.apply(1.0f32), 1.0, DELTA_F32);
+        }
+
+        for easing in [Easing::QuadInOut, Easing::CubicInOut, Easing::ElasticInOut, Easing::BackInOut, Easing::BounceInOut] {
+            assert_near!(easing.apply(0.5f32), 0.5, DELTA_F32);
+        }
+
+        assert_near!(Easing::QuadIn.apply(0.5f32), 0.25, DELTA_F32);
+        assert_near!(Easing::CubicInOut.apply(0.25f32), 0.0625, DELTA_F32);
+        assert_near!(Easing::BackIn.apply(0.5f32), -0.0876975, DELTA_F32);
+        assert_near!(Easing::ElasticOut.apply(0.5f32), 1.015625, DELTA_F32);
+        assert_near!(Easing::BounceOut.apply(0.5f32), 0.765625, DELTA_F32);
+        assert_near!(Easing::BounceIn.apply(0.5f32), 0.234375, DELTA_F32);
+
+        // Interpolation modes on a Vec3 track.
+        let mut track = Track::<f32, Vec3f>::new(Interpolation::Linear, WrapMode::Clamp);
+        assert!(track.sample(0.0).is_none());
+        track.insert(Keyframe::new(2.0, Vec3f::new(2.0, 4.0, 0.0)));
+        track.insert(Keyframe::new(0.0, Vec3f::ZERO));
+        track.insert(Keyframe::new(1.0, Vec3f::new(1.0, 0.0, 0.0)));
+        track.insert(Keyframe::new(1.0, Vec3f::new(1.0, 1.0, 0.0)));
+        assert_eq!(track.keyframes.len(), 3);
+        assert_eq!(track.duration(), 2.0);
+
+        assert_eq!(track.sample(0.5), Some(Vec3f::new(0.5, 0.5, 0.0)));
+        assert_eq!(track.sample(1.5), Some(Vec3f::new(1.5, 2.5, 0.0)));
+        assert_eq!(track.sample(-3.0), Some(Vec3f::ZERO));
+        assert_eq!(track.sample(7.0), Some(Vec3f::new(2.0, 4.0, 0.0)));
+
+        track.keyframes[0].easing = Easing::QuadIn;
+        assert_eq!(track.sample(0.5), Some(Vec3f::new(0.25, 0.25, 0.0)));
+        track.keyframes[0].easing = Easing::Linear;
+
+        track.interpolation = Interpolation::Step;
+        assert_eq!(track.sample(0.99), Some(Vec3f::ZERO));
+        assert_eq!(track.sample(1.0), Some(Vec3f::new(1.0, 1.0, 0.0)));
+        assert_eq!(track.sample(2.0), Some(Vec3f::new(2.0, 4.0, 0.0)));
+
+        // Wrap modes.
+        track.interpolation = Interpolation::Linear;
+        track.wrap = WrapMode::Loop;
+        assert_near!(track.wrap_time(2.5), 0.5, DELTA_F32);
+        assert_near!(track.wrap_time(-0.5), 1.5, DELTA_F32);
+        assert_eq!(track.sample(4.5), track.sample(0.5));
+        track.wrap = WrapMode::PingPong;
+        assert_near!(track.wrap_time(2.5), 1.5, DELTA_F32);
+        assert_near!(track.wrap_time(4.5), 0.5, DELTA_F32);
+        assert_near!(track.wrap_time(-0.5), 0.5, DELTA_F32);
+
+        // Catmull-Rom tangents reproduce evenly spaced linear motion exactly.
+        let mut scalar = Track::<f32, f32>::new(Interpolation::Cubic, WrapMode::Clamp);
+        for i in 0..5 {
+            scalar.insert(Keyframe::new(i as f32, i as f32 * 3.0));
+        }
+        for i in 0..=40 {
+            let time = i as f32 * 0.1;
+            assert_near!(scalar.sample(time).unwrap(), time * 3.0, 0.001);
+        }
+
+        // Explicit tangents set the rate of change at the keyframe.
+        let mut cubic = Track::<f32, f32>::new(Interpolation::Cubic, WrapMode::Clamp);
+        cubic.insert(Keyframe::with_tangents(0.0, 1.0, 0.0, 4.0));
+        cubic.insert(Keyframe::with_tangents(2.0, 2.0, -1.0, 0.0));
+        let h = 0.001;
+        assert_near!((cubic.sample(h).unwrap() - cubic.sample(0.0).unwrap()) / h, 4.0, 0.01);
+        assert_near!((cubic.sample(2.0).unwrap() - cubic.sample(2.0 - h).unwrap()) / h, -1.0, 0.01);
+
+        // Rotations slerp along the shorter arc.
+        let a = Quatf::from_axis_angle(Vec3f::Y, 0.0);
+        let b = Quatf::from_axis_angle(Vec3f::Y, 1.0);
+        let mut rotations = Track::<f32, Quatf>::new(Interpolation::Linear, WrapMode::Clamp);
+        rotations.insert(Keyframe::new(0.0, a));
+        rotations.insert(Keyframe::new(1.0, -b));
+        let half = rotations.sample(0.5).unwrap();
+        assert!((half * Vec3f::X - Quatf::from_axis_angle(Vec3f::Y, 0.5) * Vec3f::X).length() < DELTA_F32);
+
+        rotations.interpolation = Interpolation::Cubic;
+        let half = rotations.sample(0.5).unwrap();
+        assert!((half * Vec3f::X - Quatf::from_axis_angle(Vec3f::Y, 0.5) * Vec3f::X).length() < 0.01);
+
+        // Matrices animate through their decomposed translation, rotation and scale.
+        let transform = Transformf::new(Vec3f::new(1.0, 2.0, 3.0), Quatf::from_axis_angle(Vec3f::new(1.0, 1.0, 0.0).get_normalized(), 0.7), Vec3f::new(2.0, 1.0, 0.5));
+        let decomposed = Transformf::from_matrix(&transform.to_matrix());
+        assert!((decomposed.translation - transform.translation).length() < DELTA_F32);
+        assert!((decomposed.scale - transform.scale).length() < DELTA_F32);
+        assert!((decomposed.transform_point(Vec3f::new(1.0, -1.0, 2.0)) - transform.transform_point(Vec3f::new(1.0, -1.0, 2.0))).length() < DELTA_F32);
+
+        let mirrored = Transformf::new(Vec3f::ZERO, Quatf::IDENTITY, Vec3f::new(-1.0, 2.0, 1.0));
+        assert!((Transformf::from_matrix(&mirrored.to_matrix()).scale - mirrored.scale).length() < DELTA_F32);
+
+        let start = Mat44f::IDENTITY.get_translated(Vec3f::new(0.0, 0.0, 0.0));
+        let end = transform.to_matrix();
+        let matrices = Track::from_matrices(&[(0.0, start), (4.0, end)], Interpolation::Linear, WrapMode::Clamp);
+        let sampled = matrices.sample_matrix(4.0).unwrap();
+        let point = Vec4f::new(1.0, -2.0, 0.5, 1.0);
+        assert!((sampled * point - end * point).length() < DELTA_F32);
+        let middle = matrices.sample(2.0).unwrap();
+        assert!((middle.translation - Vec3f::new(0.5, 1.0, 1.5)).length() < DELTA_F32);
+        assert!((middle.scale - Vec3f::new(1.5, 1.0, 0.75)).length() < DELTA_F32);
+    }
+
+    #[test]
+    fn test_skeleton() {
+        let mat_near = |a: &Mat44f, b: &Mat44f| (0..4).all(|c| (0..4).all(|r| (a.data[c][r] - b.data[c][r]).abs() < 0.001));
+
+        // A root with a two joint arm and a separate leaf.
+        let locals = [
+            Transformf::new(Vec3f::new(0.0, 1.0, 0.0), Quatf::from_axis_angle(Vec3f::Y, 0.5), Vec3f::new(1.0, 1.0, 1.0)),
+            Transformf::new(Vec3f::new(1.0, 0.0, 0.0), Quatf::from_axis_angle(Vec3f::Z, 0.3), Vec3f::new(1.0, 1.0, 1.0)),
+            Transformf::new(Vec3f::new(2.0, 0.0, 0.0), Quatf::IDENTITY, Vec3f::new(1.0, 1.0, 1.0)),
+            Transformf::new(Vec3f::new(0.0, 0.0, 1.0), Quatf::from_axis_angle(Vec3f::X, -0.4), Vec3f::new(2.0, 2.0, 2.0))
+        ];
+        let parents = [None, Some(0), Some(1), Some(0)];
+        let mut binds = Vec::new();
+        for (joint, local) in locals.iter().enumerate() {
+            let matrix = local.to_matrix();
+            binds.push(match parents[joint] { Some(p) => binds[p] * matrix, None => matrix });
+        }
+
+        assert!(Skeleton::new(&[None, Some(1)], &binds[..2]).is_none());
+        assert!(Skeleton::new(&parents, &binds[..2]).is_none());
+        let skeleton = Skeleton::new(&parents, &binds).unwrap();
+
+        // The bind pose reproduces the bind matrices and needs no skinning.
+        let bind_pose = skeleton.bind_pose();
+        for (joint, local) in bind_pose.joints.iter().enumerate() {
+            assert!((local.translation - locals[joint].translation).length() < DELTA_F32);
+            assert!((local.scale - locals[joint].scale).length() < DELTA_F32);
+        }
+        let model = skeleton.model_matrices(&bind_pose);
+        for (joint, bind) in binds.iter().enumerate() {
+            assert!(mat_near(&model[joint], bind));
+            assert!(mat_near(&(*bind * skeleton.inverse_bind_matrices()[joint]), &Mat44f::IDENTITY));
+        }
+        for matrix in skeleton.skinning_palette(&bind_pose) {
+            assert!(mat_near(&matrix, &Mat44f::IDENTITY));
+        }
+
+        // Bending the elbow moves the hand but not the other leaf.
+        let mut bent = bind_pose.clone();
+        bent.joints[1].rotation = Quatf::from_axis_angle(Vec3f::Z, 0.3 + PI / 2.0);
+        let palette = skeleton.skinning_palette(&bent);
+        let elbow = binds[1] * Vec4f::new(0.0, 0.0, 0.0, 1.0);
+        let hand = binds[2] * Vec4f::new(0.0, 0.0, 0.0, 1.0);
+        let moved = palette[2] * hand;
+        assert!((palette[1] * elbow - elbow).length() < DELTA_F32);
+        assert_near!((moved - elbow).length(), 2.0, DELTA_F32);
+        assert!((moved - hand).length() > 1.0);
+
+        // Poses must cover exactly the skeleton's joints.
+        let short = Pose::new(bind_pose.joints[..2].to_vec());
+        let long = Pose::identity(skeleton.joint_count() + 1);
+        assert!(std::panic::catch_unwind(|| skeleton.model_matrices(&short)).is_err());
+        assert!(std::panic::catch_unwind(|| skeleton.skinning_palette(&long)).is_err());
+        assert!(mat_near(&palette[3], &Mat44f::IDENTITY));
+
+        // Override blending, with a mask keeping the root in place.
+        let half = bind_pose.blend(&bent, 0.5, None);
+        assert!((half.joints[1].rotation * Vec3f::X - Quatf::from_axis_angle(Vec3f::Z, 0.3 + PI / 4.0) * Vec3f::X).length() < DELTA_F32);
+        assert_eq!(bind_pose.blend(&bent, 0.0, None), bind_pose);
+
+        let mut moved_root = bent.clone();
+        moved_root.joints[0].translation = Vec3f::new(5.0, 5.0, 5.0);
+        let masked = bind_pose.blend(&moved_root, 1.0, Some(&[0.0, 1.0, 1.0, 1.0]));
+        assert_eq!(masked.joints[0], bind_pose.joints[0]);
+        assert!((masked.joints[1].translation - moved_root.joints[1].translation).length() < DELTA_F32);
+        assert_near!(masked.joints[1].rotation.dot(&moved_root.joints[1].rotation), 1.0, DELTA_F32);
+
+        // Additive layers reproduce their source on the reference pose and
+        // apply the same change on top of other poses.
+        let additive = moved_root.difference(&bind_pose);
+        let full = bind_pose.add(&additive, 1.0, None);
+        for (a, b) in full.joints.iter().zip(&moved_root.joints) {
+            assert!((a.translation - b.translation).length() < DELTA_F32);
+            assert!((a.rotation * Vec3f::X - b.rotation * Vec3f::X).length() < DELTA_F32);
+        }
+
+        let layered = half.add(&additive, 0.5, Some(&[1.0, 1.0, 0.0, 1.0]));
+        assert!((layered.joints[0].translation - (half.joints[0].translation + Vec3f::new(5.0, 4.0, 5.0) * 0.5)).length() < DELTA_F32);
+        let turned = Quatf::from_axis_angle(Vec3f::Z, PI / 4.0) * half.joints[1].rotation;
+        assert!((layered.joints[1].rotation * Vec3f::X - turned * Vec3f::X).length() < DELTA_F32);
+        assert_eq!(layered.joints[2], half.joints[2]);
+    }
+
+    #[test]
+    fn test_ik() {
+        let solved = |chain: &IkChain<f32>, solution: &IkSolution<f32>| {
+            let mut chain = chain.clone();
+            chain.rotations = solution.rotations.clone();
+            chain
+        };
+
+        // An arm along x with upper and lower bones of length 2 and 1.5.
+        let arm = IkChain::new(Vec3f::new(1.0, 1.0, 0.0), Quatf::IDENTITY,
+            &[Vec3f::new(2.0, 0.0, 0.0), Vec3f::new(1.5, 0.0, 0.0)], &[Quatf::IDENTITY; 2]);
+        assert_near!(arm.length(), 3.5, DELTA_F32);
+        assert!((arm.end_effector() - Vec3f::new(4.5, 1.0, 0.0)).length() < DELTA_F32);
+
+        // Two bone reaches targets in range and bends towards the pole.
+        let mut rng = Lcg(37);
+        for _ in 0..20 {
+            let direction = rng.vec3(-1.0, 1.0).get_normalized();
+            let target = arm.root_position + direction * rng.range(0.8, 3.3);
+            let pole = arm.root_position + rng.vec3(-5.0, 5.0);
+
+            let solution = arm.solve_two_bone(target, pole);
+            assert!(solution.error < 0.001, "two bone error {}", solution.error);
+
+            let positions = solved(&arm, &solution).positions();
+            assert_near!((positions[1] - positions[0]).length(), 2.0, DELTA_F32);
+            let axis = (target - arm.root_position).get_normalized();
+            let side = |v: Vec3f| v - axis * (v * axis);
+            assert!(side(positions[1] - positions[0]) * side(pole - arm.root_position) >= 0.0);
+        }
+
+        // Out of reach targets straighten the arm towards them.
+        let far = Vec3f::new(1.0, 10.0, 0.0);
+        let solution = arm.solve_two_bone(far, Vec3f::new(5.0, 5.0, 5.0));
+        assert_near!(solution.error, 9.0 - 3.5, 0.001);
+        assert!((solved(&arm, &solution).end_effector() - Vec3f::new(1.0, 4.5, 0.0)).length() < 0.001);
+
+        // A five bone chain for the iterative solvers.
+        let chain = IkChain::new(Vec3f::ZERO, Quatf::IDENTITY, &[Vec3f::Y; 5], &[Quatf::IDENTITY; 5]);
+        for target in [Vec3f::new(2.0, 2.0, 1.0), Vec3f::new(-1.0, 1.0, -2.0), Vec3f::new(0.5, -1.0, 0.5)] {
+            let fabrik = chain.solve_fabrik(target, 100, 0.001);
+            assert!(fabrik.error <= 0.001, "fabrik error {}", fabrik.error);
+            assert!((solved(&chain, &fabrik).end_effector() - target).length() <= 0.001);
+
+            let ccd = chain.solve_ccd(target, 100, 0.001);
+            assert!(ccd.error <= 0.001, "ccd error {}", ccd.error);
+        }
+
+        let far = Vec3f::new(10.0, 0.0, 0.0);
+        for solution in [chain.solve_fabrik(far, 50, 0.001), chain.solve_ccd(far, 50, 0.001)] {
+            assert_near!(solution.error, 5.0, 0.01);
+            assert!((solved(&chain, &solution).end_effector() - Vec3f::new(5.0, 0.0, 0.0)).length() < 0.01);
+        }
+
+        // Limits hold on every joint of the solution.
+        let hinge = JointLimit::Hinge { axis: Vec3f::Z, min: -0.5, max: 1.0 };
+        let cone = JointLimit::Cone { axis: Vec3f::Y, angle: 0.4 };
+        let twist = JointLimit::Twist { axis: Vec3f::Y, min: -0.1, max: 0.1 };
+
+        let q = Quatf::from_axis_angle(Vec3f::new(1.0, 0.3, 0.2).get_normalized(), 1.5);
+        let limited = hinge.apply(q);
+        assert!((limited * Vec3f::Z - Vec3f::Z).length() < DELTA_F32);
+        assert_eq!(hinge.apply(Quatf::from_axis_angle(Vec3f::Z, 2.0)), Quatf::from_axis_angle(Vec3f::Z, 1.0));
+        assert!((cone.apply(q) * Vec3f::Y).angle_between(&Vec3f::Y) <= 0.4 + DELTA_F32);
+        assert_eq!(cone.apply(Quatf::from_axis_angle(Vec3f::X, 0.3)), Quatf::from_axis_angle(Vec3f::X, 0.3));
+        let limited = twist.apply(Quatf::from_axis_angle(Vec3f::Y, 1.0));
+        assert!((limited * Vec3f::X - Quatf::from_axis_angle(Vec3f::Y, 0.1) * Vec3f::X).length() < DELTA_F32);
+
+        let mut limited_chain = chain.clone();
+        limited_chain.limits = vec![cone, hinge, hinge, cone, twist];
+        let target = Vec3f::new(1.5, 3.5, 0.5);
+        for solution in [limited_chain.solve_fabrik(target, 100, 0.01), limited_chain.solve_ccd(target, 100, 0.01)] {
+            for (rotation, limit) in solution.rotations.iter().zip(&limited_chain.limits) {
+                let again = limit.apply(*rotation);
+                assert!((again * Vec3f::X - *rotation * Vec3f::X).length() < 0.001);
+                assert!((again * Vec3f::Y - *rotation * Vec3f::Y).length() < 0.001);
+            }
+            // The limits still leave the target reachable.
+            assert!(solution.error < 0.05, "limited error {}", solution.error);
+        }
+    }
+
+    #[test]
+    fn test_swing_twist() {
+        // Rotations match up to sign.
+        let same = |a: Quatf, b: Quatf| a.dot(&b).abs() > 1.0 - DELTA_F32;
+        let twist_free = |q: Quatf, axis: Vec3f| (Vec3f::new(q.x, q.y, q.z) * axis).abs() < DELTA_F32;
+
+        let mut rng = Lcg(38);
+        for _ in 0..50 {
+            let q = Quatf::from_axis_angle(rng.vec3(-1.0, 1.0).get_normalized(), rng.range(-3.0, 3.0));
+            let axis = rng.vec3(-1.0, 1.0).get_normalized();
+
+            let (swing, twist) = q.swing_twist(axis);
+            assert!(same(swing * twist, q));
+            assert!(twist_free(swing, axis));
+            assert!((Vec3f::new(twist.x, twist.y, twist.z) % axis).length() < DELTA_F32);
+            assert!(same(Quatf::from_axis_angle(axis, q.twist_angle(axis)), twist));
+        }
+
+        // Half turns land in the trace < 0 branches of from_matrix: about x, y
+        // and z the largest diagonal element is m00, m11 and m22 in turn.
+        let diagonal = Vec3f::new(1.0, 1.0, 0.0).get_normalized();
+        let axes = [Vec3f::X, Vec3f::Y, Vec3f::Z, diagonal, Vec3f::new(1.0, -1.0, 1.0).get_normalized()];
+        for axis in axes {
+            for angle in [PI, -PI, PI - 0.01] {
+                let q = Quatf::from_axis_angle(axis, angle);
+                let matrix = q.to_matrix();
+                assert!(matrix.data[0][0] + matrix.data[1][1] + matrix.data[2][2] < 0.0);
+                let back = Quatf::from_matrix(&matrix);
+                assert!(same(back, q), "{} about {} came back as {}", angle, axis, back);
+
+                // All twist about its own axis.
+                let (swing, twist) = back.swing_twist(axis);
+                assert!(same(swing, Quatf::IDENTITY));
+                assert!(same(twist, q));
+                assert_near!(back.twist_angle(axis).abs(), angle.abs(), 0.001);
+
+                // No twist about a perpendicular axis, so it all goes to the swing.
+                let perpendicular = (axis % Vec3f::new(0.3, 0.5, 0.7)).get_normalized();
+                let (swing, twist) = back.swing_twist(perpendicular);
+                assert!(same(swing * twist, q));
+                assert!(twist_free(swing, perpendicular));
+                if angle.abs() == PI {
+                    assert_eq!(twist, Quatf::IDENTITY);
+                }
+            }
+        }
+
+        // Twist clamping keeps the swing.
+        let swing = Quatf::from_axis_angle(Vec3f::X, 0.3);
+        let q = swing * Quatf::from_axis_angle(Vec3f::Y, 1.2);
+        let clamped = q.clamp_twist(Vec3f::Y, -0.5, 0.5);
+        assert_near!(clamped.twist_angle(Vec3f::Y), 0.5, DELTA_F32);
+        assert!(same(clamped.swing_twist(Vec3f::Y).0, swing));
+        assert!(same(q.clamp_twist(Vec3f::Y, -2.0, 2.0), q));
+        assert_near!(Quatf::from_axis_angle(Vec3f::Y, -2.5).twist_angle(Vec3f::Y), -2.5, DELTA_F32);
+
+        // Cone clamping keeps the twist and the swing direction.
+        let twist = Quatf::from_axis_angle(Vec3f::Y, 0.7);
+        let swing_axis = Vec3f::new(1.0, 0.0, 1.0).get_normalized();
+        let q = Quatf::from_axis_angle(swing_axis, 1.0) * twist;
+        let clamped = q.clamp_swing_cone(Vec3f::Y, 0.4);
+        assert_near!((clamped * Vec3f::Y).angle_between(&Vec3f::Y), 0.4, DELTA_F32);
+        let (swing, kept) = clamped.swing_twist(Vec3f::Y);
+        assert!(same(kept, twist));
+        assert!(same(swing, Quatf::from_axis_angle(swing_axis, 0.4)));
+        assert_eq!(q.clamp_swing_cone(Vec3f::Y, 1.5), q);
+
+        // Elliptical limits of 0.8 about x and 0.2 about y % x = -z.
+        let ellipse = |q: Quatf, minor: f32| q.clamp_swing_ellipse(Vec3f::Y, Vec3f::X, 0.8, minor);
+        let q = Quatf::from_axis_angle(Vec3f::X, 0.6) * twist;
+        assert_eq!(ellipse(q, 0.2), q);
+        let clamped = ellipse(Quatf::from_axis_angle(Vec3f::Z, 0.6) * twist, 0.2);
+        assert!(same(clamped, Quatf::from_axis_angle(Vec3f::Z, 0.2) * twist));
+
+        for _ in 0..20 {
+            let direction = Vec3f::new(rng.range(-1.0, 1.0), 0.0, rng.range(-1.0, 1.0)).get_normalized();
+            let q = Quatf::from_axis_angle(direction, rng.range(0.0, 2.0));
+            let (swing, _) = ellipse(q, 0.2).swing_twist(Vec3f::Y);
+            let rotation = swing.log() * 2.0;
+            let extent = (rotation.x / 0.8).powi(2) + (rotation.z / 0.2).powi(2);
+            assert!(extent <= 1.0 + 0.001, "swing {} is outside the ellipse", rotation);
+            // Clamped swings keep their direction.
+            assert!((rotation.get_normalized() % direction).length() < 0.001 || rotation.length() < DELTA_F32);
+        }
+
+        // A zero limit locks swing about that axis.
+        let clamped = ellipse(Quatf::from_axis_angle(Vec3f::Z, 0.6) * twist, 0.0);
+        assert!(same(clamped, twist));
+        let q = Quatf::from_axis_angle(Vec3f::X, 0.5);
+        assert_eq!(ellipse(q, 0.0), q);
+    }
+
+    #[test]
+    fn test_physics() {
+        let mat_near = |a: Mat33f, b: Mat33f, delta: f32| {
+            for column in 0..3 {
+                for row in 0..3 {
+                    assert_near!(a.data[column][row], b.data[column][row], delta);
+                }
+            }
+        };
+        let diagonal = |x: f32, y: f32, z: f32| Mat33f::from_axes(Vec3f::new(x, 0.0, 0.0), Vec3f::new(0.0, y, 0.0), Vec3f::new(0.0, 0.0, z));
+
+        // A 2 x 4 x 6 box of mass 12.
+        mat_near(box_inertia(12.0, Vec3f::new(1.0, 2.0, 3.0)), diagonal(52.0, 40.0, 20.0), DELTA_F32);
+        mat_near(sphere_inertia(5.0, 2.0), diagonal(8.0, 8.0, 8.0), DELTA_F32);
+        mat_near(cylinder_inertia(6.0, 1.0, 2.0), diagonal(9.5, 3.0, 9.5), DELTA_F32);
+        mat_near(capsule_inertia(5.0, 2.0, 0.0), sphere_inertia(5.0, 2.0), DELTA_F32);
+
+        // The capsule against a sum over a fine grid of its volume.
+        let (radius, half_height, n) = (0.5, 1.0, 80);
+        let mut samples = Vec::new();
+        for i in 0..n {
+            for j in 0..n * 3 {
+                for k in 0..n {
+                    let p = Vec3f::new(
+                        -radius + (i as f32 + 0.5) / n as f32 * 2.0 * radius,
+                        -1.5 + (j as f32 + 0.5) / (n * 3) as f32 * 3.0,
+                        -radius + (k as f32 + 0.5) / n as f32 * 2.0 * radius);
+                    let axis_point = Vec3f::new(0.0, p.y.clamp(-half_height, half_height), 0.0);
+                    if (p - axis_point).length() <= radius {
+                        samples.push(p);
+                    }
+                }
+            }
+        }
+        let mass = samples.len() as f32;
+        let capsule = capsule_inertia(1.0, radius, half_height);
+        let side = samples.iter().map(|p| p.y * p.y + p.z * p.z).sum::<f32>() / mass;
+        let axial = samples.iter().map(|p| p.x * p.x + p.z * p.z).sum::<f32>() / mass;
+        assert_near!(capsule.data[0][0], side, 0.01 * side);
+        assert_near!(capsule.data[1][1], axial, 0.01 * axial);
+        assert_near!(capsule.data[2][2], side, 0.01 * side);
+
+        // A closed box mesh, moved off the origin and turned.
+        let half = Vec3f::new(1.0, 2.0, 3.0);
+        let rotation = Quatf::from_axis_angle(Vec3f::new(1.0, 2.0, 0.5).get_normalized(), 0.8);
+        let offset = Vec3f::new(5.0, -1.0, 2.0);
+        let vertices: Vec<Vec3f> = (0..8).map(|i| {
+            let corner = Vec3f::new(
+                if i & 1 == 0 { -half.x } else { half.x },
+                if i & 2 == 0 { -half.y } else { half.y },
+                if i & 4 == 0 { -half.z } else { half.z });
+            rotation * corner + offset
+        }).collect();
+        let triangles = [
+            [0, 2, 1], [1, 2, 3], [4, 5, 6], [5, 7, 6],
+            [0, 1, 4], [1, 5, 4], [2, 6, 3], [3, 6, 7],
+            [0, 4, 2], [2, 4, 6], [1, 3, 5], [3, 7, 5]];
+
+        let properties = mesh_inertia(&vertices, &triangles, 0.5).unwrap();
+        assert_near!(properties.mass, 24.0, 0.001);
+        assert!((properties.center - offset).length() < 0.001);
+        mat_near(properties.inertia, rotate_inertia(box_inertia(24.0, half), rotation), 0.01);
+
+        let inverted: Vec<[usize; 3]> = triangles.iter().map(|t| [t[0], t[2], t[1]]).collect();
+        assert!(mesh_inertia(&vertices, &inverted, 0.5).is_none());
+
+        // Parallel axis theorem against the point mass formula.
+        let moved = parallel_axis(sphere_inertia(2.0, 1.0), 2.0, Vec3f::new(0.0, 3.0, 4.0));
+        mat_near(moved, diagonal(0.8 + 50.0, 0.8 + 32.0, 0.8 + 18.0) + Mat33f::from_axes(
+            Vec3f::ZERO, Vec3f::new(0.0, 0.0, -24.0), Vec3f::new(0.0, -24.0, 0.0)), DELTA_F32);
+
+        // Integrating a constant spin about z matches the exact rotation.
+        let mut orientation = Quatf::IDENTITY;
+        for _ in 0..1000 {
+            orientation = integrate_orientation(orientation, Vec3f::new(0.0, 0.0, 1.5), 0.001);
+        }
+        assert_near!(orientation.length(), 1.0, DELTA_F32);
+        assert!((orientation * Vec3f::X - Quatf::from_axis_angle(Vec3f::Z, 1.5) * Vec3f::X).length() < 0.001);
+
+        // Semi-implicit Euler under gravity takes the velocity after each step.
+        let mut body = RigidBody::new(Vec3f::ZERO, Quatf::IDENTITY, 2.0, sphere_inertia(2.0, 1.0));
+        body.linear_velocity = Vec3f::new(1.0, 5.0, 0.0);
+        for _ in 0..10 {
+            body.integrate_euler(Vec3f::new(0.0, -20.0, 0.0), Vec3f::ZERO, 0.1);
+        }
+        assert!((body.position - Vec3f::new(1.0, 5.0 - 5.5, 0.0)).length() < 0.001);
+
+        // RK4 on a spring follows the exact cosine.
+        let mut body = RigidBody::new(Vec3f::new(1.0, 0.0, 0.0), Quatf::IDENTITY, 1.0, sphere_inertia(1.0, 1.0));
+        let mut time = 0.0;
+        for _ in 0..100 {
+            body.integrate_rk4(time, 0.05, |state, _| (state.position * -4.0, Vec3f::ZERO));
+            time += 0.05;
+        }
+        assert_near!(body.position.x, (2.0 * time).cos(), 0.001);
+        assert_near!(body.linear_velocity.x, -2.0 * (2.0 * time).sin(), 0.001);
+
+        // A tumbling box keeps its angular momentum and energy.
+        let mut body = RigidBody::new(Vec3f::ZERO, Quatf::IDENTITY, 1.0, box_inertia(1.0, Vec3f::new(0.5, 1.0, 2.0)));
+        body.angular_velocity = Vec3f::new(0.1, 2.0, 0.1);
+        let (momentum, energy) = (body.angular_momentum(), body.kinetic_energy());
+        for _ in 0..500 {
+            body.integrate_rk4(0.0, 0.01, |_, _| (Vec3f::ZERO, Vec3f::ZERO));
+        }
+        assert!((body.angular_momentum() - momentum).length() < 0.001 * momentum.length());
+        assert_near!(body.kinetic_energy(), energy, 0.001 * energy);
+
+        // Impulses, and static bodies ignoring them.
+        let mut body = RigidBody::new(Vec3f::ZERO, Quatf::IDENTITY, 2.0, box_inertia(2.0, Vec3f::new(1.0, 1.0, 1.0)));
+        body.apply_impulse(Vec3f::new(0.0, 4.0, 0.0), Vec3f::new(1.0, 0.0, 0.0));
+        assert!((body.linear_velocity - Vec3f::new(0.0, 2.0, 0.0)).length() < DELTA_F32);
+        assert!((body.angular_velocity - Vec3f::new(0.0, 0.0, 3.0)).length() < DELTA_F32);
+        assert!((body.velocity_at(Vec3f::new(1.0, 0.0, 0.0)) - Vec3f::new(0.0, 5.0, 0.0)).length() < DELTA_F32);
+
+        let mut ground = RigidBody::new(Vec3f::ZERO, Quatf::IDENTITY, 0.0, Mat33f::IDENTITY);
+        assert!(ground.is_static());
+        ground.apply_impulse(Vec3f::new(0.0, 4.0, 0.0), Vec3f::new(1.0, 0.0, 0.0));
+        ground.integrate_euler(Vec3f::new(0.0, -10.0, 0.0), Vec3f::X, 0.1);
+        assert_eq!(ground.position, Vec3f::ZERO);
+        assert_eq!(ground.kinetic_energy(), 0.0);
+    }
+
+    #[test]
+    fn test_solver() {
+        let gravity = Vec3f::new(0.0, -9.81, 0.0);
+        let ground = |world: &mut World<f32>| {
+            let body = RigidBody::new(Vec3f::new(0.0, -0.5, 0.0), Quatf::IDENTITY, 0.0, Mat33f::ZERO);
+            world.add_body(body, Some(Collider::cuboid(Vec3f::new(20.0, 0.5, 20.0))))
+        };
+        let cube = |world: &mut World<f32>, position: Vec3f| {
+            let half = Vec3f::new(0.5, 0.5, 0.5);
+            let body = RigidBody::new(position, Quatf::IDENTITY, 1.0, box_inertia(1.0, half));
+            world.add_body(body, Some(Collider::cuboid(half)))
+        };
+
+        // A stack of five boxes comes to rest where it was built.
+        let mut world = World::new(gravity);
+        ground(&mut world);
+        let boxes: Vec<usize> = (0..5).map(|i| cube(&mut world, Vec3f::new(0.0, 0.5 + i as f32, 0.0))).collect();
+        for _ in 0..300 {
+            world.step(1.0 / 60.0);
+        }
+        for (i, &index) in boxes.iter().enumerate() {
+            let body = &world.bodies[index];
+            assert!((body.position - Vec3f::new(0.0, 0.5 + i as f32, 0.0)).length() < 0.05, "box {} at {}", i, body.position);
+            assert!(body.linear_velocity.length() < 0.05 && body.angular_velocity.length() < 0.05);
+            assert!((body.orientation * Vec3f::Y).angle_between(&Vec3f::Y) < 0.02);
+        }
+        assert_eq!(world.manifolds().len(), 5);
+        assert!(world.manifolds().iter().all(|manifold| manifold.points.len() == 4 && manifold.normal.y > 0.99));
+
+        // A box dropped on its edge tips over onto a face.
+        let mut world = World::new(gravity);
+        ground(&mut world);
+        let tilted = cube(&mut world, Vec3f::new(0.0, 1.5, 0.0));
+        world.bodies[tilted].orientation = Quatf::from_axis_angle(Vec3f::new(1.0, 0.0, 1.0).get_normalized(), 0.5);
+        for _ in 0..300 {
+            world.step(1.0 / 60.0);
+        }
+        let body = &world.bodies[tilted];
+        assert_near!(body.position.y, 0.5, 0.02);
+        assert!([Vec3f::X, Vec3f::Y, Vec3f::Z].iter().any(|&axis| (body.orientation * axis).y.abs() > 0.999));
+
+        // The same staggered stack stepped twice gives identical results.
+        let staggered = || {
+            let mut world = World::new(gravity);
+            ground(&mut world);
+            for i in 0..4 {
+                cube(&mut world, Vec3f::new(if i % 2 == 0 { 0.1 } else { -0.1 }, 0.5 + i as f32 * 1.01, 0.05 * i as f32));
+            }
+            for _ in 0..120 {
+                world.step(1.0 / 60.0);
+            }
+            world
+        };
+        assert_eq!(staggered().bodies, staggered().bodies);
+
+        // A sphere dropped from 5 bounces back to about a quarter of that.
+        let mut world = World::new(gravity);
+        ground(&mut world);
+        let body = RigidBody::new(Vec3f::new(0.0, 5.5, 0.0), Quatf::IDENTITY, 1.0, sphere_inertia(1.0, 0.5));
+        let ball = world.add_body(body, Some(Collider { restitution: 0.5, ..Collider::sphere(0.5) }));
+        let mut bounced = false;
+        let mut apex = 0.0f32;
+        for _ in 0..240 {
+            world.step(1.0 / 120.0);
+            let body = &world.bodies[ball];
+            bounced |= body.linear_velocity.y > 0.0;
+            if bounced {
+                apex = apex.max(body.position.y - 0.5);
+            }
+        }
+        assert!(bounced);
+        assert_near!(apex, 1.25, 0.2);
+        assert!(world.manifolds().is_empty() || world.manifolds()[0].normal.y > 0.99);
+
+        // A sliding box stops after v^2 / (2 mu g).
+        let mut world = World::new(gravity);
+        ground(&mut world);
+        let slider = cube(&mut world, Vec3f::new(0.0, 0.5, 0.0));
+        world.bodies[slider].linear_velocity = Vec3f::new(4.0, 0.0, 0.0);
+        for _ in 0..180 {
+            world.step(1.0 / 60.0);
+        }
+        let body = &world.bodies[slider];
+        assert_near!(body.position.x, 16.0 / (2.0 * 0.5 * 9.81), 0.15);
+        assert!(body.linear_velocity.length() < 0.01);
+
+        // Joints to a static body hold their anchors.
+        let mut world = World::new(gravity);
+        let anchor = world.add_body(RigidBody::new(Vec3f::ZERO, Quatf::IDENTITY, 0.0, Mat33f::ZERO), None);
+        let body = RigidBody::new(Vec3f::new(2.0, 0.0, 0.0), Quatf::IDENTITY, 1.0, sphere_inertia(1.0, 0.3));
+        let pendulum = world.add_body(body, Some(Collider::sphere(0.3)));
+        world.add_ball_joint(anchor, pendulum, Vec3f::ZERO);
+        let body = RigidBody::new(Vec3f::new(0.0, 0.0, 3.0), Quatf::IDENTITY, 1.0, sphere_inertia(1.0, 0.3));
+        let rope = world.add_body(body, Some(Collider::sphere(0.3)));
+        world.add_distance_joint(anchor, rope, Vec3f::new(0.0, 0.0, 1.0), Vec3f::new(0.0, 0.0, 3.0));
+        let mut lowest = 0.0f32;
+        for _ in 0..240 {
+            world.step(1.0 / 120.0);
+            lowest = lowest.min(world.bodies[pendulum].position.y);
+            let pendulum = &world.bodies[pendulum];
+            assert!((pendulum.position + pendulum.orientation * Vec3f::new(-2.0, 0.0, 0.0)).length() < 0.05);
+            assert_near!((world.bodies[rope].position - Vec3f::new(0.0, 0.0, 1.0)).length(), 2.0, 0.05);
+        }
+        assert!(lowest < -1.9);
+
+        // A hinged door only turns about its hinge.
+        let mut world = World::new(Vec3f::ZERO);
+        let frame = world.add_body(RigidBody::new(Vec3f::ZERO, Quatf::IDENTITY, 0.0, Mat33f::ZERO), None);
+        let half = Vec3f::new(1.0, 2.0, 0.1);
+        let door = world.add_body(RigidBody::new(Vec3f::new(1.0, 0.0, 0.0), Quatf::IDENTITY, 2.0, box_inertia(2.0, half)), None);
+        world.add_hinge_joint(frame, door, Vec3f::ZERO, Vec3f::Y);
+        world.bodies[door].angular_velocity = Vec3f::new(0.5, 1.0, -0.5);
+        world.bodies[door].linear_velocity = Vec3f::new(0.0, 1.0, 0.0);
+        for _ in 0..120 {
+            world.step(1.0 / 60.0);
+            let door = &world.bodies[door];
+            assert!((door.orientation * Vec3f::Y - Vec3f::Y).length() < 0.05);
+            assert!((door.position + door.orientation * Vec3f::new(-1.0, 0.0, 0.0)).length() < 0.05);
+        }
+        let door = &world.bodies[door];
+        assert!(door.angular_velocity.y.abs() > 0.1);
+        assert!((door.orientation * Vec3f::X).angle_between(&Vec3f::X) > 0.2);
+    }
+
+    #[test]
+    fn test_eigen() {
+        let check = |m: Mat33f| {
+            let (values, vectors) = m.symmetric_eigen();
+            assert!(values.x >= values.y && values.y >= values.z);
+            assert_near!(vectors.det(), 1.0, DELTA_F32);
+            let identity = vectors.get_transposed() * vectors;
+            for column in 0..3 {
+                for row in 0..3 {
+                    assert_near!(identity.data[column][row], if column == row { 1.0 } else { 0.0 }, DELTA_F32);
+                }
+                let v = vectors.column(column);
+                let scale = 1.0 + values[column].abs();
+                assert!((m * v - v * values[column]).length() < 0.001 * scale, "{} is not an eigenvector of {}", v, m);
+            }
+            (values, vectors)
+        };
+
+        let mut rng = Lcg(41);
+        for _ in 0..50 {
+            let (a, b, c) = (rng.vec3(-5.0, 5.0), rng.vec3(-5.0, 5.0), rng.vec3(-5.0, 5.0));
+            let m = Mat33f::from_axes(Vec3f::new(a.x, a.y, a.z), Vec3f::new(a.y, b.y, b.z), Vec3f::new(a.z, b.z, c.z));
+            check(m);
+        }
+
+        // Repeated and already diagonal values.
+        let (values, _) = check(Mat33f::IDENTITY);
+        assert_eq!(values, Vec3f::new(1.0, 1.0, 1.0));
+        let (values, vectors) = check(Mat33f::from_axes(Vec3f::new(1.0, 0.0, 0.0), Vec3f::new(0.0, 3.0, 0.0), Vec3f::new(0.0, 0.0, 2.0)));
+        assert_eq!(values, Vec3f::new(3.0, 2.0, 1.0));
+        assert!((vectors.column(0) - Vec3f::Y).length() < DELTA_F32);
+
+        // Only the upper triangle is read.
+        let mut upper = Mat33f::from_axes(Vec3f::new(2.0, 0.0, 0.0), Vec3f::new(1.0, 2.0, 0.0), Vec3f::new(0.0, 0.0, 1.0));
+        assert_eq!(upper.symmetric_eigen().0, {
+            upper.data[0][1] = 1.0;
+            check(upper).0
+        });
+
+        // The principal axes of a turned box's inertia are its own axes.
+        let rotation = Quatf::from_axis_angle(Vec3f::new(1.0, -2.0, 0.5).get_normalized(), 1.1);
+        let inertia = rotate_inertia(box_inertia(12.0, Vec3f::new(1.0, 2.0, 3.0)), rotation);
+        let (values, vectors) = check(inertia);
+        assert!((values - Vec3f::new(52.0, 40.0, 20.0)).length() < 0.001);
+        assert!((vectors.column(2) % (rotation * Vec3f::Z)).length() < 0.001);
+
+        // Points spread along a direction have it as their main axis.
+        let direction = Vec3f::new(1.0, 2.0, 2.0).get_normalized();
+        let points: Vec<Vec3f> = (0..200).map(|_| direction * rng.range(-10.0, 10.0) + rng.vec3(-0.5, 0.5) + Vec3f::new(3.0, 4.0, 5.0)).collect();
+        let (values, vectors) = check(covariance(&points));
+        assert!((vectors.column(0) % direction).length() < 0.01);
+        assert!(values.x > 20.0 * values.y);
+        assert_eq!(covariance(&[]), Mat33f::ZERO);
+        let line = [Vec3f::new(-1.0, 0.0, 0.0), Vec3f::new(1.0, 0.0, 0.0)];
+        assert_eq!(covariance(&line), Mat33f::from_axes(Vec3f::X, Vec3f::ZERO, Vec3f::ZERO));
+
+        // The two by two case.
+        for m in [Mat22f::from_axes(Vec2f::new(2.0, 1.0), Vec2f::new(1.0, 2.0)),
+            Mat22f::from_axes(Vec2f::new(1.0, 0.0), Vec2f::new(0.0, 4.0)),
+            Mat22f::from_axes(Vec2f::new(-3.0, 2.5), Vec2f::new(2.5, 0.5))] {
+            let (values, vectors) = m.symmetric_eigen();
+            assert!(values.x >= values.y);
+            assert_near!(vectors.det(), 1.0, DELTA_F32);
+            for i in 0..2 {
+                let v = Vec2f::new(vectors.data[i][0], vectors.data[i][1]);
+                let image = m * v - v * values[i];
+                assert!(image.x.abs() < DELTA_F32 && image.y.abs() < DELTA_F32);
+            }
+        }
+        let (values, _) = Mat22f::from_axes(Vec2f::new(2.0, 1.0), Vec2f::new(1.0, 2.0)).symmetric_eigen();
+        assert!((values.x - 3.0).abs() < DELTA_F32 && (values.y - 1.0).abs() < DELTA_F32);
+    }
+
+    #[test]
+    fn test_svd() {
+        let mat_near = |a: Mat33f, b: Mat33f, delta: f32| {
+            for column in 0..3 {
+                for row in 0..3 {
+                    assert_near!(a.data[column][row], b.data[column][row], delta);
+                }
+            }
+        };
+        let is_rotation = |m: Mat33f| {
+            mat_near(m.get_transposed() * m, Mat33f::IDENTITY, 0.001);
+            assert_near!(m.det(), 1.0, 0.001);
+        };
+        let check = |m: Mat33f| {
+            let (u, sigma, v) = m.svd();
+            is_rotation(u);
+            is_rotation(v);
+            assert!(sigma.x >= sigma.y - DELTA_F32 && sigma.y >= sigma.z.abs() - DELTA_F32);
+            assert!(sigma.x >= 0.0 && sigma.y >= 0.0);
+            let diagonal = Mat33f::from_axes(Vec3f::X * sigma.x, Vec3f::Y * sigma.y, Vec3f::Z * sigma.z);
+            mat_near(u * diagonal * v.get_transposed(), m, 0.001);
+
+            let (rotation, stretch) = m.polar_decompose();
+            is_rotation(rotation);
+            mat_near(stretch, stretch.get_transposed(), 0.001);
+            mat_near(rotation * stretch, m, 0.001);
+            sigma
+        };
+
+        let mut rng = Lcg(42);
+        for _ in 0..50 {
+            let m = Mat33f::from_axes(rng.vec3(-3.0, 3.0), rng.vec3(-3.0, 3.0), rng.vec3(-3.0, 3.0));
+            let sigma = check(m);
+            assert_eq!(sigma.z < 0.0, m.det() < 0.0);
+        }
+
+        // A reflection leaves a negative singular value and a rotation that
+        // survives a round trip through Quat.
+        let turn = Quatf::from_axis_angle(Vec3f::new(0.3, 1.0, -0.2).get_normalized(), 2.0);
+        let turn_matrix = Mat33f::from_mat44(&turn.to_matrix());
+        let mirrored = turn_matrix * Mat33f::from_axes(Vec3f::X * -1.0, Vec3f::Y * 2.0, Vec3f::Z * 0.5);
+        let sigma = check(mirrored);
+        assert!((sigma - Vec3f::new(2.0, 1.0, -0.5)).length() < 0.001);
+        let (rotation, stretch) = mirrored.polar_decompose();
+        assert_near!(stretch.det(), -1.0, 0.001);
+        let quat = Quatf::from_matrix(&rotation.to_mat44());
+        mat_near(Mat33f::from_mat44(&quat.to_matrix()), rotation, 0.001);
+
+        // Rank deficient and zero matrices.
+        let flat = Mat33f::from_axes(Vec3f::new(1.0, 2.0, 0.0), Vec3f::new(2.0, 4.0, 0.0), Vec3f::new(0.0, 0.0, 3.0));
+        let sigma = check(flat);
+        assert!(sigma.z.abs() < 0.001);
+        assert_eq!(check(Mat33f::ZERO), Vec3f::ZERO);
+        assert_eq!(Mat33f::IDENTITY.svd(), (Mat33f::IDENTITY, Vec3f::new(1.0, 1.0, 1.0), Mat33f::IDENTITY));
+
+        // A sheared TRS matrix splits into its parts.
+        let shear = Mat33f::from_axes(Vec3f::new(2.0, 0.3, 0.1), Vec3f::new(0.3, 1.0, -0.2), Vec3f::new(0.1, -0.2, 0.5));
+        let translation = Vec3f::new(4.0, -2.0, 1.0);
+        let mut matrix = (turn_matrix * shear).to_mat44();
+        matrix.data[3][0] = translation.x;
+        matrix.data[3][1] = translation.y;
+        matrix.data[3][2] = translation.z;
+
+        let (t, r, s) = matrix.decompose();
+        assert!((t - translation).length() < DELTA_F32);
+        assert_near!(r.dot(&turn).abs(), 1.0, 0.001);
+        mat_near(s, shear, 0.001);
+
+        // Transform keeps the closest rotation and the stretch's diagonal.
+        let transform = Transformf::from_matrix(&matrix);
+        assert_near!(transform.rotation.dot(&turn).abs(), 1.0, 0.001);
+        assert!((transform.scale - Vec3f::new(2.0, 1.0, 0.5)).length() < 0.001);
+        assert!((transform.translation - translation).length() < DELTA_F32);
+    }
+
+    #[test]
+    fn test_factor() {
+        let mat_near = |a: Mat44f, b: Mat44f, delta: f32| {
+            for column in 0..4 {
+                for row in 0..4 {
+                    assert_near!(a.data[column][row], b.data[column][row], delta);
+                }
+            }
+        };
+
+        let mut rng = Lcg(7);
+        for _ in 0..50 {
+            let mut column = || Vec4f::new(rng.range(-2.0, 2.0), rng.range(-2.0, 2.0), rng.range(-2.0, 2.0), rng.range(-2.0, 2.0));
+            let m = Mat44f::from_axes(column(), column(), column(), column());
+            let b = column();
+
+            let condition = m.condition_number().unwrap();
+            if condition > 1000.0 {
+                continue;
+            }
+
+            // Every solve agrees with the cofactor inverse.
+            let expected = m.get_inverted() * b;
+            let delta = 0.0001 * condition;
+            assert!((m.solve(b).unwrap() - expected).length() < delta);
+            assert!((m.solve_least_squares(b) - expected).length() < delta);
+            assert_eq!(m.rank(), 4);
+
+            let (p, l, u) = m.lu();
+            mat_near(p * m, l * u, 0.001);
+
+            let (q, r) = m.qr();
+            mat_near(q.get_transposed() * q, Mat44f::IDENTITY, 0.001);
+            mat_near(q * r, m, 0.001);
+            for column in 0..4 {
+                for row in column + 1..4 {
+                    assert_eq!(r.data[column][row], 0.0);
+                }
+            }
+
+            // M^T M is symmetric positive definite.
+            let spd = m.get_transposed() * m;
+            let cholesky = spd.cholesky().unwrap();
+            mat_near(cholesky * cholesky.get_transposed(), spd, 0.001 * condition);
+            assert!((spd.solve_cholesky(b).unwrap() - spd.get_inverted() * b).length() < 0.0001 * condition * condition);
+        }
+
+        // Smaller matrices use the same algorithms.
+        let m = Mat33f::from_axes(Vec3f::new(4.0, 1.0, 0.0), Vec3f::new(1.0, 3.0, 1.0), Vec3f::new(0.0, 1.0, 2.0));
+        let b = Vec3f::new(1.0, -2.0, 3.0);
+        assert!((m.solve(b).unwrap() - m.get_inverted() * b).length() < DELTA_F32);
+        assert!((m.solve_cholesky(b).unwrap() - m.get_inverted() * b).length() < DELTA_F32);
+        let m = Mat22f::from_axes(Vec2f::new(0.0, 2.0), Vec2f::new(3.0, 1.0));
+        let b = Vec2f::new(1.0, 1.0);
+        assert!((m.solve(b).unwrap() - m.get_inverted() * b).length() < DELTA_F32);
+        assert!(m.cholesky().is_none());
+
+        // Singular matrices have no solve, but a least squares solution.
+        let singular = Mat33f::from_axes(Vec3f::X, Vec3f::Y, Vec3f::X + Vec3f::Y);
+        assert_eq!(singular.rank(), 2);
+        assert!(singular.solve(Vec3f::X).is_none());
+        assert!(singular.condition_number().is_none());
+        let x = singular.solve_least_squares(Vec3f::new(1.0, 2.0, 5.0));
+        assert!((singular * x - Vec3f::new(1.0, 2.0, 0.0)).length() < DELTA_F32);
+        assert_eq!(Mat33f::ZERO.rank(), 0);
+        assert_eq!(Mat33f::IDENTITY.condition_number(), Some(1.0));
+
+        // Fitting a plane z = a x + b y + c to noiseless points.
+        let rows: Vec<Vec3f> = (0..20).map(|_| Vec3f::new(rng.range(-5.0, 5.0), rng.range(-5.0, 5.0), 1.0)).collect();
+        let values: Vec<f32> = rows.iter().map(|row| 2.0 * row.x - 3.0 * row.y + 0.5).collect();
+        let fit = Mat33f::least_squares(&rows, &values);
+        assert!((fit - Vec3f::new(2.0, -3.0, 0.5)).length() < 0.001);
+        assert!(std::panic::catch_unwind(|| Mat33f::least_squares(&rows, &values[1..])).is_err());
+    }
+
+    #[test]
+    fn test_matrix() {
+        let mut rng = Lcg(11);
+        let mut column = || Vec4f::new(rng.range(-2.0, 2.0), rng.range(-2.0, 2.0), rng.range(-2.0, 2.0), rng.range(-2.0, 2.0));
+        let mut random_mat44 = || Mat44f::from_axes(column(), column(), column(), column());
+        let a = random_mat44();
+        let b = random_mat44();
+
+        // The generic product and transpose agree with the fixed size ones.
+        let product: Mat44f = (Matrixf::<4, 4>::from(a) * Matrixf::<4, 4>::from(b)).into();
+        for column in 0..4 {
+            for row in 0..4 {
+                assert_near!(product.data[column][row], (a * b).data[column][row], 0.0001);
+            }
+        }
+        assert_eq!(Mat44f::from(Matrixf::<4, 4>::from(a).get_transposed()), a.get_transposed());
+        assert_eq!(Mat33f::from(Matrixf::<3, 3>::IDENTITY), Mat33f::IDENTITY);
+        let turn = Mat22f::from_axes(Vec2f::new(0.0, 1.0), Vec2f::new(-1.0, 0.0));
+        assert_eq!(Mat22f::from(Matrixf::<2, 2>::from(turn)), turn);
+
+        // A 3x4 affine matrix maps homogeneous points to 3D points.
+        let model = Mat44f::IDENTITY.get_translated(Vec3f::new(1.0, 2.0, 3.0)).get_rotated(Vec3f::Y, 0.5);
+        let affine = Matrixf::<3, 4>::from(model);
+        let point = Vec3f::new(0.5, -1.0, 2.0);
+        let mapped: Vec3f = (affine * Vectorf::<4>::from(Vec4f::new(point.x, point.y, point.z, 1.0))).into();
+        assert!((mapped - (model * Vec4f::new(point.x, point.y, point.z, 1.0)).xyz()).length() < DELTA_F32);
+        assert_eq!(Mat44f::from(affine), model);
+
+        // Rectangular shapes follow the inner dimension.
+        let wide = Matrixf::<2, 3>::from_rows([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+        let square: Matrixf<2, 2> = wide * wide.get_transposed();
+        assert_eq!(square, Matrixf::<2, 2>::from_rows([[14.0, 32.0], [32.0, 77.0]]));
+        assert_eq!(wide[(1, 2)], 6.0);
+        assert_eq!(wide.row(1).dot(&Vectorf::<3>::from_array([1.0, 1.0, 1.0])), 15.0);
+        assert_eq!((wide.get_transposed() * wide).trace(), 91.0);
+        assert_eq!(wide - wide, Matrixf::<2, 3>::ZERO);
+        assert_eq!(-wide + wide * 2.0, wide);
+
+        // A 6x6 spatial inertia solved with the shared factorizations.
+        let inertia = Mat33f::from_axes(Vec3f::new(2.0, 0.1, 0.0), Vec3f::new(0.1, 3.0, 0.2), Vec3f::new(0.0, 0.2, 4.0));
+        let mut spatial = Matrixf::<6, 6>::from_diagonal(Vectorf::<6>::from_array([0.0, 0.0, 0.0, 5.0, 5.0, 5.0]));
+        for column in 0..3 {
+            for row in 0..3 {
+                spatial[(row, column)] = inertia.data[column][row];
+            }
+        }
+        let force = Vectorf::<6>::from_array([1.0, -2.0, 0.5, 10.0, 0.0, -5.0]);
+        let acceleration = spatial.solve(force).unwrap();
+        assert!((spatial * acceleration - force).length() < 0.0001);
+        assert!((spatial.solve_cholesky(force).unwrap() - acceleration).length() < 0.0001);
+        let angular: Vec3f = Vectorf::<3>::from_array([acceleration[0], acceleration[1], acceleration[2]]).into();
+        assert!((angular - inertia.get_inverted() * Vec3f::new(1.0, -2.0, 0.5)).length() < 0.0001);
+        assert_near!(acceleration[3], 2.0, DELTA_F32);
+    }
+
+    #[test]
+    fn test_dynamic() {
+        // Products match the fixed size types.
+        let model = Mat44f::IDENTITY.get_translated(Vec3f::new(1.0, -2.0, 0.5)).get_rotated(Vec3f::X, 0.7);
+        let view = Mat44f::IDENTITY.get_rotated(Vec3f::Z, -0.3).get_translated(Vec3f::new(0.0, 4.0, 0.0));
+        let product = &DMatrixf::from(view) * &DMatrixf::from(model);
+        assert!((product.get_mat44(0, 0) - view * model).data.iter().flatten().all(|v| v.abs() < DELTA_F32));
+
+        let point = Vec4f::new(0.3, -0.4, 2.0, 1.0);
+        let transformed = &DMatrixf::from(model) * &DVectorf::from_vec(vec![point.x, point.y, point.z, point.w]);
+        let expected = model * point;
+        assert!((transformed.get_vec3(0) - expected.xyz()).length() < DELTA_F32);
+        assert_near!(transformed[3], 1.0, DELTA_F32);
+
+        // Blocks and packed points copy in and out.
+        let mut big = DMatrixf::zeros(8, 8);
+        big.set_mat44(4, 2, &model);
+        assert_eq!(big.get_mat44(4, 2), model);
+        assert_eq!(big[(5, 3)], model.data[1][1]);
+        assert_eq!(big.get_transposed()[(3, 5)], model.data[1][1]);
+
+        let points = vec![Vec3f::new(1.0, 2.0, 3.0), Vec3f::new(-1.0, 0.5, 4.0)];
+        let mut packed = DVectorf::from_vec3s(&points);
+        assert_eq!(packed.len(), 6);
+        packed.set_vec3(1, Vec3f::Z);
+        assert_eq!(packed.to_vec3s(), vec![points[0], Vec3f::Z]);
+        assert_eq!(DVectorf::from(Vec3f::Y).data, vec![0.0, 1.0, 0.0]);
+
+        // BLAS level 1 and 2.
+        let mut y = DVectorf::from_vec(vec![1.0, 2.0, 3.0]);
+        y.axpy(2.0, &DVectorf::from_vec(vec![1.0, -1.0, 0.5]));
+        assert_eq!(y.data, vec![3.0, 0.0, 4.0]);
+        assert_eq!(y.length(), 5.0);
+        assert_eq!(y.abs_sum(), 7.0);
+        let a = DMatrixf::from_rows(2, 3, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let mut out = DVectorf::from_vec(vec![1.0, 1.0]);
+        a.gemv(2.0, &DVectorf::from_vec(vec![1.0, 0.0, -1.0]), 3.0, &mut out);
+        assert_eq!(out.data, vec![-1.0, -1.0]);
+
+        // A zero beta overwrites the output, and empty matrices are fine.
+        let mut out = DVectorf::from_vec(vec![f32::NAN, f32::INFINITY]);
+        a.gemv(1.0, &DVectorf::from_vec(vec![1.0, 0.0, -1.0]), 0.0, &mut out);
+        assert_eq!(out.data, vec![-2.0, -2.0]);
+        let mut c = DMatrixf::from_rows(2, 1, &[f32::NAN, f32::NAN]);
+        a.gemm(1.0, &DMatrixf::from_rows(3, 1, &[1.0, 0.0, -1.0]), 0.0, &mut c);
+        assert_eq!(c.data, vec![-2.0, -2.0]);
+        assert!((&DMatrixf::zeros(0, 3) * &DVectorf::zeros(3)).is_empty());
+
+        // Fitting a cubic through samples by the normal equations, solved
+        // by LU and by conjugate gradients.
+        let samples: Vec<(f32, f32)> = (0..20).map(|i| {
+            let t = i as f32 / 19.0 * 2.0 - 1.0;
+            (t, 0.5 - t + 2.0 * t * t * t)
+        }).collect();
+        let mut design = DMatrixf::zeros(samples.len(), 4);
+        for (row, (t, _)) in samples.iter().enumerate() {
+            for power in 0..4 {
+                design[(row, power)] = t.powi(power as i32);
+            }
+        }
+        let values = DVectorf::from_vec(samples.iter().map(|(_, v)| *v).collect());
+        let transposed = design.get_transposed();
+        let normal = &transposed * &design;
+        let rhs = &transposed * &values;
+
+        let lu = normal.solve(&rhs).unwrap();
+        let cg = normal.conjugate_gradient(&rhs, 1e-6, 100).unwrap();
+        for (x, expected) in lu.data.iter().zip([0.5, -1.0, 0.0, 2.0]) {
+            assert_near!(*x, expected, 0.001);
+        }
+        assert!((&lu - &cg).length() < 0.001);
+        assert!((&(&normal * &lu) - &rhs).length() < 0.001);
+
+        assert!(DMatrixf::zeros(3, 3).solve(&DVectorf::zeros(3)).is_none());
+        assert_eq!(DMatrixf::identity(3).solve(&y), Some(y.clone()));
+    }
+
+    #[test]
+    fn test_sparse() {
+        // Repeated triplets are summed and rows come out sorted.
+        let mut builder = TripletBuilder::<f32>::new(3, 3);
+        builder.add(2, 0, 1.0);
+        builder.add(0, 2, 4.0);
+        builder.add(0, 0, 2.0);
+        builder.add(2, 0, 0.5);
+        let small = builder.build();
+        assert_eq!(small.non_zeros(), 3);
+        assert_eq!(small.row_offsets, vec![0, 2, 2, 3]);
+        assert_eq!(small.get(2, 0), 1.5);
+        assert_eq!(small.get(1, 1), 0.0);
+        assert_eq!(small.mul_vector(&DVectorf::from_vec(vec![1.0, 2.0, 3.0])).data, vec![14.0, 0.0, 1.5]);
+        assert_eq!(small.mul_vec3(&[Vec3f::X, Vec3f::Y, Vec3f::Z]), vec![Vec3f::X * 2.0 + Vec3f::Z * 4.0, Vec3f::ZERO, Vec3f::X * 1.5]);
+
+        // A jittered, bumpy grid mesh.
+        let size = 16;
+        let mut rng = Lcg(5);
+        let mut flat = Vec::new();
+        let mut bumpy = Vec::new();
+        for j in 0..size {
+            for i in 0..size {
+                let interior = i > 0 && j > 0 && i < size - 1 && j < size - 1;
+                let jitter = if interior { 0.3 } else { 0.0 };
+                let p = Vec3f::new(i as f32 + rng.range(-jitter, jitter), j as f32 + rng.range(-jitter, jitter), 0.0);
+                flat.push(p);
+                bumpy.push(p + Vec3f::Z * rng.range(-1.0, 1.0));
+            }
+        }
+        let mut triangles = Vec::new();
+        for j in 0..size - 1 {
+            for i in 0..size - 1 {
+                let corner = j * size + i;
+                triangles.push([corner, corner + 1, corner + size + 1]);
+                triangles.push([corner, corner + size + 1, corner + size]);
+            }
+        }
+
+        // The Laplacian is symmetric with zero row sums, and vanishes on
+        // the interior of a flat mesh.
+        let laplacian = cotangent_laplacian(&flat, &triangles);
+        for row in 0..laplacian.rows {
+            assert_near!(laplacian.row(row).map(|(_, v)| v).sum::<f32>(), 0.0, 0.0001);
+            for (column, value) in laplacian.row(row) {
+                assert_near!(laplacian.get(column, row), value, 0.0001);
+            }
+        }
+        let flat_laplacian = laplacian.mul_vec3(&flat);
+        for j in 1..size - 1 {
+            for i in 1..size - 1 {
+                assert!(flat_laplacian[j * size + i].length() < 0.001);
+            }
+        }
+
+        // Implicit smoothing (I + t L) x = x0 of the bumpy mesh.
+        let system = CsrMatrix::<f32>::identity(bumpy.len()).add_scaled(1.0, &cotangent_laplacian(&bumpy, &triangles), 2.0);
+        let solve = |preconditioner, iterations| system.conjugate_gradient(&bumpy, &bumpy, preconditioner, 1e-5, iterations);
+        let plain = solve(Preconditioner::None, 500).unwrap();
+        let jacobi = solve(Preconditioner::Jacobi, 500).unwrap();
+        let incomplete = solve(Preconditioner::IncompleteCholesky, 500).unwrap();
+
+        let residual = system.mul_vec3(&incomplete).iter().zip(&bumpy).fold(0.0f32, |sum, (a, b)| sum + (*a - *b) * (*a - *b));
+        assert!(residual.sqrt() < 0.001);
+        for i in 0..bumpy.len() {
+            assert!((plain[i] - incomplete[i]).length() < 0.001);
+            assert!((jacobi[i] - incomplete[i]).length() < 0.001);
+        }
+
+        // Incomplete Cholesky needs far fewer iterations.
+        assert!(solve(Preconditioner::IncompleteCholesky, 15).is_some());
+        assert!(solve(Preconditioner::None, 15).is_none());
+        assert!(solve(Preconditioner::Jacobi, 15).is_none());
+
+        // The heights match a dense LU solve.
+        let mut dense = DMatrixf::zeros(system.rows, system.columns);
+        for row in 0..system.rows {
+            for (column, value) in system.row(row) {
+                dense[(row, column)] = value;
+            }
+        }
+        let heights = dense.solve(&DVectorf::from_vec(bumpy.iter().map(|p| p.z).collect())).unwrap();
+        for (p, z) in incomplete.iter().zip(&heights.data) {
+            assert_near!(p.z, *z, 0.001);
+        }
+        assert!(heights.data.iter().map(|z| z.abs()).fold(0.0, f32::max) < bumpy.iter().map(|p| p.z.abs()).fold(0.0, f32::max));
+    }
+
+    #[test]
+    fn test_roots() {
+        let close = |roots: Vec<f64>, expected: &[f64], delta: f64| {
+            assert_eq!(roots.len(), expected.len(), "{:?} vs {:?}", roots, expected);
+            for (root, expected) in roots.iter().zip(expected) {
+                assert!((root - expected).abs() < delta, "{:?} vs {:?}", roots, expected);
+            }
+        };
+
+        close(quadratic_roots(1.0, -3.0, 2.0), &[1.0, 2.0], 1e-12);
+        close(quadratic_roots(1.0, 2.0, 5.0), &[], 0.0);
+        close(quadratic_roots(9.0, -6.0, 1.0), &[1.0 / 3.0, 1.0 / 3.0], 1e-9);
+        close(quadratic_roots(0.0, 2.0, -1.0), &[0.5], 1e-12);
+
+        close(cubic_roots(1.0, -6.0, 11.0, -6.0), &[1.0, 2.0, 3.0], 1e-9);
+        close(cubic_roots(2.0, 0.0, 0.0, -16.0), &[2.0], 1e-12);
+        close(cubic_roots(1.0, -3.0, 3.0, -1.0), &[1.0, 1.0, 1.0], 1e-5);
+        close(cubic_roots(1.0, -4.0, 5.0, -2.0), &[1.0, 1.0, 2.0], 1e-6);
+
+        close(quartic_roots(1.0, -10.0, 35.0, -50.0, 24.0), &[1.0, 2.0, 3.0, 4.0], 1e-9);
+        close(quartic_roots(1.0, 0.0, -5.0, 0.0, 4.0), &[-2.0, -1.0, 1.0, 2.0], 1e-12);
+        close(quartic_roots(1.0, 0.0, 1.0, 0.0, 1.0), &[], 0.0);
+        close(quartic_roots(1.0, -2.0, 2.0, -2.0, 1.0), &[1.0, 1.0], 1e-6);
+
+        // Random real roots, with complex pairs mixed in for the quartic.
+        let mut rng = Lcg(3);
+        for _ in 0..200 {
+            let mut roots: Vec<f64> = (0..4).map(|_| rng.range(-10.0, 10.0) as f64).collect();
+            let p = Polynomial::<f64>::from_roots(&roots);
+            let c = &p.coefficients;
+            roots.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            let separation = roots.windows(2).map(|w| w[1] - w[0]).fold(f64::MAX, f64::min);
+            if separation > 0.01 {
+                close(quartic_roots(c[4], c[3], c[2], c[1], c[0]), &roots, 1e-6);
+                close(p.roots(), &roots, 1e-6);
+            }
+
+            let cubic = Polynomial::<f64>::from_roots(&roots[..3]);
+            let c = &cubic.coefficients;
+            if roots[1] - roots[0] > 0.01 && roots[2] - roots[1] > 0.01 {
+                close(cubic_roots(c[3], c[2], c[1], c[0]), &roots[..3], 1e-6);
+            }
+
+            let (re, im) = (rng.range(-5.0, 5.0) as f64, rng.range(0.1, 5.0) as f64);
+            let pair = Polynomial::<f64>::new(vec![re * re + im * im, -2.0 * re, 1.0]);
+            let mixed = &Polynomial::<f64>::from_roots(&roots[..2]) * &pair;
+            let c = &mixed.coefficients;
+            if roots[1] - roots[0] > 0.01 {
+                close(quartic_roots(c[4], c[3], c[2], c[1], c[0]), &roots[..2], 1e-6);
+            }
+        }
+
+        // Sturm sequences count distinct roots, including repeated ones.
+        let p = &Polynomial::<f64>::from_roots(&[-2.0, 0.5, 0.5, 3.0]) * &Polynomial::<f64>::new(vec![1.0, 0.0, 1.0]);
+        assert_eq!(p.degree(), 6);
+        assert_eq!(p.count_roots(-10.0, 10.0), 3);
+        assert_eq!(p.count_roots(0.0, 1.0), 1);
+        assert_eq!(p.count_roots(-2.0, 3.0), 2);
+        assert_eq!(p.isolate_roots(-10.0, 10.0).len(), 3);
+        close(p.roots(), &[-2.0, 0.5, 3.0], 1e-6);
+        close(p.roots_between(0.0, 10.0), &[0.5, 3.0], 1e-6);
+        assert_near!(p.derivative().evaluate(1.5), (p.evaluate(1.5 + 1e-6) - p.evaluate(1.5 - 1e-6)) / 2e-6, 1e-4);
+        let (quotient, remainder) = p.div_rem(&Polynomial::<f64>::from_roots(&[0.5, 3.0]));
+        assert!(remainder.is_zero());
+        assert_eq!(quotient.degree(), 4);
+
+        // Scalar root finders on cos(x) = x.
+        let f = |x: f64| x.cos() - x;
+        let expected = 0.7390851332151607;
+        assert_near!(newton(|x: f64| (x.cos() - x, -x.sin() - 1.0), 1.0, 1e-12, 20).unwrap(), expected, 1e-12);
+        assert_near!(bisection(f, 0.0, 1.0, 1e-12, 100).unwrap(), expected, 1e-11);
+        assert_near!(brent(f, 0.0, 1.0, 1e-12, 100).unwrap(), expected, 1e-11);
+        assert!(brent(f, 1.0, 2.0, 1e-12, 100).is_none());
+        assert!(newton(|_: f64| (1.0, 0.0), 0.0, 1e-12, 10).is_none());
+        assert_near!(brent(|x: f32| x * x - 2.0, 0.0, 2.0, 1e-6, 100).unwrap(), 2.0f32.sqrt(), 1e-6);
+
+        // A ray against a torus around z with radii 2 and 0.5, whose
+        // implicit form is (|p|^2 + R^2 - r^2)^2 - 4 R^2 (x^2 + y^2).
+        let (major, minor) = (2.0, 0.5);
+        let origin = [-5.0, 0.1, 0.2];
+        let direction = [1.0, 0.05, -0.02];
+        let axis = |i: usize| Polynomial::<f64>::new(vec![origin[i], direction[i]]);
+        let planar = &(&axis(0) * &axis(0)) + &(&axis(1) * &axis(1));
+        let squared = &(&planar + &(&axis(2) * &axis(2))) + &Polynomial::<f64>::new(vec![major * major - minor * minor]);
+        let torus = &(&squared * &squared) - &planar.scaled(4.0 * major * major);
+        let c = &torus.coefficients;
+        let hits = quartic_roots(c[4], c[3], c[2], c[1], c[0]);
+        assert_eq!(hits.len(), 4);
+        for t in &hits {
+            let point = [0, 1, 2].map(|i| origin[i] + direction[i] * t);
+            let ring = ((point[0] * point[0] + point[1] * point[1]).sqrt() - major).hypot(point[2]);
+            assert_near!(ring, minor, 1e-9);
+        }
+        close(torus.roots(), &hits, 1e-6);
+    }
+
+    #[test]
+    fn test_dual() {
+        type J3 = Jet<f64, 3>;
+        type J4 = Jet<f64, 4>;
+
+        let (value, slope) = derivative(|x| x * x * x - x / Dual::<f64>::constant(2.0), 2.0);
+        assert_eq!((value, slope), (7.0, 11.5));
+
+        // The gradient of the length is the unit direction.
+        let (length, grad) = gradient(|[x, y, z]: [J3; 3]| Vec3::<J3>::new(x, y, z).length(), [1.0, 2.0, 2.0]);
+        assert_near!(length, 3.0, 1e-12);
+        for (g, expected) in grad.iter().zip([1.0 / 3.0, 2.0 / 3.0, 2.0 / 3.0]) {
+            assert_near!(*g, expected, 1e-12);
+        }
+        let (_, grad) = gradient(|[x, y, z]: [J3; 3]| Vec3::<J3>::new(x, y, z).length(), [0.0, 0.0, 0.0]);
+        assert_eq!(grad, [0.0, 0.0, 0.0]);
+
+        // Projecting a point through a yawing camera, differentiated with
+        // respect to the point and the yaw through Quat::to_matrix and Mat44
+        // products.
+        let project = |[x, y, z, yaw]: [J4; 4]| {
+            let c = J4::constant;
+            let rotation = Quat::<J4>::from_axis_angle(Vec3::<J4>::Y, yaw);
+            let view = rotation.conjugate().to_matrix() * Mat44::<J4>::IDENTITY.get_translated(Vec3::<J4>::new(c(-0.5), c(-1.0), c(-5.0)));
+            let (focal, near, far) = (c(1.5), c(0.1), c(100.0));
+            let projection = Mat44::<J4>::from_axes(
+                Vec4::<J4>::new(focal, c(0.0), c(0.0), c(0.0)),
+                Vec4::<J4>::new(c(0.0), focal, c(0.0), c(0.0)),
+                Vec4::<J4>::new(c(0.0), c(0.0), (far + near) / (near - far), c(-1.0)),
+                Vec4::<J4>::new(c(0.0), c(0.0), c(2.0) * far * near / (near - far), c(0.0)));
+            let clip = projection * view * Vec4::<J4>::new(x, y, z, c(1.0));
+            [clip.x / clip.w, clip.y / clip.w]
+        };
+
+        let at = [0.3, 1.2, -1.0, 0.2];
+        let (screen, jac) = jacobian(project, at);
+        assert!(screen.iter().all(|s| s.abs() < 1.0));
+
+        let h = 1e-6;
+        for variable in 0..4 {
+            let shifted = |offset: f64| {
+                let mut inputs = at.map(J4::constant);
+                inputs[variable].value += offset;
+                project(inputs).map(|s| s.value)
+            };
+            let (ahead, behind) = (shifted(h), shifted(-h));
+            for output in 0..2 {
+                assert_near!(jac[(output, variable)], (ahead[output] - behind[output]) / (2.0 * h), 1e-6);
+            }
+        }
+
+        // Comparisons only look at the value.
+        assert!(J4::variable(1.0, 0) == J4::constant(1.0));
+        assert!(Dual::<f32>::variable(1.0, 0) < Dual::<f32>::constant(2.0));
+    }
+
+    #[test]
+    fn test_interval() {
+        type I = Interval<f64>;
+        let i = |lo: f64, hi: f64| I::new(lo, hi);
+        let encloses = |outer: I, lo: f64, hi: f64| outer.lo <= lo && hi <= outer.hi && outer.width() < (hi - lo) + 1e-9;
+
+        assert!(encloses(i(1.0, 2.0) + i(3.0, 4.0), 4.0, 6.0));
+        assert!(encloses(i(1.0, 2.0) - i(3.0, 4.0), -3.0, -1.0));
+        assert!(encloses(i(-1.0, 2.0) * i(3.0, 4.0), -4.0, 8.0));
+        assert!(encloses(i(1.0, 2.0) / i(4.0, 8.0), 0.125, 0.5));
+        assert_eq!((i(1.0, 2.0) / i(-1.0, 1.0)).hi, f64::INFINITY);
+        assert!(encloses(-i(1.0, 2.0), -2.0, -1.0));
+        assert!((i(0.1, 0.1) * i(3.0, 3.0)).contains(0.30000000000000004) && (i(0.1, 0.1) * i(3.0, 3.0)).width() > 0.0);
+        assert!(i(1.0, 2.0) < i(3.0, 4.0));
+        assert!(i(1.0, 3.0).partial_cmp(&i(2.0, 4.0)).is_none());
+
+        // Products that underflow to zero still bound the exact value.
+        let tiny = I::point(1e-200) * I::point(1e-200);
+        assert!(tiny.lo <= 0.0 && tiny.hi > 0.0);
+        let tiny = Interval::<f32>::point(1e-30) * Interval::<f32>::point(-1e-30);
+        assert!(tiny.lo < 0.0 && tiny.hi >= 0.0);
+
+        // Functions bound their extremes inside the interval.
+        let spin = Quat::<I>::from_axis_angle(Vec3::<I>::Z, i(-0.1, 3.2));
+        assert!(spin.w.lo < 0.0 && spin.w.hi >= 1.0);
+        let radius = Vec3::<I>::new(i(3.0, 3.0), i(4.0, 4.0), I::point(0.0)).length();
+        assert!(radius.contains(5.0) && radius.width() < 1e-13);
+
+        // Bounding a box through a Mat44 with dyadic entries, where f64
+        // transforms the corners exactly.
+        let exact = Mat44::<f64>::from_axes(
+            Vec4::<f64>::new(0.0, 1.5, 0.0, 0.0),
+            Vec4::<f64>::new(-0.75, 0.0, 0.25, 0.0),
+            Vec4::<f64>::new(0.125, 0.0, 2.0, 0.0),
+            Vec4::<f64>::new(10.0, -3.0, 0.5, 1.0));
+        let aabb = Aabb3::<f64>::new(Vec3::<f64>::new(-1.0, 0.5, 2.0), Vec3::<f64>::new(3.0, 1.25, 4.5));
+        let to_intervals = |m: &Mat44<f64>| Mat44::<I> { data: m.data.map(|column| column.map(I::point)) };
+
+        let bounds = Vec3::<I>::from(aabb);
+        let mapped = to_intervals(&exact) * Vec4::<I>::new(bounds.x, bounds.y, bounds.z, I::point(1.0));
+        let image = Aabb3::<f64>::from(Vec3::<I>::new(mapped.x, mapped.y, mapped.z));
+
+        let corners: Vec<Vec3<f64>> = (0..8).map(|k| {
+            let corner = Vec4::<f64>::new(
+                if k & 1 == 0 { aabb.min.x } else { aabb.max.x },
+                if k & 2 == 0 { aabb.min.y } else { aabb.max.y },
+                if k & 4 == 0 { aabb.min.z } else { aabb.max.z }, 1.0);
+            let p = exact * corner;
+            Vec3::<f64>::new(p.x, p.y, p.z)
+        }).collect();
+        let tight = Aabb3::<f64>::from_points(&corners).unwrap();
+        for corner in &corners {
+            assert!(image.contains_point(*corner));
+        }
+        assert!((image.min - tight.min).length() < 1e-12 && (image.max - tight.max).length() < 1e-12);
+
+        // A rotation through Quat::to_matrix in f32 intervals encloses the
+        // f64 result, which is far more precise than f32.
+        type F = Interval<f32>;
+        let axis = Vec3::<F>::new(F::point(0.48), F::point(0.6), F::point(0.64));
+        let turn = Quat::<F>::from_axis_angle(axis, F::point(1.1)).to_matrix().get_translated(Vec3::<F>::new(F::point(1.0), F::point(2.0), F::point(-0.5)));
+        let turn64 = Quat::<f64>::from_axis_angle(Vec3::<f64>::new(0.48, 0.6, 0.64), 1.1f32 as f64).to_matrix().get_translated(Vec3::<f64>::new(1.0, 2.0, -0.5));
+        let (lo, hi) = (Vec3::<f32>::new(-1.0, 0.5, 2.0), Vec3::<f32>::new(3.0, 1.25, 4.5));
+        let mapped = turn * Vec4::<F>::new(F::new(lo.x, hi.x), F::new(lo.y, hi.y), F::new(lo.z, hi.z), F::point(1.0));
+        for k in 0..8 {
+            let corner = Vec4::<f64>::new(
+                if k & 1 == 0 { lo.x } else { hi.x } as f64,
+                if k & 2 == 0 { lo.y } else { hi.y } as f64,
+                if k & 4 == 0 { lo.z } else { hi.z } as f64, 1.0);
+            let p = turn64 * corner;
+            for (bound, value) in [(mapped.x, p.x), (mapped.y, p.y), (mapped.z, p.z)] {
+                assert!((bound.lo as f64) <= value && value <= (bound.hi as f64), "{} outside {}", value, bound);
+            }
+        }
+    }
+
+    #[test]
+    fn test_predicates() {
+        type P2 = [i128; 2];
+        type P3 = [i128; 3];
+        let v2 = |p: P2| Vec2::<f64>::new(p[0] as f64, p[1] as f64);
+        let v3 = |p: P3| Vec3::<f64>::new(p[0] as f64, p[1] as f64, p[2] as f64);
+        let sign = |x: f64| if x > 0.0 { 1 } else if x < 0.0 { -1 } else { 0 };
+
+        // Reference determinants in integers, exact for these coordinates.
+        let exact_orient2d = |a: P2, b: P2, c: P2| ((a[0] - c[0]) * (b[1] - c[1]) - (a[1] - c[1]) * (b[0] - c[0])).signum();
+        let minor = |p: &[i128], q: &[i128]| p[0] * q[1] - q[0] * p[1];
+        let exact_orient3d = |a: P3, b: P3, c: P3, d: P3| {
+            let [ad, bd, cd] = [a, b, c].map(|p| [p[0] - d[0], p[1] - d[1], p[2] - d[2]]);
+            (ad[2] * minor(&bd, &cd) + bd[2] * minor(&cd, &ad) + cd[2] * minor(&ad, &bd)).signum()
+        };
+        let exact_incircle = |a: P2, b: P2, c: P2, d: P2| {
+            let [ad, bd, cd] = [a, b, c].map(|p| [p[0] - d[0], p[1] - d[1]]);
+            let lift = |p: P2| p[0] * p[0] + p[1] * p[1];
+            (lift(ad) * minor(&bd, &cd) + lift(bd) * minor(&cd, &ad) + lift(cd) * minor(&ad, &bd)).signum()
+        };
+        let exact_insphere = |a: P3, b: P3, c: P3, d: P3, e: P3| {
+            let [ae, be, ce, de] = [a, b, c, d].map(|p| [p[0] - e[0], p[1] - e[1], p[2] - e[2]]);
+            let lift = |p: P3| p[0] * p[0] + p[1] * p[1] + p[2] * p[2];
+            let (ab, bc, cd, da, ac, bd) = (minor(&ae, &be), minor(&be, &ce), minor(&ce, &de), minor(&de, &ae), minor(&ae, &ce), minor(&be, &de));
+            let abc = ae[2] * bc - be[2] * ac + ce[2] * ab;
+            let bcd = be[2] * cd - ce[2] * bd + de[2] * bc;
+            let cda = ce[2] * da + de[2] * ac + ae[2] * cd;
+            let dab = de[2] * ab + ae[2] * bd + be[2] * da;
+            (lift(de) * abc - lift(ce) * dab + lift(be) * cda - lift(ae) * bcd).signum()
+        };
+
+        assert!(orient2d(Vec2::<f64>::new(0.0, 0.0), Vec2::<f64>::new(1.0, 0.0), Vec2::<f64>::new(0.0, 1.0)) > 0.0);
+        assert!(orient3d(Vec3::<f64>::new(0.0, 0.0, 0.0), Vec3::<f64>::new(1.0, 0.0, 0.0), Vec3::<f64>::new(0.0, 1.0, 0.0), Vec3::<f64>::new(0.0, 0.0, -1.0)) > 0.0);
+        assert!(incircle(Vec2::<f64>::new(1.0, 0.0), Vec2::<f64>::new(0.0, 1.0), Vec2::<f64>::new(-1.0, 0.0), Vec2::<f64>::new(0.1, 0.2)) > 0.0);
+
+        // Points a few ulps off the line through b and c, scaled by 2^53 so
+        // they become integers.
+        let unit = 1i128 << 53;
+        let (b, c) = ([12 * unit, 12 * unit], [24 * unit, 24 * unit]);
+        for i in 0..32 {
+            for j in 0..32 {
+                let a = [unit / 2 + i, unit / 2 + j];
+                let point = Vec2::<f64>::new(0.5 + i as f64 * f64::EPSILON * 0.5, 0.5 + j as f64 * f64::EPSILON * 0.5);
+                assert_eq!(sign(orient2d(point, Vec2::<f64>::new(12.0, 12.0), Vec2::<f64>::new(24.0, 24.0))), exact_orient2d(a, b, c), "{} {}", i, j);
+            }
+        }
+
+        // Cocircular, coplanar and cospherical points far from the origin,
+        // where the plain determinants have large rounding errors, and the
+        // same points nudged by one unit either way.
+        let (k, center) = (1027, [1 << 22, -(3 << 20), 5 << 21]);
+        let circle: Vec<P2> = [[5, 0], [3, 4], [-4, 3], [0, -5], [-3, -4]].iter().map(|o| [center[0] + k * o[0], center[1] + k * o[1]]).collect();
+        let sphere: Vec<P3> = [[5, 0, 0], [0, 5, 0], [0, 0, 5], [3, 4, 0], [0, -3, 4], [-4, 0, -3]].iter()
+            .map(|o| [center[0] + k * o[0], center[1] + k * o[1], center[2] + k * o[2]]).collect();
+        let plane = |x: i128, y: i128| [x, y, 3 * x - 2 * y + (1 << 24)];
+        let flat = [plane(1 << 23, 7), plane(-(1 << 22), 1 << 21), plane(12345, -(1 << 23)), plane(-999, -777)];
+
+        assert_eq!(incircle(v2(circle[0]), v2(circle[1]), v2(circle[2]), v2(circle[3])), 0.0);
+        assert_eq!(orient3d(v3(flat[0]), v3(flat[1]), v3(flat[2]), v3(flat[3])), 0.0);
+
+        let (mut a, mut b) = (sphere[0], sphere[1]);
+        if orient3d(v3(a), v3(b), v3(sphere[2]), v3(sphere[3])) < 0.0 {
+            std::mem::swap(&mut a, &mut b);
+        }
+        assert_eq!(insphere(v3(a), v3(b), v3(sphere[2]), v3(sphere[3]), v3(sphere[4])), 0.0);
+
+        for nudge in [-1, 1] {
+            for axis in 0..3 {
+                let mut d = sphere[4];
+                d[axis] += nudge;
+                let expected = exact_insphere(a, b, sphere[2], sphere[3], d);
+                assert!(expected != 0);
+                assert_eq!(sign(insphere(v3(a), v3(b), v3(sphere[2]), v3(sphere[3]), v3(d))), expected);
+
+                let mut d = flat[3];
+                d[axis] += nudge;
+                assert_eq!(sign(orient3d(v3(flat[0]), v3(flat[1]), v3(flat[2]), v3(d))), exact_orient3d(flat[0], flat[1], flat[2], d));
+            }
+            for axis in 0..2 {
+                let mut d = circle[4];
+                d[axis] += nudge;
+                assert_eq!(sign(incircle(v2(circle[0]), v2(circle[1]), v2(circle[2]), v2(d))), exact_incircle(circle[0], circle[1], circle[2], d));
+            }
+        }
+
+        // Random points close to each degenerate configuration.
+        let mut rng = Lcg(11);
+        let mut jitter = |scale: f32| (rng.range(-scale, scale) as i128).clamp(-4, 4);
+        for _ in 0..200 {
+            let d2 = [circle[4][0] + jitter(4.0), circle[4][1] + jitter(4.0)];
+            assert_eq!(sign(incircle(v2(circle[1]), v2(circle[2]), v2(circle[3]), v2(d2))), exact_incircle(circle[1], circle[2], circle[3], d2));
+            assert_eq!(sign(orient2d(v2(circle[0]), v2(d2), v2(circle[4]))), exact_orient2d(circle[0], d2, circle[4]));
+
+            let d3 = [sphere[5][0] + jitter(4.0), sphere[5][1] + jitter(4.0), sphere[5][2] + jitter(4.0)];
+            assert_eq!(sign(insphere(v3(a), v3(b), v3(sphere[2]), v3(sphere[3]), v3(d3))), exact_insphere(a, b, sphere[2], sphere[3], d3));
+
+            let f = [flat[2][0] + jitter(4.0), flat[2][1] + jitter(4.0), flat[2][2] + jitter(4.0)];
+            assert_eq!(sign(orient3d(v3(flat[0]), v3(flat[1]), v3(f), v3(flat[3]))), exact_orient3d(flat[0], flat[1], f, flat[3]));
+        }
+    }
+}